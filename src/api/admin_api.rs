@@ -0,0 +1,146 @@
+use crate::{
+    models::{
+        api_models::{ApiKeySummary, ApiResult, MintedApiKey, NewApiKeyMessage, ServerResponse},
+        db_models::{ApiKey, ApiKeyScope},
+    },
+    repository::Database,
+    utils::api_keys::{generate_api_key, hash_api_key, Admin, DEFAULT_API_KEY_RATE_LIMIT},
+    utils::sanity_check::decode_ss58_address,
+};
+use log::{error, info};
+use rocket::response::status::Custom;
+use rocket::{http::Status, serde::json::Json, State};
+
+// /admin/api-keys endpoint for minting a new API key
+#[post("/admin/api-keys", data = "<new_api_key_message>")]
+pub async fn create_api_key(
+    db: &State<Database>,
+    _admin: Admin,
+    new_api_key_message: Json<NewApiKeyMessage>,
+) -> ApiResult<MintedApiKey> {
+    if new_api_key_message.name.trim().is_empty() || new_api_key_message.scopes.is_empty() {
+        return Err(Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::new_error(String::from(
+                "API key name and scopes are required.",
+            ))),
+        ));
+    }
+
+    // Keys write the deployments of the addresses they are created for, never of any address
+    let writes_deployments = new_api_key_message
+        .scopes
+        .contains(&ApiKeyScope::WriteDeployments);
+    if writes_deployments == new_api_key_message.addresses.is_empty() {
+        return Err(Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::new_error(String::from(
+                "API key addresses are required by the write_deployments scope, and only used by it.",
+            ))),
+        ));
+    }
+    if let Some(address) = new_api_key_message
+        .addresses
+        .iter()
+        .find(|address| decode_ss58_address(address).is_err())
+    {
+        return Err(Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::new_error(format!(
+                "API key address {} is not a valid SS58 address.",
+                address
+            ))),
+        ));
+    }
+
+    // The key is only returned in this response, the database keeps its hash
+    let (key_id, secret_key) = generate_api_key();
+    let rate_limit_per_minute = new_api_key_message
+        .rate_limit_per_minute
+        .unwrap_or(DEFAULT_API_KEY_RATE_LIMIT);
+    let api_key = ApiKey {
+        addresses: new_api_key_message.addresses.clone(),
+        ..ApiKey::new(
+            &key_id,
+            &hash_api_key(&secret_key),
+            &new_api_key_message.name,
+            new_api_key_message.scopes.clone(),
+            rate_limit_per_minute,
+        )
+    };
+
+    match db.create_api_key(&api_key).await {
+        Ok(_) => {
+            info!(target: "compiler", "API key {} created for {}", &api_key.key_id, &api_key.name);
+            Ok(Json(ServerResponse::new_valid(MintedApiKey {
+                key_id,
+                api_key: secret_key,
+                name: api_key.name,
+                scopes: api_key.scopes,
+                addresses: api_key.addresses,
+                rate_limit_per_minute,
+            })))
+        }
+        Err(_) => {
+            error!(target: "compiler", "There was an error saving the API key {}", &api_key.key_id);
+            Err(Custom(
+                Status::InternalServerError,
+                Json(ServerResponse::new_error(String::from(
+                    "Error creating API key.",
+                ))),
+            ))
+        }
+    }
+}
+
+// /admin/api-keys endpoint for listing the API keys and their usage
+#[get("/admin/api-keys")]
+pub async fn get_api_keys(db: &State<Database>, _admin: Admin) -> ApiResult<Vec<ApiKeySummary>> {
+    match db.get_api_keys().await {
+        Ok(api_keys) => Ok(Json(ServerResponse::new_valid(
+            api_keys.into_iter().map(ApiKeySummary::from).collect(),
+        ))),
+        Err(_) => {
+            error!(target: "compiler", "There was an error fetching the API keys");
+            Err(Custom(
+                Status::InternalServerError,
+                Json(ServerResponse::new_error(String::from(
+                    "Error getting API keys.",
+                ))),
+            ))
+        }
+    }
+}
+
+// /admin/api-keys/<key_id> endpoint for revoking an API key
+#[delete("/admin/api-keys/<key_id>")]
+pub async fn revoke_api_key(
+    db: &State<Database>,
+    _admin: Admin,
+    key_id: String,
+) -> ApiResult<String> {
+    match db.revoke_api_key(&key_id).await {
+        Ok(false) => {
+            info!(target: "compiler", "API key {} was not found", &key_id);
+            Err(Custom(
+                Status::NotFound,
+                Json(ServerResponse::new_error(String::from(
+                    "API key not found.",
+                ))),
+            ))
+        }
+        Ok(_) => {
+            info!(target: "compiler", "API key {} revoked", &key_id);
+            Ok(Json(ServerResponse::new_valid(String::from("ok"))))
+        }
+        Err(_) => {
+            error!(target: "compiler", "There was an error revoking the API key {}", &key_id);
+            Err(Custom(
+                Status::InternalServerError,
+                Json(ServerResponse::new_error(String::from(
+                    "Error revoking API key.",
+                ))),
+            ))
+        }
+    }
+}
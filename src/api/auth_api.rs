@@ -0,0 +1,105 @@
+use crate::{
+    models::api_models::{ApiResult, AuthChallenge, LoginMessage, ServerResponse, SessionToken},
+    utils::address_signature::{unix_timestamp, verify_signature, SignatureType},
+    utils::sanity_check::check_address,
+    utils::session::{challenge_message, ChallengeStore, SessionError, SessionKeys},
+};
+use log::{error, info};
+use rocket::response::status::Custom;
+use rocket::{http::Status, serde::json::Json, State};
+use std::net::IpAddr;
+
+// /auth/challenge endpoint returning a nonce to be signed by the wallet of the address
+#[get("/auth/challenge?<address>")]
+pub fn get_auth_challenge(
+    challenges: &State<ChallengeStore>,
+    client_ip: Option<IpAddr>,
+    address: String,
+) -> ApiResult<AuthChallenge> {
+    if let Err(err) = check_address(&address, None) {
+        return Err(Custom(
+            Status::InternalServerError,
+            Json(ServerResponse::new_error(format!(
+                "Address is not valid: {}.",
+                err
+            ))),
+        ));
+    }
+
+    match challenges.create(&address, client_ip, unix_timestamp()) {
+        Ok(challenge) => {
+            info!(target: "compiler", "Sign-in challenge created for {}", &address);
+            Ok(Json(ServerResponse::new_valid(challenge)))
+        }
+        Err(SessionError::TooManyChallenges) => {
+            error!(target: "compiler", "Too many pending challenges for client {:?}", client_ip);
+            Err(Custom(
+                Status::TooManyRequests,
+                Json(ServerResponse::new_error(String::from(
+                    "Too many pending challenges, answer or wait for the previous ones to expire.",
+                ))),
+            ))
+        }
+        Err(_) => Err(Custom(
+            Status::InternalServerError,
+            Json(ServerResponse::new_error(String::from(
+                "Error creating challenge.",
+            ))),
+        )),
+    }
+}
+
+// /auth/login endpoint exchanging a signed challenge for a session token
+#[post("/auth/login", data = "<login_message>")]
+pub fn login(
+    challenges: &State<ChallengeStore>,
+    keys: &State<SessionKeys>,
+    login_message: Json<LoginMessage>,
+) -> ApiResult<SessionToken> {
+    let unauthorized = |reason: String| {
+        error!(target: "compiler", "Rejecting sign-in of {}: {}", &login_message.address, reason);
+        Custom(
+            Status::Unauthorized,
+            Json(ServerResponse::new_error(format!("{}.", reason))),
+        )
+    };
+
+    let signature_type = match &login_message.signature_type {
+        Some(signature_type) => {
+            SignatureType::parse(signature_type).map_err(|err| unauthorized(err.to_string()))?
+        }
+        None => SignatureType::Sr25519,
+    };
+
+    // The challenge is consumed even if the signature is wrong, a new one must be requested
+    let now = unix_timestamp();
+    challenges
+        .take(&login_message.address, &login_message.nonce, now)
+        .map_err(|err| unauthorized(err.to_string()))?;
+
+    let message = challenge_message(&login_message.address, &login_message.nonce);
+    verify_signature(
+        &login_message.address,
+        message.as_bytes(),
+        &login_message.signature,
+        signature_type,
+    )
+    .map_err(|err| unauthorized(err.to_string()))?;
+
+    match keys.issue(&login_message.address, now) {
+        Ok((token, expires_at)) => {
+            info!(target: "compiler", "Session started for {}", &login_message.address);
+            Ok(Json(ServerResponse::new_valid(SessionToken {
+                token,
+                address: login_message.address.clone(),
+                expires_at,
+            })))
+        }
+        Err(_) => Err(Custom(
+            Status::InternalServerError,
+            Json(ServerResponse::new_error(String::from(
+                "Error issuing session token.",
+            ))),
+        )),
+    }
+}
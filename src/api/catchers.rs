@@ -0,0 +1,30 @@
+use crate::models::api_models::ServerResponse;
+use crate::utils::address_signature::SignatureError;
+use crate::utils::api_keys::ApiKeyError;
+use crate::utils::session::SessionError;
+use rocket::serde::json::Json;
+use rocket::Request;
+
+// Catcher for requests rejected by the API key, session or signature guards, returns the rejection reason
+#[catch(401)]
+pub fn unauthorized(request: &Request) -> Json<ServerResponse<String>> {
+    let reason = if let Some(err) = request.local_cache(|| None::<ApiKeyError>) {
+        format!("{}.", err)
+    } else if let Some(err) = request.local_cache(|| None::<SessionError>) {
+        format!("{}.", err)
+    } else if let Some(err) = request.local_cache(|| None::<SignatureError>) {
+        format!("{}.", err)
+    } else {
+        String::from("Unauthorized.")
+    };
+
+    Json(ServerResponse::new_error(reason))
+}
+
+// Catcher for requests over their rate limit
+#[catch(429)]
+pub fn too_many_requests() -> Json<ServerResponse<String>> {
+    Json(ServerResponse::new_error(String::from(
+        "Rate limit exceeded.",
+    )))
+}
@@ -1,73 +1,230 @@
-use std::sync::mpsc::channel;
 use std::sync::Arc;
 
-use crate::utils::compilation_queue::CompilationRequest;
-use crate::utils::sanity_check::check_address_len;
+use crate::utils::address_signature::SignedJson;
+use crate::utils::api_keys::{Caller, OptionalApiKey};
+use crate::utils::artifacts::{
+    contract_bundle, wasm_content_type, ArtifactResponse, ArtifactResult, IfNoneMatch,
+};
+use crate::utils::compilation_queue::{CompilationRequest, CompilationResult};
+use crate::utils::config::AppConfig;
+use crate::utils::contract_abi::ContractAbi;
+use crate::utils::contract_generator::{generate_contract, ContractSpec};
+use crate::utils::network_registry::NetworkRegistry;
+use crate::utils::rate_limiter::{QuotaReport, RateLimiter};
+use crate::utils::request_id::RequestId;
+use crate::utils::sanity_check::{
+    check_code_size, check_contract_address, check_network, check_toolchain,
+    sanity_check_deployment_addresses,
+};
+use crate::utils::scale_codec::{decode_data, encode_call, CallKind, DecodedData, EncodedCall};
+use crate::utils::size_report::size_report;
+use crate::utils::storage_layout::{
+    check_upgrade, storage_layout, StorageLayout, UpgradeCompatibility,
+};
+use crate::utils::wasm_text::{contract_wat, ContractWat, WatError};
+use crate::utils::wasm_validation::validate_wasm;
 use crate::{
     models::{
         api_models::{
-            DeployMessage, GetDeploymentsMessage, ServerResponse, UpdateDeployMessage,
+            ApiResult, BuildOptions, DecodeMessage, DeployMessage, EncodeCallMessage,
+            GenerateMessage, GetDeploymentsMessage, ServerResponse, UpdateDeployMessage,
             WizardMessage,
         },
-        db_models::{Contract, Deployment},
+        db_models::{ApiKeyScope, Contract, Deployment, Job, JobStatus},
     },
-    repository::mongodb_repo::MongoRepo,
+    repository::Database,
     utils::compilation_queue::CompilationQueue,
     utils::sanity_check::sanity_check_wizard_message,
 };
 use log::{debug, error, info};
+use rocket::http::ContentType;
 use rocket::response::status::Custom;
+use rocket::tokio::sync::oneshot;
 use rocket::{http::Status, serde::json::Json, State};
 use sha2::{Digest, Sha256};
 
 // /contract endpoint for obtaining a new contract compilation
-#[post("/contract", data = "<wizard_message>")]
-pub fn fetch_or_compile_contract(
+#[post("/contract?<legacy_metadata>", data = "<wizard_message>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn fetch_or_compile_contract(
+    compilation_queue: &State<Arc<CompilationQueue>>,
+    config: &State<AppConfig>,
+    db: &State<Database>,
+    networks: &State<NetworkRegistry>,
+    rate_limiter: &State<RateLimiter>,
+    request_id: RequestId,
+    caller: Caller,
+    quota_report: QuotaReport,
+    legacy_metadata: bool,
+    wizard_message: SignedJson<WizardMessage>,
+) -> ApiResult<Contract> {
+    caller.require(ApiKeyScope::Compile)?;
+
+    compile_wizard_message(
+        compilation_queue,
+        config,
+        db,
+        networks,
+        rate_limiter,
+        &request_id,
+        &caller,
+        &quota_report,
+        legacy_metadata,
+        Json(wizard_message.into_inner()),
+    )
+    .await
+}
+
+// /contract/generate endpoint for compiling a contract rendered from the server templates
+#[post("/contract/generate?<legacy_metadata>", data = "<generate_message>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_contract_from_spec(
+    compilation_queue: &State<Arc<CompilationQueue>>,
+    config: &State<AppConfig>,
+    db: &State<Database>,
+    networks: &State<NetworkRegistry>,
+    rate_limiter: &State<RateLimiter>,
+    request_id: RequestId,
+    caller: Caller,
+    quota_report: QuotaReport,
+    legacy_metadata: bool,
+    generate_message: SignedJson<GenerateMessage>,
+) -> ApiResult<Contract> {
+    caller.require(ApiKeyScope::Compile)?;
+
+    let generate_message = generate_message.into_inner();
+    let code = generate_code(&generate_message.spec)?;
+    let wizard_message = WizardMessage {
+        address: generate_message.address,
+        code,
+        features: generate_message.spec.features(),
+        network: generate_message.network,
+        build: generate_message.build,
+    };
+
+    compile_wizard_message(
+        compilation_queue,
+        config,
+        db,
+        networks,
+        rate_limiter,
+        &request_id,
+        &caller,
+        &quota_report,
+        legacy_metadata,
+        Json(wizard_message),
+    )
+    .await
+}
+
+// /contract/generate/source endpoint for the lib.rs rendered from a spec, without compiling it
+#[post("/contract/generate/source", format = "json", data = "<spec>")]
+pub async fn get_generated_source(
+    api_key: OptionalApiKey,
+    spec: Json<ContractSpec>,
+) -> ApiResult<String> {
+    api_key.require(ApiKeyScope::Read)?;
+
+    Ok(Json(ServerResponse::new_valid(generate_code(&spec)?)))
+}
+
+fn generate_code<T>(spec: &ContractSpec) -> Result<String, Custom<Json<ServerResponse<T>>>> {
+    generate_contract(spec).map_err(|err| {
+        info!(target: "compiler", "The contract can not be generated: {}", err);
+        Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::new_error(err)),
+        )
+    })
+}
+
+// Compiles a wizard message, or returns the contract compiled from the same code and options
+#[allow(clippy::too_many_arguments)]
+async fn compile_wizard_message(
     compilation_queue: &State<Arc<CompilationQueue>>,
-    db: &State<MongoRepo>,
+    config: &State<AppConfig>,
+    db: &State<Database>,
+    networks: &State<NetworkRegistry>,
+    rate_limiter: &State<RateLimiter>,
+    request_id: &RequestId,
+    caller: &Caller,
+    quota_report: &QuotaReport,
+    legacy_metadata: bool,
     wizard_message: Json<WizardMessage>,
-) -> Result<Json<ServerResponse<Contract>>, Custom<Json<ServerResponse<Contract>>>> {
+) -> ApiResult<Contract> {
+    // The sender is the authenticated wallet, not the address in the body
+    // Service clients compile on behalf of the address in the body
+    let mut wizard_message = wizard_message;
+    if let Caller::Wallet(authenticated_address) = caller {
+        wizard_message.address = authenticated_address.0.clone();
+    }
+
     // Checking input data
-    sanity_check_wizard_message(&wizard_message)?;
+    sanity_check_wizard_message(&wizard_message, &config.compiler)?;
+    let network = match &wizard_message.network {
+        Some(network) => Some(check_network(networks, network)?),
+        None => None,
+    };
+    if let Some(network) = network {
+        check_toolchain(network, &config.compiler)?;
+    }
 
     // Hashing the contract code to create an unique identifier
-    let code_hash_str = hash_code(&wizard_message.code);
+    let code_hash_str = contract_code_id(&wizard_message);
     debug!(target: "compiler", "hash_code completed");
 
     // Check if contract already exists in DB
-    let contract_on_db = db.get_contract_by_hash(&code_hash_str);
+    let contract_on_db = db.get_contract_by_hash(&code_hash_str).await;
     debug!(target: "compiler", "get_contract_by_hash completed");
 
     // If contract already exists in DB, return it
     match contract_on_db {
-        Ok(contract) => match contract {
-            Some(mut contract) => {
-                info!(target: "compiler", "Contract existing in the db with id: {:?}", &contract.id);
-                contract.id = None;
-                return Ok(Json(ServerResponse::new_valid(contract)));
+        Ok(Some(mut contract)) => {
+            info!(target: "compiler", "Contract existing in the db with id: {:?}", &contract.id);
+            contract.id = None;
+
+            // The validation is not stored, the target network can differ from the first compilation
+            let validation = validate_wasm(&contract.wasm, networks, network);
+            if !validation.is_valid() {
+                error!(target: "compiler", "Contract {} is not valid for the network", &contract.code_id);
+                return Err(Custom(
+                    Status::UnprocessableEntity,
+                    Json(ServerResponse::new_error(validation.error_message())),
+                ));
             }
-            None => (),
-        },
+            contract.validation = Some(validation);
+            contract.size_report = size_report(None, &contract.wasm).ok();
+            return Ok(Json(ServerResponse::new_valid(
+                contract.with_legacy_metadata(legacy_metadata),
+            )));
+        }
+        Ok(None) => (),
         Err(_) => {
             error!(target: "compiler", "Error getting contract from db");
         }
     }
 
     // If contract does not exist in DB, create it
+    // Only actual compilations count towards the daily quota
+    rate_limiter
+        .check_compile_quota(db, caller, &wizard_message.address, quota_report)
+        .await?;
 
-    // Creating a channel to communicate with the compilation thread
-    let (tx, rx) = channel::<Result<Contract, String>>();
+    // Creating a channel to receive the result from the compilation thread
+    let (tx, rx) = oneshot::channel::<CompilationResult>();
 
     // Sending the compilation request to the compilation thread
+    let address = wizard_message.address.clone();
     let compilation_request = CompilationRequest {
         wizard_message: wizard_message.into_inner(),
         code_id: code_hash_str.clone(),
-        tx: tx.clone(),
+        request_id: request_id.0.clone(),
+        tx,
     };
     compilation_queue.add_request(compilation_request);
 
-    // Waiting for the compilation thread to finish
-    let comp_msg = rx.recv();
+    // Waiting for the compilation thread to finish, the worker is free meanwhile
+    let comp_msg = rx.await;
 
     if comp_msg.is_err() {
         error!(target: "compiler", "Error receiving compilation result from channel");
@@ -80,12 +237,33 @@ pub fn fetch_or_compile_contract(
     }
 
     // Getting the compilation result
-    let contract = comp_msg.expect("This will never panic because we checked for errors before");
+    let comp_result = comp_msg.expect("This will never panic because we checked for errors before");
+
+    // Storing the job with its build log, the job id is the id of the request
+    let job_status = match comp_result.contract {
+        Ok(_) => JobStatus::Succeeded,
+        Err(_) => JobStatus::Failed,
+    };
+    let job = Job::new(
+        &request_id.0,
+        &code_hash_str,
+        &address,
+        job_status,
+        &comp_result.build_log,
+    );
+    match db.create_job(&job).await {
+        Ok(_) => {
+            info!(target: "compiler", "Job {} saved in the database", &job.job_id);
+        }
+        Err(_) => {
+            error!(target: "compiler", "There was an error saving the job {} in the database", &job.job_id);
+        }
+    };
 
     // Checking if compilation was successful
-    match contract {
+    match comp_result.contract {
         Ok(contract_unwrapped) => {
-            let contract_save_result = db.create_contract(&contract_unwrapped);
+            let contract_save_result = db.create_contract(&contract_unwrapped).await;
             info!(target: "compiler",
                 "Contract {} successfully compiled", &contract_unwrapped.code_id
             );
@@ -100,41 +278,58 @@ pub fn fetch_or_compile_contract(
                 }
             };
 
-            return Ok(Json(ServerResponse::new_valid(contract_unwrapped)));
+            Ok(Json(ServerResponse::new_valid(
+                contract_unwrapped.with_legacy_metadata(legacy_metadata),
+            )))
         }
         // If compilation failed, return the error
         Err(error_msg) => {
-            error!(target: "compiler", "There was an error saving the contract {} in the database", &code_hash_str);
+            error!(target: "compiler", "There was an error compiling the contract {}", &code_hash_str);
 
-            return Err(Custom(
+            Err(Custom(
                 Status::InternalServerError,
                 Json(ServerResponse::new_error(error_msg)),
-            ));
+            ))
         }
-    };
+    }
 }
 
 // /deployments endpoint for storing a new deployment
 #[post("/deployments", data = "<deploy_message>")]
-pub fn store_deployment(
-    db: &State<MongoRepo>,
-    deploy_message: Json<DeployMessage>,
-) -> Result<Json<ServerResponse<String>>, Custom<Json<ServerResponse<String>>>> {
-    // Check the address is valid
-    if check_address_len(&deploy_message.user_address).is_err()
-        || check_address_len(&deploy_message.contract_address).is_err()
-    {
-        return Err(Custom(
-            Status::InternalServerError,
-            Json(ServerResponse::new_error(String::from(
-                "Invalid address length",
-            ))),
-        ));
+pub async fn store_deployment(
+    db: &State<Database>,
+    networks: &State<NetworkRegistry>,
+    caller: Caller,
+    deploy_message: SignedJson<DeployMessage>,
+) -> ApiResult<String> {
+    // Check the network is supported and the addresses are valid for it
+    let network = check_network(networks, &deploy_message.network)?;
+    sanity_check_deployment_addresses(
+        &deploy_message.user_address,
+        &deploy_message.contract_address,
+        network,
+    )?;
+
+    // Only the owner of the user address can store deployments for it
+    caller.check_owns(&deploy_message.user_address)?;
+
+    // Check the code fits in the network when the contract was compiled by this service
+    let contract = db
+        .get_contract_by_hash(&deploy_message.code_id)
+        .await
+        .ok()
+        .flatten();
+    if let Some(contract) = &contract {
+        check_code_size(contract.wasm.len(), network)?;
     }
 
-    // Generating a new deployment structure and storing in db
-    let deployment = Deployment::new(&deploy_message);
-    let deployment_save_result = db.create_deployment(&deployment);
+    // Check the claimed address is the one of the contract instantiated by the user
+    check_contract_address(&deploy_message, contract.as_ref(), network)?;
+
+    // Generating a new deployment structure with the canonical network id and storing in db
+    let mut deployment = Deployment::new(&deploy_message);
+    deployment.network = network.id.clone();
+    let deployment_save_result = db.create_deployment(&deployment).await;
 
     info!(target: "compiler", "Storing new deployment for user {} for contract {} in network {}", &deployment.user_address , &deployment.code_id, &deployment.network);
 
@@ -158,28 +353,42 @@ pub fn store_deployment(
 }
 
 #[patch("/deployments", data = "<update_deploy_message>")]
-pub fn update_deployment(
-    db: &State<MongoRepo>,
-    update_deploy_message: Json<UpdateDeployMessage>,
-) -> Result<Json<ServerResponse<String>>, Custom<Json<ServerResponse<String>>>> {
-    // Check the address is valid
-    if check_address_len(&update_deploy_message.user_address).is_err()
-        || check_address_len(&update_deploy_message.contract_address).is_err()
-    {
-        return Err(Custom(
-            Status::InternalServerError,
-            Json(ServerResponse::new_error(String::from(
-                "Invalid address length",
-            ))),
-        ));
-    }
+pub async fn update_deployment(
+    db: &State<Database>,
+    networks: &State<NetworkRegistry>,
+    caller: Caller,
+    update_deploy_message: SignedJson<UpdateDeployMessage>,
+) -> ApiResult<String> {
+    // Check the network is supported and the addresses are valid for it
+    let network = check_network(networks, &update_deploy_message.network)?;
+    sanity_check_deployment_addresses(
+        &update_deploy_message.user_address,
+        &update_deploy_message.contract_address,
+        network,
+    )?;
+
+    // Only the owner of the user address can update its deployments
+    caller.check_owns(&update_deploy_message.user_address)?;
+
+    // Deployments are stored with the canonical network id
+    let mut update_deploy_message = update_deploy_message.into_inner();
+    update_deploy_message.network = network.id.clone();
 
     // Updating the deployment in db
-    let deployment_update_result = db.update_deployment(&update_deploy_message);
+    let deployment_update_result = db.update_deployment(&update_deploy_message).await;
     info!(target: "compiler", "Updating deployment {} for user {} in network {}", &update_deploy_message.contract_address, &update_deploy_message.user_address, &update_deploy_message.network);
 
     // Evaluate the result of the update operation
     match deployment_update_result {
+        Ok(false) => {
+            info!(target: "compiler", "Deployment {} was not found", &update_deploy_message.contract_address);
+            Err(Custom(
+                Status::NotFound,
+                Json(ServerResponse::new_error(String::from(
+                    "Deployment not found.",
+                ))),
+            ))
+        }
         Ok(_) => {
             info!(target: "compiler", "Deployment {} updated in the database", &update_deploy_message.contract_address);
             Ok(Json(ServerResponse::new_valid(String::from("ok"))))
@@ -199,19 +408,29 @@ pub fn update_deployment(
 
 // /deployments endpoint for fetching a deployment
 #[get("/deployments?<user_address>&<network>&<contract_address>")]
-pub fn get_contract_deployments(
-    db: &State<MongoRepo>,
+pub async fn get_contract_deployments(
+    db: &State<Database>,
+    networks: &State<NetworkRegistry>,
+    api_key: OptionalApiKey,
     user_address: String,
     network: Option<String>,
     contract_address: Option<String>,
-) -> Result<Json<ServerResponse<Vec<Deployment>>>, Custom<Json<ServerResponse<Vec<Deployment>>>>> {
+) -> ApiResult<Vec<Deployment>> {
+    api_key.require(ApiKeyScope::Read)?;
+
+    // Filtering by the canonical network id
+    let network = match network {
+        Some(network) if !network.is_empty() => Some(check_network(networks, &network)?.id.clone()),
+        _ => None,
+    };
+
     // Creating structure and fetching the deployments from db
     let get_deployments = GetDeploymentsMessage {
         user_address: user_address.clone(),
         network,
         contract_address,
     };
-    let deployments = db.get_deployments(&get_deployments);
+    let deployments = db.get_deployments(&get_deployments).await;
 
     // Evaluate the result of the fetch operation
     match deployments {
@@ -232,14 +451,18 @@ pub fn get_contract_deployments(
 }
 
 // /contract-metadata endpoint for fetching a contract's metadata
-#[get("/contract?<code_id>&<wasm>")]
-pub fn get_contract(
-    db: &State<MongoRepo>,
+#[get("/contract?<code_id>&<wasm>&<legacy_metadata>")]
+pub async fn get_contract(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
     code_id: String,
     wasm: bool,
-) -> Result<Json<ServerResponse<Contract>>, Custom<Json<ServerResponse<Contract>>>> {
+    legacy_metadata: bool,
+) -> ApiResult<Contract> {
+    api_key.require(ApiKeyScope::Read)?;
+
     // Fetching metadata from code_id
-    let db_result = db.get_contract_by_hash(&code_id);
+    let db_result = db.get_contract_by_hash(&code_id).await;
 
     // Evaluate the result of the fetch operation and building the response on each case
     match db_result {
@@ -263,10 +486,15 @@ pub fn get_contract(
                     code_id: contract.code_id,
                     metadata: contract.metadata,
                     wasm: vec![], // Empty wasm
+                    legacy_metadata: None,
+                    validation: None,
+                    size_report: None,
                 };
             }
 
-            return Ok(Json(ServerResponse::new_valid(contract)));
+            Ok(Json(ServerResponse::new_valid(
+                contract.with_legacy_metadata(legacy_metadata),
+            )))
         }
         Err(_) => {
             error!(target: "compiler", "There was DB error fetching metadata for {}", &code_id);
@@ -280,6 +508,293 @@ pub fn get_contract(
     }
 }
 
+// /contract/<code_id>/wasm endpoint for downloading the compiled wasm
+#[get("/contract/<code_id>/wasm")]
+pub async fn get_contract_wasm(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    if_none_match: IfNoneMatch,
+    code_id: String,
+) -> ArtifactResult {
+    api_key.require(ApiKeyScope::Read)?;
+
+    let contract = find_contract(db, &code_id).await?;
+    Ok(ArtifactResponse::new(
+        wasm_content_type(),
+        format!("{}.wasm", code_id),
+        contract.wasm,
+        &if_none_match,
+    ))
+}
+
+// /contract/<code_id>/metadata.json endpoint for downloading the contract metadata
+#[get("/contract/<code_id>/metadata.json")]
+pub async fn get_contract_metadata(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    if_none_match: IfNoneMatch,
+    code_id: String,
+) -> ArtifactResult {
+    api_key.require(ApiKeyScope::Read)?;
+
+    let contract = find_contract(db, &code_id).await?;
+    Ok(ArtifactResponse::new(
+        ContentType::JSON,
+        format!("{}.json", code_id),
+        contract.metadata.to_string().into_bytes(),
+        &if_none_match,
+    ))
+}
+
+// /contract/<code_id>/bundle.contract endpoint for downloading the cargo-contract bundle
+#[get("/contract/<code_id>/bundle.contract")]
+pub async fn get_contract_bundle(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    if_none_match: IfNoneMatch,
+    code_id: String,
+) -> ArtifactResult {
+    api_key.require(ApiKeyScope::Read)?;
+
+    let contract = find_contract(db, &code_id).await?;
+    match contract_bundle(&contract) {
+        Ok(bundle) => Ok(ArtifactResponse::new(
+            ContentType::JSON,
+            format!("{}.contract", code_id),
+            bundle,
+            &if_none_match,
+        )),
+        Err(err) => {
+            error!(target: "compiler", "Error building the bundle of {}: {}", &code_id, err);
+            Err(Custom(
+                Status::InternalServerError,
+                Json(ServerResponse::new_error(String::from(
+                    "Error building contract bundle.",
+                ))),
+            ))
+        }
+    }
+}
+
+// /contract/<code_id>/abi endpoint for fetching the interface of a contract
+#[get("/contract/<code_id>/abi")]
+pub async fn get_contract_abi(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    code_id: String,
+) -> ApiResult<ContractAbi> {
+    api_key.require(ApiKeyScope::Read)?;
+
+    let contract = find_contract(db, &code_id).await?;
+    match ContractAbi::from_metadata(&contract.metadata) {
+        Ok(abi) => Ok(Json(ServerResponse::new_valid(abi))),
+        Err(err) => {
+            info!(target: "compiler", "The metadata of {} can not be read: {}", &code_id, err);
+            Err(Custom(
+                Status::UnprocessableEntity,
+                Json(ServerResponse::new_error(format!(
+                    "Unsupported contract metadata. {}",
+                    err
+                ))),
+            ))
+        }
+    }
+}
+
+// /contract/<code_id>/encode endpoint for building the call data of a constructor or a message
+#[post(
+    "/contract/<code_id>/encode",
+    format = "json",
+    data = "<encode_message>"
+)]
+pub async fn encode_contract_call(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    code_id: String,
+    encode_message: Json<EncodeCallMessage>,
+) -> ApiResult<EncodedCall> {
+    api_key.require(ApiKeyScope::Read)?;
+
+    let (kind, label) = match (&encode_message.constructor, &encode_message.message) {
+        (Some(label), None) => (CallKind::Constructor, label),
+        (None, Some(label)) => (CallKind::Message, label),
+        _ => {
+            return Err(codec_error(String::from(
+                "Either a constructor or a message is required",
+            )))
+        }
+    };
+
+    let contract = find_contract(db, &code_id).await?;
+    match encode_call(&contract.metadata, kind, label, &encode_message.args) {
+        Ok(encoded_call) => Ok(Json(ServerResponse::new_valid(encoded_call))),
+        Err(err) => {
+            info!(target: "compiler", "The call to {} of {} can not be encoded: {}", label, &code_id, err);
+            Err(codec_error(err))
+        }
+    }
+}
+
+// /contract/<code_id>/decode endpoint for decoding call data, return values and events
+#[post(
+    "/contract/<code_id>/decode",
+    format = "json",
+    data = "<decode_message>"
+)]
+pub async fn decode_contract_data(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    code_id: String,
+    decode_message: Json<DecodeMessage>,
+) -> ApiResult<DecodedData> {
+    api_key.require(ApiKeyScope::Read)?;
+
+    let contract = find_contract(db, &code_id).await?;
+    match decode_data(
+        &contract.metadata,
+        decode_message.kind,
+        decode_message.label.as_deref(),
+        &decode_message.data,
+    ) {
+        Ok(decoded) => Ok(Json(ServerResponse::new_valid(decoded))),
+        Err(err) => {
+            info!(target: "compiler", "The data of {} can not be decoded: {}", &code_id, err);
+            Err(codec_error(err))
+        }
+    }
+}
+
+// /contract/<code_id>/storage endpoint for the storage layout of a contract
+#[get("/contract/<code_id>/storage")]
+pub async fn get_contract_storage(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    code_id: String,
+) -> ApiResult<StorageLayout> {
+    api_key.require(ApiKeyScope::Read)?;
+
+    let contract = find_contract(db, &code_id).await?;
+    match storage_layout(&contract.metadata) {
+        Ok(layout) => Ok(Json(ServerResponse::new_valid(layout))),
+        Err(err) => {
+            info!(target: "compiler", "The storage layout of {} can not be read: {}", &code_id, err);
+            Err(layout_error(err))
+        }
+    }
+}
+
+// /contract/<code_id>/upgrade/<new_code_id> endpoint for checking the storage of a contract
+// is still read by the new code after set_code_hash
+#[get("/contract/<code_id>/upgrade/<new_code_id>")]
+pub async fn check_contract_upgrade(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    code_id: String,
+    new_code_id: String,
+) -> ApiResult<UpgradeCompatibility> {
+    api_key.require(ApiKeyScope::Read)?;
+
+    let contract = find_contract(db, &code_id).await?;
+    let new_contract = find_contract(db, &new_code_id).await?;
+    match check_upgrade(&contract.metadata, &new_contract.metadata) {
+        Ok(compatibility) => Ok(Json(ServerResponse::new_valid(compatibility))),
+        Err(err) => {
+            info!(target: "compiler", "The upgrade from {} to {} can not be checked: {}", &code_id, &new_code_id, err);
+            Err(layout_error(err))
+        }
+    }
+}
+
+// /contract/<code_id>/wat endpoint for the text format of the wasm, optionally of a single function
+#[get("/contract/<code_id>/wat?<function>")]
+pub async fn get_contract_wat(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    code_id: String,
+    function: Option<String>,
+) -> ApiResult<ContractWat> {
+    api_key.require(ApiKeyScope::Read)?;
+
+    let contract = find_contract(db, &code_id).await?;
+    match contract_wat(&contract.wasm, &contract.metadata, function.as_deref()) {
+        Ok(contract_wat) => Ok(Json(ServerResponse::new_valid(contract_wat))),
+        Err(WatError::FunctionNotFound(function)) => {
+            info!(target: "compiler", "Function {} was not found in {}", function, &code_id);
+            Err(Custom(
+                Status::NotFound,
+                Json(ServerResponse::new_error(format!(
+                    "Function {} not found.",
+                    function
+                ))),
+            ))
+        }
+        Err(WatError::InvalidWasm(err)) => {
+            error!(target: "compiler", "The wasm of {} can not be disassembled: {}", &code_id, err);
+            Err(Custom(
+                Status::UnprocessableEntity,
+                Json(ServerResponse::new_error(format!(
+                    "Invalid contract wasm. {}",
+                    err
+                ))),
+            ))
+        }
+    }
+}
+
+fn layout_error<T>(err: String) -> Custom<Json<ServerResponse<T>>> {
+    Custom(
+        Status::UnprocessableEntity,
+        Json(ServerResponse::new_error(format!(
+            "Unsupported contract metadata. {}",
+            err
+        ))),
+    )
+}
+
+fn codec_error<T>(err: String) -> Custom<Json<ServerResponse<T>>> {
+    Custom(
+        Status::UnprocessableEntity,
+        Json(ServerResponse::new_error(err)),
+    )
+}
+
+// Fetches a stored contract, a missing contract is a not found error
+async fn find_contract<T>(
+    db: &Database,
+    code_id: &str,
+) -> Result<Contract, Custom<Json<ServerResponse<T>>>> {
+    match db.get_contract_by_hash(code_id).await {
+        Ok(Some(contract)) => Ok(contract),
+        Ok(None) => {
+            info!(target: "compiler", "Contract was not found for {}", code_id);
+            Err(Custom(
+                Status::NotFound,
+                Json(ServerResponse::new_error(String::from(
+                    "Contract not found.",
+                ))),
+            ))
+        }
+        Err(_) => {
+            error!(target: "compiler", "There was DB error fetching contract {}", code_id);
+            Err(Custom(
+                Status::InternalServerError,
+                Json(ServerResponse::new_error(String::from(
+                    "Error getting contract.",
+                ))),
+            ))
+        }
+    }
+}
+
+// Code id of a compilation, the build options are hashed with the code when they are not the defaults
+pub fn contract_code_id(wizard_message: &WizardMessage) -> String {
+    if wizard_message.build == BuildOptions::default() {
+        return hash_code(&wizard_message.code);
+    }
+    let build_options = serde_json::to_string(&wizard_message.build).unwrap_or_default();
+    hash_code(&format!("{}\n{}", wizard_message.code, build_options))
+}
+
 // This function creates the hash of the contract file
 pub fn hash_code(code: &String) -> String {
     let mut hasher = Sha256::new();
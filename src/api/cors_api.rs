@@ -0,0 +1,7 @@
+use rocket::http::Status;
+
+// Answers the CORS preflight requests of every route, the CORS fairing adds the headers
+#[options("/<_..>")]
+pub fn preflight() -> Status {
+    Status::NoContent
+}
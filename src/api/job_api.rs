@@ -0,0 +1,48 @@
+use crate::{
+    models::{
+        api_models::{ApiResult, ServerResponse},
+        db_models::{ApiKeyScope, Job},
+    },
+    repository::Database,
+    utils::api_keys::OptionalApiKey,
+};
+use log::{error, info};
+use rocket::response::status::Custom;
+use rocket::{http::Status, serde::json::Json, State};
+
+// /jobs/<job_id>/log endpoint for fetching the build log of a compilation job
+#[get("/jobs/<job_id>/log")]
+pub async fn get_job_log(
+    db: &State<Database>,
+    api_key: OptionalApiKey,
+    job_id: String,
+) -> ApiResult<Job> {
+    api_key.require(ApiKeyScope::Read)?;
+
+    // Fetching the job from the db
+    let db_result = db.get_job(&job_id).await;
+
+    // Evaluate the result of the fetch operation
+    match db_result {
+        Ok(Some(job)) => {
+            info!(target: "compiler", "Build log fetched for job {}", &job_id);
+            Ok(Json(ServerResponse::new_valid(job)))
+        }
+        Ok(None) => {
+            info!(target: "compiler", "Job was not found for {}", &job_id);
+            Err(Custom(
+                Status::NotFound,
+                Json(ServerResponse::new_error(String::from("Job not found."))),
+            ))
+        }
+        Err(_) => {
+            error!(target: "compiler", "There was DB error fetching job {}", &job_id);
+            Err(Custom(
+                Status::InternalServerError,
+                Json(ServerResponse::new_error(String::from(
+                    "Error getting job.",
+                ))),
+            ))
+        }
+    }
+}
@@ -1 +1,7 @@
+pub mod admin_api;
+pub mod auth_api;
+pub mod catchers;
 pub mod contract_api;
+pub mod cors_api;
+pub mod job_api;
+pub mod network_api;
@@ -0,0 +1,13 @@
+use crate::{
+    models::api_models::{ApiResult, ServerResponse},
+    utils::network_registry::{Network, NetworkRegistry},
+};
+use log::info;
+use rocket::{serde::json::Json, State};
+
+// /networks endpoint for listing the supported networks
+#[get("/networks")]
+pub fn get_networks(networks: &State<NetworkRegistry>) -> ApiResult<Vec<Network>> {
+    info!(target: "compiler", "Listing {} networks", networks.networks.len());
+    Ok(Json(ServerResponse::new_valid(networks.networks.clone())))
+}
@@ -1,3 +1,6 @@
+// Rocket handlers return the full error response in the Err variant
+#![allow(clippy::result_large_err)]
+
 mod api;
 mod models;
 mod repository;
@@ -9,86 +12,138 @@ use dotenv::dotenv;
 #[macro_use]
 extern crate rocket;
 
+use api::admin_api::{create_api_key, get_api_keys, revoke_api_key};
+use api::auth_api::{get_auth_challenge, login};
+use api::catchers::{too_many_requests, unauthorized};
 use api::contract_api::{
-    fetch_or_compile_contract, get_contract, get_contract_deployments, store_deployment,
-    update_deployment,
+    check_contract_upgrade, decode_contract_data, encode_contract_call, fetch_or_compile_contract,
+    generate_contract_from_spec, get_contract, get_contract_abi, get_contract_bundle,
+    get_contract_deployments, get_contract_metadata, get_contract_storage, get_contract_wasm,
+    get_contract_wat, get_generated_source, store_deployment, update_deployment,
 };
-use repository::mongodb_repo::MongoRepo;
+use api::cors_api::preflight;
+use api::job_api::get_job_log;
+use api::network_api::get_networks;
 use rocket::fairing::AdHoc;
-use std::{
-    sync::{atomic::AtomicBool, Arc},
-    thread,
-};
+use rocket::figment::Figment;
+use rocket::{Build, Rocket};
+use std::sync::{atomic::AtomicBool, Arc};
 use utils::compilation_queue::CompilationQueue;
-use utils::compiler::Compiler;
+use utils::compiler::{Compiler, CompilerThread};
 
 use log::{debug, error, info};
 
-use utils::cors::CORS;
+use utils::address_signature::NonceStore;
+use utils::api_keys::ApiKeyRateLimiter;
+use utils::config::AppConfig;
+use utils::cors::Cors;
+use utils::network_registry::NetworkRegistry;
+use utils::rate_limiter::{rate_limited, RateLimitFairing};
+use utils::request_id::{log_scoped, RequestIdFairing, RequestIdLogger};
+use utils::session::ChallengeStore;
+
+// Logging configuration used when LOGGING_CONFIG is not set
+const DEFAULT_LOGGING_CONFIG: &str = "logging_config.yaml";
 
 // Rocket launching the server in rocket function
 #[launch]
 fn rocket() -> _ {
+    // Loading env variables
+    dotenv().ok();
+
     // Creating the logger and checking it's ok.
-    let logger = log4rs::init_file("logging_config.yaml", Default::default());
+    let logging_config =
+        std::env::var("LOGGING_CONFIG").unwrap_or_else(|_| DEFAULT_LOGGING_CONFIG.to_string());
+    // The records are tagged with the request id of the task writing them
+    let logger = log4rs::config::load_config_file(&logging_config, Default::default())
+        .map(log4rs::Logger::new)
+        .map_err(|err| err.to_string())
+        .and_then(|logger| {
+            let max_level = logger.max_log_level();
+            log::set_boxed_logger(Box::new(RequestIdLogger(logger)))
+                .map(|_| log::set_max_level(max_level))
+                .map_err(|err| err.to_string())
+        });
     if logger.is_err() {
         error!("Error initializing logger");
     } else {
-        info!(target: "compiler", "Logger Initialized");
+        info!(target: "compiler", "Logger Initialized with {}", logging_config);
     }
-
-    // Loading env variables
-    dotenv().ok();
     debug!(target: "compiler", "dotenv loaded");
 
+    server(AppConfig::figment(rocket::Config::figment()))
+}
+
+// Builds the server from the configuration figment
+pub fn server(figment: Figment) -> Rocket<Build> {
     // Creating compilation queue
     let queue = CompilationQueue::new();
     let compilation_queue = Arc::new(queue);
-    let compilation_queue_clone = compilation_queue.clone();
 
     // Setting shutdown flag to perform operations when the server is shutting down
     let shutdown_flag = Arc::new(AtomicBool::new(false));
 
-    // Creating compiler instance and running a new thread
-    let compiler = Compiler::init(compilation_queue_clone, shutdown_flag.clone());
-    let compiler_thread = thread::spawn(move || {
-        compiler.start();
-    });
-    debug!(target: "compiler", "compiler initialized");
-
-    // Initializing mongo
-    let db = MongoRepo::init();
-    debug!(target: "compiler", "mongo repo initialized");
-
-    // Initializing the server
-    rocket::build()
-        .manage(compilation_queue)
-        .manage(db)
+    // Initializing the server, the configuration is loaded before the storage and the compiler
+    rocket::custom(figment)
+        .manage(compilation_queue.clone())
         .manage(shutdown_flag.clone())
+        .manage(NonceStore::new())
+        .manage(ChallengeStore::new())
+        .manage(ApiKeyRateLimiter::new())
         .mount(
             "/",
-            routes![
+            rate_limited(log_scoped(routes![
                 fetch_or_compile_contract,
+                generate_contract_from_spec,
+                get_generated_source,
                 store_deployment,
                 update_deployment,
                 get_contract_deployments,
-                get_contract
-            ],
+                get_contract,
+                get_contract_wasm,
+                get_contract_metadata,
+                get_contract_bundle,
+                get_contract_abi,
+                encode_contract_call,
+                decode_contract_data,
+                get_contract_wat,
+                get_contract_storage,
+                check_contract_upgrade,
+                get_job_log,
+                get_networks,
+                get_auth_challenge,
+                login,
+                create_api_key,
+                get_api_keys,
+                revoke_api_key,
+                preflight
+            ])),
         )
-        .attach(AdHoc::on_shutdown("Shutdown Handler", |_| {
+        .register("/", catchers![unauthorized, too_many_requests])
+        .attach(AppConfig::fairing())
+        .attach(NetworkRegistry::fairing())
+        .attach(repository::fairing())
+        .attach(Compiler::fairing(compilation_queue, shutdown_flag.clone()))
+        .attach(AdHoc::on_shutdown("Shutdown Handler", |rocket| {
+            let compiler_thread = rocket
+                .state::<CompilerThread>()
+                .and_then(|thread| thread.0.lock().ok()?.take());
             Box::pin(async move {
                 info!(target: "compiler", "Shutting down");
                 shutdown_flag.store(true, std::sync::atomic::Ordering::Relaxed);
-                let join_res = compiler_thread.join();
 
-                if join_res.is_err() {
-                    error!(target: "compiler", "Error joining compiler thread");
+                if let Some(compiler_thread) = compiler_thread {
+                    if compiler_thread.join().is_err() {
+                        error!(target: "compiler", "Error joining compiler thread");
+                    }
                 }
 
                 info!(target: "compiler", "Shutdown complete");
             })
         }))
-        .attach(CORS)
+        .attach(RateLimitFairing)
+        .attach(Cors)
+        .attach(RequestIdFairing)
 }
 #[cfg(test)]
 #[path = "./tests/main_post_contract_tests.rs"]
@@ -98,6 +153,10 @@ mod main_post_contract_test;
 #[path = "./tests/main_get_contract_tests.rs"]
 mod main_get_contract_test;
 
+#[cfg(test)]
+#[path = "./tests/main_get_contract_artifacts_tests.rs"]
+mod main_get_contract_artifacts_test;
+
 #[cfg(test)]
 #[path = "./tests/main_post_deployments_tests.rs"]
 mod main_post_deployments_test;
@@ -105,3 +164,19 @@ mod main_post_deployments_test;
 #[cfg(test)]
 #[path = "./tests/main_get_deployments_tests.rs"]
 mod main_get_deployments_test;
+
+#[cfg(test)]
+#[path = "./tests/main_auth_tests.rs"]
+mod main_auth_test;
+
+#[cfg(test)]
+#[path = "./tests/main_admin_tests.rs"]
+mod main_admin_test;
+
+#[cfg(test)]
+#[path = "./tests/test_signer.rs"]
+mod test_signer;
+
+#[cfg(test)]
+#[path = "./tests/test_server.rs"]
+mod test_server;
@@ -1,5 +1,13 @@
+use rocket::{response::status::Custom, serde::json::Json};
 use serde::{Deserialize, Serialize};
 
+use super::db_models::{ApiKey, ApiKeyScope};
+use crate::utils::contract_generator::ContractSpec;
+use crate::utils::scale_codec::DecodeKind;
+
+// Result returned by the API handlers, the error variant carries the status code
+pub type ApiResult<T> = Result<Json<ServerResponse<T>>, Custom<Json<ServerResponse<T>>>>;
+
 // Generic server response
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct ServerResponse<T> {
@@ -29,9 +37,62 @@ pub struct ServerError {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WizardMessage {
+    // Replaced by the authenticated address of the request
+    #[serde(default)]
     pub address: String,
     pub code: String,
     pub features: Vec<String>,
+    // Network the contract targets, its code size limit is enforced by the wasm validation
+    #[serde(default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub build: BuildOptions,
+}
+
+// Contract generated from the server templates and compiled as a wizard message
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerateMessage {
+    // Replaced by the authenticated address of the request
+    #[serde(default)]
+    pub address: String,
+    #[serde(flatten)]
+    pub spec: ContractSpec,
+    #[serde(default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub build: BuildOptions,
+}
+
+// Options of the cargo contract build, the defaults are the ones of cargo-contract
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct BuildOptions {
+    pub optimization: OptimizationLevel,
+    pub keep_debug_names: bool,
+}
+
+// wasm-opt passes run on the linked wasm
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum OptimizationLevel {
+    // Optimizes for size, the smallest upload deposit
+    #[default]
+    #[serde(rename = "Oz")]
+    Size,
+    #[serde(rename = "O3")]
+    Speed,
+    #[serde(rename = "none")]
+    Disabled,
+}
+
+impl OptimizationLevel {
+    // Value of the --optimization-passes option of cargo contract
+    pub fn passes(&self) -> &'static str {
+        match self {
+            OptimizationLevel::Size => "z",
+            OptimizationLevel::Speed => "3",
+            OptimizationLevel::Disabled => "0",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,9 +106,12 @@ pub struct DeployMessage {
     pub date: String,
     pub contract_type: String,
     pub external_abi: Option<String>,
+    // Hex encoded instantiation salt and constructor call data, to verify the contract address
+    pub salt: Option<String>,
+    pub constructor_data: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateDeployMessage {
     pub contract_address: String,
     pub network: String,
@@ -56,9 +120,100 @@ pub struct UpdateDeployMessage {
     pub hidden: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GetDeploymentsMessage {
     pub user_address: String,
     pub network: Option<String>,
     pub contract_address: Option<String>,
 }
+
+// Challenge to be signed by the wallet to get a session token
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AuthChallenge {
+    pub address: String,
+    pub nonce: String,
+    pub message: String,
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginMessage {
+    pub address: String,
+    pub nonce: String,
+    pub signature: String,
+    pub signature_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SessionToken {
+    pub token: String,
+    pub address: String,
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewApiKeyMessage {
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    // Required by the write_deployments scope
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+// Key returned once when it is minted, only its hash is stored
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct MintedApiKey {
+    pub key_id: String,
+    pub api_key: String,
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub addresses: Vec<String>,
+    pub rate_limit_per_minute: u32,
+}
+
+// Key listed by the admin routes, its hash is never returned
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApiKeySummary {
+    pub key_id: String,
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub addresses: Vec<String>,
+    pub rate_limit_per_minute: u32,
+    pub usage_count: u64,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+    pub revoked: bool,
+}
+
+impl From<ApiKey> for ApiKeySummary {
+    fn from(api_key: ApiKey) -> Self {
+        ApiKeySummary {
+            key_id: api_key.key_id,
+            name: api_key.name,
+            scopes: api_key.scopes,
+            addresses: api_key.addresses,
+            rate_limit_per_minute: api_key.rate_limit_per_minute,
+            usage_count: api_key.usage_count,
+            last_used_at: api_key.last_used_at,
+            created_at: api_key.created_at,
+            revoked: api_key.revoked,
+        }
+    }
+}
+
+// Call to encode, either a constructor or a message is given
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncodeCallMessage {
+    pub constructor: Option<String>,
+    pub message: Option<String>,
+    #[serde(default)]
+    pub args: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecodeMessage {
+    pub kind: DecodeKind,
+    pub label: Option<String>,
+    pub data: String,
+}
@@ -1,7 +1,11 @@
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{oid::ObjectId, Binary, DateTime, Document};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use super::api_models::DeployMessage;
+use crate::utils::compiler::BuildLog;
+use crate::utils::size_report::SizeReport;
+use crate::utils::wasm_validation::WasmValidation;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Contract {
@@ -9,8 +13,60 @@ pub struct Contract {
     #[serde(skip_serializing)]
     pub id: Option<ObjectId>,
     pub code_id: String,
-    pub metadata: String,
+    // ink metadata generated by cargo-contract
+    pub metadata: Value,
     pub wasm: Vec<u8>,
+    // Metadata as a JSON string, only returned to the clients asking for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legacy_metadata: Option<String>,
+    // Issues found by the wasm validation and sizes of the wasm, not stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<WasmValidation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_report: Option<SizeReport>,
+}
+
+impl Contract {
+    // Adds the metadata as a JSON string for the clients still parsing it
+    pub fn with_legacy_metadata(mut self, legacy_metadata: bool) -> Self {
+        if legacy_metadata {
+            self.legacy_metadata = Some(self.metadata.to_string());
+        }
+        self
+    }
+}
+
+// Contract as stored in the contracts collection of mongo, the wasm is kept as BSON binary
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContractDocument {
+    #[serde(rename = "_id", skip_serializing)]
+    pub id: Option<ObjectId>,
+    pub code_id: String,
+    // The metadata is stored as a document, or as an artifact when it is too large
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Document>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_artifact: Option<StoredArtifact>,
+    pub wasm: StoredArtifact,
+}
+
+// Artifact stored in the document itself, or in GridFS when it is too large
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StoredArtifact {
+    pub encoding: ArtifactEncoding,
+    // Size in bytes of the artifact before the encoding
+    pub size: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Binary>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gridfs_id: Option<ObjectId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactEncoding {
+    Raw,
+    Zstd,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -47,3 +103,120 @@ impl Deployment {
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Job {
+    //#[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing)]
+    pub id: Option<ObjectId>,
+    pub job_id: String,
+    pub code_id: String,
+    pub address: String,
+    pub status: JobStatus,
+    pub build_log: String,
+    pub build_log_truncated: bool,
+    pub date: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Succeeded,
+    Failed,
+}
+
+impl Job {
+    pub fn new(
+        job_id: &str,
+        code_id: &str,
+        address: &str,
+        status: JobStatus,
+        build_log: &BuildLog,
+    ) -> Self {
+        Job {
+            id: None,
+            job_id: job_id.to_owned(),
+            code_id: code_id.to_owned(),
+            address: address.to_owned(),
+            status,
+            build_log: build_log.output.clone(),
+            build_log_truncated: build_log.truncated,
+            date: DateTime::now().try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiKey {
+    //#[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing)]
+    pub id: Option<ObjectId>,
+    pub key_id: String,
+    // Only the sha256 hash of the key is stored
+    pub key_hash: String,
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    // User addresses the key writes deployments for, keys created before the list can't write any
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    pub rate_limit_per_minute: u32,
+    pub usage_count: u64,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+    pub revoked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    Compile,
+    Read,
+    WriteDeployments,
+}
+
+impl ApiKey {
+    pub fn new(
+        key_id: &str,
+        key_hash: &str,
+        name: &str,
+        scopes: Vec<ApiKeyScope>,
+        rate_limit_per_minute: u32,
+    ) -> Self {
+        ApiKey {
+            id: None,
+            key_id: key_id.to_owned(),
+            key_hash: key_hash.to_owned(),
+            name: name.to_owned(),
+            scopes,
+            addresses: Vec::new(),
+            rate_limit_per_minute,
+            usage_count: 0,
+            last_used_at: None,
+            created_at: DateTime::now().try_to_rfc3339_string().unwrap_or_default(),
+            revoked: false,
+        }
+    }
+
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+// Compilations made by an address or an API key in a UTC day
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CompileQuota {
+    //#[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing)]
+    pub id: Option<ObjectId>,
+    pub quota_key: String,
+    pub day: String,
+    pub count: u32,
+}
+
+// Schema migration applied to the database
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MigrationRecord {
+    pub version: u32,
+    pub description: String,
+    pub applied_at: String,
+}
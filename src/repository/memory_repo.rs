@@ -0,0 +1,173 @@
+use crate::models::api_models::{GetDeploymentsMessage, UpdateDeployMessage};
+use crate::models::db_models::{ApiKey, Contract, Deployment, Job};
+use crate::repository::{
+    ApiKeyRepository, ContractRepository, DeploymentRepository, JobRepository, QuotaRepository,
+    RepoResult,
+};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+// Repository keeping everything in memory, the data is lost when the server stops
+// Used to run the API and its tests without a database
+#[derive(Default)]
+pub struct MemoryRepo {
+    contracts: Mutex<HashMap<String, Contract>>,
+    deployments: Mutex<Vec<Deployment>>,
+    jobs: Mutex<HashMap<String, Job>>,
+    api_keys: Mutex<Vec<ApiKey>>,
+    compile_quotas: Mutex<HashMap<(String, String), u32>>,
+}
+
+impl MemoryRepo {
+    pub fn new() -> Self {
+        MemoryRepo::default()
+    }
+}
+
+// Locks a collection, a poisoned lock is reported as a storage error
+fn lock<T>(collection: &Mutex<T>) -> RepoResult<MutexGuard<'_, T>> {
+    collection
+        .lock()
+        .map_err(|_| "Error locking the in-memory repository".into())
+}
+
+#[rocket::async_trait]
+impl ContractRepository for MemoryRepo {
+    // The first contract stored for a code id is kept, as the lookup by hash does on mongo
+    async fn create_contract(&self, new_contract: &Contract) -> RepoResult<()> {
+        lock(&self.contracts)?
+            .entry(new_contract.code_id.clone())
+            .or_insert_with(|| Contract {
+                validation: None,
+                size_report: None,
+                ..new_contract.clone()
+            });
+        Ok(())
+    }
+
+    async fn get_contract_by_hash(&self, hash: &str) -> RepoResult<Option<Contract>> {
+        Ok(lock(&self.contracts)?.get(hash).cloned())
+    }
+}
+
+#[rocket::async_trait]
+impl DeploymentRepository for MemoryRepo {
+    async fn create_deployment(&self, new_deployment: &Deployment) -> RepoResult<()> {
+        lock(&self.deployments)?.push(new_deployment.clone());
+        Ok(())
+    }
+
+    // Only the first matching deployment is updated
+    async fn update_deployment(&self, update_deployment: &UpdateDeployMessage) -> RepoResult<bool> {
+        let mut deployments = lock(&self.deployments)?;
+        let deployment = deployments.iter_mut().find(|deployment| {
+            deployment.contract_address == update_deployment.contract_address
+                && deployment.network == update_deployment.network
+                && deployment.user_address == update_deployment.user_address
+        });
+
+        match deployment {
+            Some(deployment) => {
+                deployment.contract_name = update_deployment.contract_name.clone();
+                deployment.hidden = update_deployment.hidden;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // Empty filters match every network and contract address
+    async fn get_deployments(
+        &self,
+        deployment_message: &GetDeploymentsMessage,
+    ) -> RepoResult<Vec<Deployment>> {
+        let matches = |filter: &Option<String>, value: &str| match filter {
+            Some(filter) if !filter.is_empty() => filter == value,
+            _ => true,
+        };
+
+        Ok(lock(&self.deployments)?
+            .iter()
+            .filter(|deployment| {
+                deployment.user_address == deployment_message.user_address
+                    && matches(&deployment_message.network, &deployment.network)
+                    && matches(
+                        &deployment_message.contract_address,
+                        &deployment.contract_address,
+                    )
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+#[rocket::async_trait]
+impl JobRepository for MemoryRepo {
+    async fn create_job(&self, new_job: &Job) -> RepoResult<()> {
+        lock(&self.jobs)?.insert(new_job.job_id.clone(), new_job.clone());
+        Ok(())
+    }
+
+    async fn get_job(&self, job_id: &str) -> RepoResult<Option<Job>> {
+        Ok(lock(&self.jobs)?.get(job_id).cloned())
+    }
+}
+
+#[rocket::async_trait]
+impl ApiKeyRepository for MemoryRepo {
+    async fn create_api_key(&self, new_api_key: &ApiKey) -> RepoResult<()> {
+        lock(&self.api_keys)?.push(new_api_key.clone());
+        Ok(())
+    }
+
+    async fn get_api_key_by_hash(&self, key_hash: &str) -> RepoResult<Option<ApiKey>> {
+        Ok(lock(&self.api_keys)?
+            .iter()
+            .find(|api_key| api_key.key_hash == key_hash)
+            .cloned())
+    }
+
+    async fn get_api_keys(&self) -> RepoResult<Vec<ApiKey>> {
+        Ok(lock(&self.api_keys)?.clone())
+    }
+
+    async fn revoke_api_key(&self, key_id: &str) -> RepoResult<bool> {
+        let mut api_keys = lock(&self.api_keys)?;
+        match api_keys.iter_mut().find(|api_key| api_key.key_id == key_id) {
+            Some(api_key) => {
+                api_key.revoked = true;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn record_api_key_usage(&self, key_id: &str, date: &str) -> RepoResult<()> {
+        let mut api_keys = lock(&self.api_keys)?;
+        if let Some(api_key) = api_keys.iter_mut().find(|api_key| api_key.key_id == key_id) {
+            api_key.usage_count += 1;
+            api_key.last_used_at = Some(date.to_string());
+        }
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl QuotaRepository for MemoryRepo {
+    // The counter is only shared by the threads of this instance
+    async fn increment_compile_quota(&self, quota_key: &str, day: &str) -> RepoResult<u32> {
+        let mut compile_quotas = lock(&self.compile_quotas)?;
+        // Counters of previous days are no longer needed
+        compile_quotas.retain(|(_, quota_day), _| quota_day == day);
+
+        let count = compile_quotas
+            .entry((quota_key.to_string(), day.to_string()))
+            .or_insert(0);
+        *count += 1;
+        Ok(*count)
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/repository/memory_repo_tests.rs"]
+mod memory_repo_test;
@@ -1 +1,116 @@
+pub mod memory_repo;
+pub mod mongodb_artifacts;
+pub mod mongodb_migrations;
 pub mod mongodb_repo;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_repo;
+
+use crate::models::api_models::{GetDeploymentsMessage, UpdateDeployMessage};
+use crate::models::db_models::{ApiKey, Contract, Deployment, Job};
+use crate::utils::config::{AppConfig, StorageBackend};
+use crate::utils::network_registry::NetworkRegistry;
+use log::{error, info};
+use memory_repo::MemoryRepo;
+use mongodb_repo::MongoRepo;
+use rocket::fairing::AdHoc;
+
+pub type RepoResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+// Storage of the compiled contracts, indexed by code id
+#[rocket::async_trait]
+pub trait ContractRepository: Send + Sync {
+    async fn create_contract(&self, new_contract: &Contract) -> RepoResult<()>;
+    async fn get_contract_by_hash(&self, hash: &str) -> RepoResult<Option<Contract>>;
+}
+
+// Storage of the deployments made by the users
+#[rocket::async_trait]
+pub trait DeploymentRepository: Send + Sync {
+    async fn create_deployment(&self, new_deployment: &Deployment) -> RepoResult<()>;
+    // Returns false when no deployment matched
+    async fn update_deployment(&self, update_deployment: &UpdateDeployMessage) -> RepoResult<bool>;
+    async fn get_deployments(
+        &self,
+        deployment_message: &GetDeploymentsMessage,
+    ) -> RepoResult<Vec<Deployment>>;
+}
+
+// Storage of the compilation jobs and their build logs
+#[rocket::async_trait]
+pub trait JobRepository: Send + Sync {
+    async fn create_job(&self, new_job: &Job) -> RepoResult<()>;
+    async fn get_job(&self, job_id: &str) -> RepoResult<Option<Job>>;
+}
+
+// Storage of the API keys, keys are looked up by their hash
+#[rocket::async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn create_api_key(&self, new_api_key: &ApiKey) -> RepoResult<()>;
+    async fn get_api_key_by_hash(&self, key_hash: &str) -> RepoResult<Option<ApiKey>>;
+    async fn get_api_keys(&self) -> RepoResult<Vec<ApiKey>>;
+    // Returns false when the key does not exist
+    async fn revoke_api_key(&self, key_id: &str) -> RepoResult<bool>;
+    async fn record_api_key_usage(&self, key_id: &str, date: &str) -> RepoResult<()>;
+}
+
+// Storage of the daily compile quotas
+#[rocket::async_trait]
+pub trait QuotaRepository: Send + Sync {
+    // Increases the compilations of the quota key in the day and returns the new count
+    async fn increment_compile_quota(&self, quota_key: &str, day: &str) -> RepoResult<u32>;
+}
+
+// Every storage backend implements all the repositories, the calls do not block the async workers
+pub trait Repository:
+    ContractRepository + DeploymentRepository + JobRepository + ApiKeyRepository + QuotaRepository
+{
+}
+
+impl<T> Repository for T where
+    T: ContractRepository
+        + DeploymentRepository
+        + JobRepository
+        + ApiKeyRepository
+        + QuotaRepository
+{
+}
+
+// Storage backend managed by the server
+pub type Database = Box<dyn Repository>;
+
+// Fairing initializing the configured storage backend once the configuration is loaded
+pub fn fairing() -> AdHoc {
+    AdHoc::try_on_ignite("Repository", |rocket| async move {
+        // The migrations map the stored network names to the registry
+        let db: Result<Database, String> = match (
+            rocket.state::<AppConfig>(),
+            rocket.state::<NetworkRegistry>(),
+        ) {
+            (Some(config), Some(networks)) => match config.storage {
+                StorageBackend::Mongo => MongoRepo::init(&config.mongo, networks)
+                    .await
+                    .map(|db| Box::new(db) as Database),
+                StorageBackend::Memory => Ok(Box::new(MemoryRepo::new())),
+                #[cfg(feature = "sqlite")]
+                StorageBackend::Sqlite => sqlite_repo::SqliteRepo::open(&config.sqlite.path)
+                    .map(|db| Box::new(db) as Database),
+                #[cfg(not(feature = "sqlite"))]
+                StorageBackend::Sqlite => Err(String::from(
+                    "storage sqlite requires building with the sqlite feature",
+                )),
+            },
+            _ => Err(String::from("Configuration is not loaded")),
+        };
+
+        match db {
+            Ok(db) => {
+                info!(target: "compiler", "repository initialized");
+                Ok(rocket.manage(db))
+            }
+            Err(err) => {
+                error!(target: "compiler", "{}", err);
+                Err(rocket)
+            }
+        }
+    })
+}
@@ -0,0 +1,192 @@
+use crate::models::db_models::{ArtifactEncoding, Contract, ContractDocument, StoredArtifact};
+use crate::repository::RepoResult;
+use crate::utils::config::{ArtifactCompression, ArtifactConfig};
+use mongodb::bson::spec::BinarySubtype;
+use mongodb::bson::{to_document, Binary, Bson, Document};
+use mongodb::gridfs::GridFsBucket;
+use mongodb::options::GridFsBucketOptions;
+use mongodb::Database;
+use rocket::futures::AsyncReadExt;
+use serde_json::Value;
+
+// Compression level of the artifacts, the default level of zstd
+const ZSTD_LEVEL: i32 = 3;
+
+// Encodes the contract artifacts, moving the large ones to a GridFS bucket
+// The metadata is kept as a document unless its JSON is larger than the GridFS threshold
+pub struct ArtifactStore {
+    bucket: GridFsBucket,
+    config: ArtifactConfig,
+}
+
+impl ArtifactStore {
+    pub fn new(db: &Database, bucket_name: &str, config: &ArtifactConfig) -> Self {
+        let options = GridFsBucketOptions::builder()
+            .bucket_name(bucket_name.to_string())
+            .build();
+        ArtifactStore {
+            bucket: db.gridfs_bucket(options),
+            config: config.clone(),
+        }
+    }
+
+    // Builds the document stored for a contract, uploading its large artifacts
+    pub async fn to_document(&self, contract: &Contract) -> RepoResult<ContractDocument> {
+        let (metadata, metadata_artifact) = self
+            .store_metadata(&contract.code_id, &contract.metadata)
+            .await?;
+        let wasm = match self
+            .store(&format!("{}.wasm", contract.code_id), &contract.wasm)
+            .await
+        {
+            Ok(wasm) => wasm,
+            Err(err) => {
+                if let Some(metadata_artifact) = &metadata_artifact {
+                    self.delete(metadata_artifact).await?;
+                }
+                return Err(err);
+            }
+        };
+
+        Ok(ContractDocument {
+            id: None,
+            code_id: contract.code_id.clone(),
+            metadata,
+            metadata_artifact,
+            wasm,
+        })
+    }
+
+    // Decodes the artifacts of a stored contract
+    pub async fn to_contract(&self, document: ContractDocument) -> RepoResult<Contract> {
+        let metadata = match (document.metadata, &document.metadata_artifact) {
+            (Some(metadata), _) => Bson::Document(metadata).into_relaxed_extjson(),
+            (None, Some(metadata_artifact)) => {
+                serde_json::from_slice(&self.load(metadata_artifact).await?)?
+            }
+            (None, None) => return Err("Stored contract has no metadata".into()),
+        };
+        let wasm = self.load(&document.wasm).await?;
+
+        Ok(Contract {
+            id: document.id,
+            code_id: document.code_id,
+            metadata,
+            wasm,
+            legacy_metadata: None,
+            validation: None,
+            size_report: None,
+        })
+    }
+
+    // Removes the GridFS files of a document that was not stored
+    pub async fn delete_document(&self, document: &ContractDocument) -> RepoResult<()> {
+        if let Some(metadata_artifact) = &document.metadata_artifact {
+            self.delete(metadata_artifact).await?;
+        }
+        self.delete(&document.wasm).await
+    }
+
+    // Stores the metadata as a document, or as an artifact when it is too large
+    pub async fn store_metadata(
+        &self,
+        code_id: &str,
+        metadata: &Value,
+    ) -> RepoResult<(Option<Document>, Option<StoredArtifact>)> {
+        let json = serde_json::to_vec(metadata)?;
+        if !is_stored_in_gridfs(json.len(), &self.config) {
+            return Ok((Some(to_document(metadata)?), None));
+        }
+
+        let artifact = self.store(&format!("{}.json", code_id), &json).await?;
+        Ok((None, Some(artifact)))
+    }
+
+    pub async fn store(&self, filename: &str, bytes: &[u8]) -> RepoResult<StoredArtifact> {
+        let (encoding, encoded) = encode(bytes, self.config.compression)?;
+        let mut artifact = StoredArtifact {
+            encoding,
+            size: bytes.len() as i64,
+            data: None,
+            gridfs_id: None,
+        };
+
+        if is_stored_in_gridfs(encoded.len(), &self.config) {
+            let id = self
+                .bucket
+                .upload_from_futures_0_3_reader(filename, encoded.as_slice(), None)
+                .await?;
+            artifact.gridfs_id = Some(id);
+        } else {
+            artifact.data = Some(Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: encoded,
+            });
+        }
+
+        Ok(artifact)
+    }
+
+    pub async fn load(&self, artifact: &StoredArtifact) -> RepoResult<Vec<u8>> {
+        let encoded = match (&artifact.data, artifact.gridfs_id) {
+            (Some(data), _) => data.bytes.clone(),
+            (None, Some(id)) => {
+                let mut stream = self.bucket.open_download_stream(Bson::ObjectId(id)).await?;
+                let mut encoded = Vec::new();
+                stream.read_to_end(&mut encoded).await?;
+                encoded
+            }
+            (None, None) => return Err("Stored artifact has no data".into()),
+        };
+
+        decode(artifact.encoding, &encoded, artifact.size)
+    }
+
+    pub async fn delete(&self, artifact: &StoredArtifact) -> RepoResult<()> {
+        if let Some(id) = artifact.gridfs_id {
+            self.bucket.delete(Bson::ObjectId(id)).await?;
+        }
+        Ok(())
+    }
+}
+
+// Compresses the artifact, it is kept raw when the compression does not make it smaller
+pub fn encode(
+    bytes: &[u8],
+    compression: ArtifactCompression,
+) -> RepoResult<(ArtifactEncoding, Vec<u8>)> {
+    if compression == ArtifactCompression::Zstd {
+        let compressed = zstd::encode_all(bytes, ZSTD_LEVEL)?;
+        if compressed.len() < bytes.len() {
+            return Ok((ArtifactEncoding::Zstd, compressed));
+        }
+    }
+    Ok((ArtifactEncoding::Raw, bytes.to_vec()))
+}
+
+// Restores an encoded artifact, checking it has the stored size
+pub fn decode(encoding: ArtifactEncoding, encoded: &[u8], size: i64) -> RepoResult<Vec<u8>> {
+    let bytes = match encoding {
+        ArtifactEncoding::Raw => encoded.to_vec(),
+        ArtifactEncoding::Zstd => zstd::decode_all(encoded)?,
+    };
+
+    if bytes.len() as i64 != size {
+        return Err(format!(
+            "Stored artifact has {} bytes, expected {}",
+            bytes.len(),
+            size
+        )
+        .into());
+    }
+    Ok(bytes)
+}
+
+// Checks if an encoded artifact is too large to be kept in the contract document
+pub fn is_stored_in_gridfs(encoded_size: usize, config: &ArtifactConfig) -> bool {
+    encoded_size > config.gridfs_threshold
+}
+
+#[cfg(test)]
+#[path = "../tests/repository/mongodb_artifacts_tests.rs"]
+mod mongodb_artifacts_test;
@@ -0,0 +1,374 @@
+use crate::models::db_models::{MigrationRecord, StoredArtifact};
+use crate::utils::network_registry::NetworkRegistry;
+use log::{info, warn};
+use mongodb::bson::{doc, from_document, to_bson, Bson, DateTime, Document};
+use mongodb::error::{Error, ErrorKind, WriteFailure};
+use mongodb::options::{FindOneOptions, FindOptions, IndexOptions};
+use mongodb::{Collection, Database, IndexModel};
+use rocket::futures::TryStreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::mongodb_artifacts::ArtifactStore;
+use super::RepoResult;
+use crate::utils::config::CollectionNames;
+
+// Code of the error returned when a unique index is violated
+const DUPLICATE_KEY_ERROR: i32 = 11000;
+
+// Schema changes applied at startup, in order. The applied ones are recorded in the migrations collection
+// New migrations are appended with the next version, the existing ones must not be changed
+pub const MIGRATIONS: [Migration; 7] = [
+    Migration {
+        version: 1,
+        description: "Unique index on the contract code id",
+    },
+    Migration {
+        version: 2,
+        description: "Compound index for the deployment lookups",
+    },
+    Migration {
+        version: 3,
+        description: "Unique indexes on the job id and API keys",
+    },
+    Migration {
+        version: 4,
+        description: "Unique index on the compile quota key and day",
+    },
+    Migration {
+        version: 5,
+        description: "Contract artifacts stored as BSON binary",
+    },
+    Migration {
+        version: 6,
+        description: "Contract metadata stored as a document",
+    },
+    Migration {
+        version: 7,
+        description: "Deployment networks stored with the registry id",
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+}
+
+// Migrations newer than the given schema version
+pub fn pending_migrations(current_version: u32) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS
+        .iter()
+        .filter(move |migration| migration.version > current_version)
+}
+
+// Checks if the error was caused by a duplicated value in a unique index
+pub fn is_duplicate_key_error(err: &Error) -> bool {
+    matches!(
+        err.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(write_error))
+            if write_error.code == DUPLICATE_KEY_ERROR
+    )
+}
+
+// Applies the pending migrations to the database
+// Several instances can start at once, every migration can be applied more than once
+pub async fn migrate(
+    db: &Database,
+    collections: &CollectionNames,
+    artifacts: &ArtifactStore,
+    networks: &NetworkRegistry,
+) -> RepoResult<()> {
+    let migrations: Collection<MigrationRecord> = db.collection(&collections.migrations);
+    let latest = migrations
+        .find_one(
+            doc! {},
+            FindOneOptions::builder().sort(doc! {"version": -1}).build(),
+        )
+        .await?;
+    let current_version = latest.map(|record| record.version).unwrap_or(0);
+
+    for migration in pending_migrations(current_version) {
+        info!(target: "compiler", "Applying migration {}: {}", migration.version, migration.description);
+        apply(db, collections, artifacts, networks, migration.version).await?;
+
+        let record = MigrationRecord {
+            version: migration.version,
+            description: migration.description.to_string(),
+            applied_at: DateTime::now().try_to_rfc3339_string().unwrap_or_default(),
+        };
+        match migrations.insert_one(&record, None).await {
+            Ok(_) => (),
+            // Another instance recorded it first
+            Err(err) if is_duplicate_key_error(&err) => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(())
+}
+
+async fn apply(
+    db: &Database,
+    collections: &CollectionNames,
+    artifacts: &ArtifactStore,
+    networks: &NetworkRegistry,
+    version: u32,
+) -> RepoResult<()> {
+    match version {
+        1 => {
+            let contracts: Collection<Document> = db.collection(&collections.contracts);
+            remove_duplicated_contracts(&contracts).await?;
+            create_index(&contracts, doc! {"code_id": 1}, true).await?;
+            // The versions are unique from here on
+            let migrations: Collection<Document> = db.collection(&collections.migrations);
+            create_index(&migrations, doc! {"version": 1}, true).await
+        }
+        2 => {
+            let deployments: Collection<Document> = db.collection(&collections.deployments);
+            create_index(
+                &deployments,
+                doc! {"user_address": 1, "network": 1, "contract_address": 1},
+                false,
+            )
+            .await
+        }
+        3 => {
+            let jobs: Collection<Document> = db.collection(&collections.jobs);
+            create_index(&jobs, doc! {"job_id": 1}, true).await?;
+            let api_keys: Collection<Document> = db.collection(&collections.api_keys);
+            create_index(&api_keys, doc! {"key_id": 1}, true).await?;
+            create_index(&api_keys, doc! {"key_hash": 1}, true).await
+        }
+        4 => {
+            let compile_quotas: Collection<Document> = db.collection(&collections.compile_quotas);
+            create_index(&compile_quotas, doc! {"quota_key": 1, "day": 1}, true).await
+        }
+        5 => {
+            let contracts: Collection<Document> = db.collection(&collections.contracts);
+            encode_legacy_contracts(&contracts, artifacts).await
+        }
+        6 => {
+            let contracts: Collection<Document> = db.collection(&collections.contracts);
+            decode_metadata_artifacts(&contracts, artifacts).await
+        }
+        7 => {
+            let deployments: Collection<Document> = db.collection(&collections.deployments);
+            normalize_deployment_networks(&deployments, networks).await
+        }
+        _ => Err(format!("Unknown migration {}", version).into()),
+    }
+}
+
+async fn create_index(
+    collection: &Collection<Document>,
+    keys: Document,
+    unique: bool,
+) -> RepoResult<()> {
+    let index = IndexModel::builder()
+        .keys(keys)
+        .options(IndexOptions::builder().unique(unique).build())
+        .build();
+    collection.create_index(index, None).await?;
+    Ok(())
+}
+
+// Keeps the first contract stored for every code id, the one returned by the lookups before the index existed
+async fn remove_duplicated_contracts(contracts: &Collection<Document>) -> RepoResult<()> {
+    let pipeline = vec![
+        doc! {"$sort": {"_id": 1}},
+        doc! {"$group": {"_id": "$code_id", "ids": {"$push": "$_id"}, "count": {"$sum": 1}}},
+        doc! {"$match": {"count": {"$gt": 1}}},
+    ];
+    let duplicates: Vec<Document> = contracts
+        .aggregate(pipeline, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    for duplicate in duplicates {
+        let ids = duplicate.get_array("ids")?;
+        let removed = contracts
+            .delete_many(doc! {"_id": {"$in": &ids[1..]}}, None)
+            .await?;
+        info!(target: "compiler", "Removed {} duplicated contracts", removed.deleted_count);
+    }
+
+    Ok(())
+}
+
+// Contract as stored before the artifacts were encoded, the wasm is an array of integers
+#[derive(Deserialize)]
+struct LegacyContract {
+    code_id: String,
+    metadata: String,
+    wasm: Vec<u8>,
+}
+
+// Rewrites the contracts stored with the wasm as an array of integers and the metadata as a string
+// Both are stored as encoded artifacts, migration 6 turns the metadata into a document
+async fn encode_legacy_contracts(
+    contracts: &Collection<Document>,
+    artifacts: &ArtifactStore,
+) -> RepoResult<()> {
+    let legacy = doc! {"wasm": {"$type": "array"}};
+    let mut encoded = 0;
+
+    for id in matching_ids(contracts, &legacy).await? {
+        let mut filter = legacy.clone();
+        filter.insert("_id", id);
+        // Another instance already rewrote it
+        let Some(contract) = contracts.find_one(filter.clone(), None).await? else {
+            continue;
+        };
+        let contract: LegacyContract = from_document(contract)?;
+
+        let metadata = artifacts
+            .store(
+                &format!("{}.json", contract.code_id),
+                contract.metadata.as_bytes(),
+            )
+            .await?;
+        let wasm = match artifacts
+            .store(&format!("{}.wasm", contract.code_id), &contract.wasm)
+            .await
+        {
+            Ok(wasm) => wasm,
+            Err(err) => {
+                artifacts.delete(&metadata).await?;
+                return Err(err);
+            }
+        };
+
+        let update = doc! {"$set": {"metadata": to_bson(&metadata)?, "wasm": to_bson(&wasm)?}};
+        let result = contracts.update_one(filter, update, None).await?;
+        if result.matched_count == 0 {
+            artifacts.delete(&metadata).await?;
+            artifacts.delete(&wasm).await?;
+        } else {
+            encoded += 1;
+        }
+    }
+    info!(target: "compiler", "Encoded the artifacts of {} contracts", encoded);
+
+    Ok(())
+}
+
+// Rewrites the metadata stored as an encoded JSON string into a document
+async fn decode_metadata_artifacts(
+    contracts: &Collection<Document>,
+    artifacts: &ArtifactStore,
+) -> RepoResult<()> {
+    let encoded = doc! {"metadata.encoding": {"$exists": true}};
+    let mut decoded = 0;
+
+    for id in matching_ids(contracts, &encoded).await? {
+        let mut filter = encoded.clone();
+        filter.insert("_id", id);
+        // Another instance already rewrote it
+        let Some(contract) = contracts.find_one(filter.clone(), None).await? else {
+            continue;
+        };
+
+        let code_id = contract.get_str("code_id")?;
+        let stored: StoredArtifact = from_document(contract.get_document("metadata")?.clone())?;
+        let metadata: Value = serde_json::from_slice(&artifacts.load(&stored).await?)?;
+        let (metadata, metadata_artifact) = artifacts.store_metadata(code_id, &metadata).await?;
+
+        let update = metadata_update(&metadata, &metadata_artifact)?;
+        let result = contracts.update_one(filter, update, None).await?;
+        if result.matched_count == 0 {
+            if let Some(metadata_artifact) = &metadata_artifact {
+                artifacts.delete(metadata_artifact).await?;
+            }
+        } else {
+            artifacts.delete(&stored).await?;
+            decoded += 1;
+        }
+    }
+    info!(target: "compiler", "Stored the metadata of {} contracts as documents", decoded);
+
+    Ok(())
+}
+
+// Rewrites the network names stored before the registry, like "Rococo" or "rococo-testnet", with the network id
+async fn normalize_deployment_networks(
+    deployments: &Collection<Document>,
+    networks: &NetworkRegistry,
+) -> RepoResult<()> {
+    let names: Vec<String> = deployments
+        .distinct("network", None, None)
+        .await?
+        .into_iter()
+        .filter_map(|name| name.as_str().map(String::from))
+        .collect();
+
+    for (name, id) in network_renames(&names, networks) {
+        let result = deployments
+            .update_many(
+                doc! {"network": &name},
+                doc! {"$set": {"network": &id}},
+                None,
+            )
+            .await?;
+        info!(target: "compiler", "Renamed network {:?} to {} in {} deployments", name, id, result.modified_count);
+    }
+    for name in names.iter().filter(|name| networks.resolve(name).is_none()) {
+        warn!(target: "compiler", "Deployments of network {:?} are kept, it is not in the registry", name);
+    }
+
+    Ok(())
+}
+
+// Stored network names to rewrite with the id of their registry network
+pub fn network_renames(names: &[String], networks: &NetworkRegistry) -> Vec<(String, String)> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let network = networks.resolve(name)?;
+            (network.id != *name).then(|| (name.clone(), network.id.clone()))
+        })
+        .collect()
+}
+
+// Update setting the stored metadata and removing the other representation
+fn metadata_update(
+    metadata: &Option<Document>,
+    metadata_artifact: &Option<StoredArtifact>,
+) -> RepoResult<Document> {
+    Ok(match (metadata, metadata_artifact) {
+        (Some(metadata), _) => doc! {
+            "$set": {"metadata": metadata},
+            "$unset": {"metadata_artifact": ""},
+        },
+        (None, Some(metadata_artifact)) => doc! {
+            "$set": {"metadata_artifact": to_bson(metadata_artifact)?},
+            "$unset": {"metadata": ""},
+        },
+        (None, None) => return Err("Stored contract has no metadata".into()),
+    })
+}
+
+// Ids of the documents matching the filter, read before they are rewritten
+async fn matching_ids(
+    collection: &Collection<Document>,
+    filter: &Document,
+) -> RepoResult<Vec<Bson>> {
+    let documents: Vec<Document> = collection
+        .find(
+            filter.clone(),
+            FindOptions::builder().projection(doc! {"_id": 1}).build(),
+        )
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(documents
+        .into_iter()
+        .filter_map(|document| document.get("_id").cloned())
+        .collect())
+}
+
+#[cfg(test)]
+#[path = "../tests/repository/mongodb_migrations_tests.rs"]
+mod mongodb_migrations_test;
@@ -1,109 +1,126 @@
-use std::env;
-
 use crate::models::api_models::{GetDeploymentsMessage, UpdateDeployMessage};
-use crate::models::db_models::{Contract, Deployment};
-use mongodb::results::UpdateResult;
-use mongodb::{
-    bson::doc,
-    results::InsertOneResult,
-    sync::{Client, Collection},
+use crate::models::db_models::{ApiKey, CompileQuota, Contract, ContractDocument, Deployment, Job};
+use crate::repository::mongodb_artifacts::ArtifactStore;
+use crate::repository::mongodb_migrations::{is_duplicate_key_error, migrate};
+use crate::repository::{
+    ApiKeyRepository, ContractRepository, DeploymentRepository, JobRepository, QuotaRepository,
+    RepoResult,
 };
+use crate::utils::config::MongoConfig;
+use crate::utils::network_registry::NetworkRegistry;
+use mongodb::options::{FindOneAndUpdateOptions, ReturnDocument};
+use mongodb::{bson::doc, Client, Collection};
+use rocket::futures::StreamExt;
 
-use log::{debug, error};
+use log::debug;
 pub struct MongoRepo {
-    pub contracts: Collection<Contract>,
+    pub contracts: Collection<ContractDocument>,
+    pub artifacts: ArtifactStore,
     pub deployments: Collection<Deployment>,
+    pub jobs: Collection<Job>,
+    pub api_keys: Collection<ApiKey>,
+    pub compile_quotas: Collection<CompileQuota>,
 }
 
 // Mongo Repo implementation
 impl MongoRepo {
     // Create a new Mongo Repo
-    pub fn init() -> Self {
+    pub async fn init(config: &MongoConfig, networks: &NetworkRegistry) -> Result<Self, String> {
         // Generating dabase connection
-        let uri = match env::var("MONGOURI") {
-            Ok(v) => v,
-            Err(_) => {
-                error!(target: "compiler", "MONGOURI environment variable not set");
-                std::process::exit(1);
-            }
-        };
-
-        let client = match Client::with_uri_str(uri) {
-            Ok(v) => v,
-            Err(_) => {
-                error!(target: "compiler", "Error connecting to MongoDB");
-                std::process::exit(1);
-            }
-        };
-
-        let db = client.database("ContractWizard");
-        let contracts: Collection<Contract> = db.collection("Contracts");
-        let deployments: Collection<Deployment> = db.collection("Deployments");
+        let client = Client::with_uri_str(&config.uri)
+            .await
+            .map_err(|err| format!("Error connecting to MongoDB: {}", err))?;
+
+        let db = client.database(&config.database);
+        let contracts: Collection<ContractDocument> = db.collection(&config.collections.contracts);
+        let deployments: Collection<Deployment> = db.collection(&config.collections.deployments);
+        let jobs: Collection<Job> = db.collection(&config.collections.jobs);
+        let api_keys: Collection<ApiKey> = db.collection(&config.collections.api_keys);
+        let compile_quotas: Collection<CompileQuota> =
+            db.collection(&config.collections.compile_quotas);
+        let artifacts = ArtifactStore::new(&db, &config.collections.artifacts, &config.artifacts);
 
         // Test db is up and running
-        let ping_database = client
-            .database("ContractWizard")
-            .run_command(doc! {"ping": 1}, None);
+        let ping_database = db.run_command(doc! {"ping": 1}, None).await;
 
         // Checking the response
         match ping_database {
             Ok(_) => debug!(target: "compiler", "Connected to Database"),
-            _ => {
-                error!(target: "compiler", "Error connecting to database. Connection timed out");
-                std::process::exit(1);
+            Err(err) => {
+                return Err(format!(
+                    "Error connecting to database {}: {}",
+                    config.database, err
+                ))
             }
         }
 
-        MongoRepo {
+        // Creating the indexes and applying the schema changes
+        migrate(&db, &config.collections, &artifacts, networks)
+            .await
+            .map_err(|err| format!("Error migrating database {}: {}", config.database, err))?;
+        debug!(target: "compiler", "Database migrated");
+
+        Ok(MongoRepo {
             contracts,
+            artifacts,
             deployments,
-        }
+            jobs,
+            api_keys,
+            compile_quotas,
+        })
     }
+}
 
-    // Insert a new contract into the database
-    pub fn create_contract(
-        &self,
-        new_contract: &Contract,
-    ) -> Result<InsertOneResult, Box<dyn std::error::Error>> {
-        let contract = self.contracts.insert_one(new_contract, None)?;
-        Ok(contract)
+#[rocket::async_trait]
+impl ContractRepository for MongoRepo {
+    // Insert a new contract into the database, the code id is unique so the first contract is kept
+    async fn create_contract(&self, new_contract: &Contract) -> RepoResult<()> {
+        let document = self.artifacts.to_document(new_contract).await?;
+        match self.contracts.insert_one(&document, None).await {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                // The uploaded artifacts are not referenced by any contract
+                self.artifacts.delete_document(&document).await?;
+                if is_duplicate_key_error(&err) {
+                    debug!(target: "compiler", "Contract {} already stored", &new_contract.code_id);
+                    return Ok(());
+                }
+                Err(err.into())
+            }
+        }
     }
 
     // Get an existing contract from the DB
-    pub fn get_contract_by_hash(
-        &self,
-        hash: &String,
-    ) -> Result<Option<Contract>, Box<dyn std::error::Error>> {
+    async fn get_contract_by_hash(&self, hash: &str) -> RepoResult<Option<Contract>> {
         let filter = doc! {"code_id": hash};
-        let contract = self.contracts.find_one(filter, None)?;
-        Ok(contract)
+        match self.contracts.find_one(filter, None).await? {
+            Some(document) => Ok(Some(self.artifacts.to_contract(document).await?)),
+            None => Ok(None),
+        }
     }
+}
 
+#[rocket::async_trait]
+impl DeploymentRepository for MongoRepo {
     // Create a deployment in the database
-    pub fn create_deployment(
-        &self,
-        new_deployment: &Deployment,
-    ) -> Result<InsertOneResult, Box<dyn std::error::Error>> {
-        let deployment = self.deployments.insert_one(new_deployment, None)?;
-        Ok(deployment)
+    async fn create_deployment(&self, new_deployment: &Deployment) -> RepoResult<()> {
+        self.deployments.insert_one(new_deployment, None).await?;
+        Ok(())
     }
 
     // Update a deployment in the database
-    pub fn update_deployment(
-        &self,
-        update_deployment: &UpdateDeployMessage,
-    ) -> Result<UpdateResult, Box<dyn std::error::Error>> {
+    async fn update_deployment(&self, update_deployment: &UpdateDeployMessage) -> RepoResult<bool> {
         let filter = doc! {"contract_address": &update_deployment.contract_address, "network": &update_deployment.network, "user_address": &update_deployment.user_address};
         let deployment = self
-            .deployments.update_one(filter, doc! {"$set": {"contract_name": &update_deployment.contract_name, "hidden": &update_deployment.hidden}}, None)?;
-        Ok(deployment)
+            .deployments.update_one(filter, doc! {"$set": {"contract_name": &update_deployment.contract_name, "hidden": &update_deployment.hidden}}, None).await?;
+        Ok(deployment.matched_count > 0)
     }
 
     // Fetch stored deployments from the db
-    pub fn get_deployments(
+    async fn get_deployments(
         &self,
         deployment_message: &GetDeploymentsMessage,
-    ) -> Result<Vec<Deployment>, Box<dyn std::error::Error>> {
+    ) -> RepoResult<Vec<Deployment>> {
         let mut filter = doc! {"user_address": &deployment_message.user_address};
 
         if let Some(network) = &deployment_message.network {
@@ -118,15 +135,98 @@ impl MongoRepo {
             }
         }
 
-        let deployments = self.deployments.find(filter, None)?;
+        let deployments = self.deployments.find(filter, None).await?;
 
         let deployments_vec: Vec<Deployment> = deployments
-            .filter(|deployment| deployment.is_ok())
-            .map(|deployment| {
-                deployment.expect("This will never panic because of the filter above")
-            })
-            .collect();
+            .filter_map(|deployment| async move { deployment.ok() })
+            .collect()
+            .await;
 
         Ok(deployments_vec)
     }
 }
+
+#[rocket::async_trait]
+impl JobRepository for MongoRepo {
+    // Store a compilation job and its build log in the database
+    async fn create_job(&self, new_job: &Job) -> RepoResult<()> {
+        self.jobs.insert_one(new_job, None).await?;
+        Ok(())
+    }
+
+    // Get a compilation job from the DB
+    async fn get_job(&self, job_id: &str) -> RepoResult<Option<Job>> {
+        let filter = doc! {"job_id": job_id};
+        let job = self.jobs.find_one(filter, None).await?;
+        Ok(job)
+    }
+}
+
+#[rocket::async_trait]
+impl ApiKeyRepository for MongoRepo {
+    // Store a new API key in the database
+    async fn create_api_key(&self, new_api_key: &ApiKey) -> RepoResult<()> {
+        self.api_keys.insert_one(new_api_key, None).await?;
+        Ok(())
+    }
+
+    // Get an API key by the hash of the key
+    async fn get_api_key_by_hash(&self, key_hash: &str) -> RepoResult<Option<ApiKey>> {
+        let filter = doc! {"key_hash": key_hash};
+        let api_key = self.api_keys.find_one(filter, None).await?;
+        Ok(api_key)
+    }
+
+    // Fetch all the API keys, revoked ones included
+    async fn get_api_keys(&self) -> RepoResult<Vec<ApiKey>> {
+        let api_keys = self.api_keys.find(doc! {}, None).await?;
+
+        let api_keys_vec: Vec<ApiKey> = api_keys
+            .filter_map(|api_key| async move { api_key.ok() })
+            .collect()
+            .await;
+
+        Ok(api_keys_vec)
+    }
+
+    // Revoke an API key, it is kept in the database for attribution
+    async fn revoke_api_key(&self, key_id: &str) -> RepoResult<bool> {
+        let filter = doc! {"key_id": key_id};
+        let api_key = self
+            .api_keys
+            .update_one(filter, doc! {"$set": {"revoked": true}}, None)
+            .await?;
+        Ok(api_key.matched_count > 0)
+    }
+
+    // Increase the usage counter of an API key
+    async fn record_api_key_usage(&self, key_id: &str, date: &str) -> RepoResult<()> {
+        let filter = doc! {"key_id": key_id};
+        self.api_keys
+            .update_one(
+                filter,
+                doc! {"$inc": {"usage_count": 1_i64}, "$set": {"last_used_at": date}},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl QuotaRepository for MongoRepo {
+    // Increase the compilations of the quota key in the day and return the new count
+    // The counter is shared by every instance of the service
+    async fn increment_compile_quota(&self, quota_key: &str, day: &str) -> RepoResult<u32> {
+        let filter = doc! {"quota_key": quota_key, "day": day};
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build();
+        let quota = self
+            .compile_quotas
+            .find_one_and_update(filter, doc! {"$inc": {"count": 1_i64}}, options)
+            .await?;
+        Ok(quota.map(|quota| quota.count).unwrap_or(1))
+    }
+}
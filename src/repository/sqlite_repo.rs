@@ -0,0 +1,479 @@
+use crate::models::api_models::{GetDeploymentsMessage, UpdateDeployMessage};
+use crate::models::db_models::{ApiKey, Contract, Deployment, Job};
+use crate::repository::{
+    ApiKeyRepository, ContractRepository, DeploymentRepository, JobRepository, QuotaRepository,
+    RepoResult,
+};
+use log::{debug, info};
+use rocket::tokio::task::spawn_blocking;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// Schema migrations, applied in order. The applied ones are tracked with the user_version pragma
+// New migrations are appended, the existing ones must not be changed
+const MIGRATIONS: [&str; 2] = [
+    r#"
+    CREATE TABLE contracts (
+        code_id TEXT PRIMARY KEY,
+        metadata TEXT NOT NULL,
+        wasm BLOB NOT NULL
+    );
+
+    CREATE TABLE deployments (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        contract_name TEXT,
+        contract_address TEXT NOT NULL,
+        network TEXT NOT NULL,
+        code_id TEXT NOT NULL,
+        user_address TEXT NOT NULL,
+        tx_hash TEXT,
+        date TEXT NOT NULL,
+        contract_type TEXT NOT NULL,
+        external_abi TEXT,
+        hidden INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX deployments_user_network_address
+        ON deployments (user_address, network, contract_address);
+
+    CREATE TABLE jobs (
+        job_id TEXT PRIMARY KEY,
+        code_id TEXT NOT NULL,
+        address TEXT NOT NULL,
+        status TEXT NOT NULL,
+        build_log TEXT NOT NULL,
+        build_log_truncated INTEGER NOT NULL,
+        date TEXT NOT NULL
+    );
+
+    CREATE TABLE api_keys (
+        key_id TEXT PRIMARY KEY,
+        key_hash TEXT NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        scopes TEXT NOT NULL,
+        rate_limit_per_minute INTEGER NOT NULL,
+        usage_count INTEGER NOT NULL,
+        last_used_at TEXT,
+        created_at TEXT NOT NULL,
+        revoked INTEGER NOT NULL
+    );
+
+    CREATE TABLE compile_quotas (
+        quota_key TEXT NOT NULL,
+        day TEXT NOT NULL,
+        count INTEGER NOT NULL,
+        PRIMARY KEY (quota_key, day)
+    );
+"#,
+    // User addresses an API key writes deployments for
+    r#"
+    ALTER TABLE api_keys ADD COLUMN addresses TEXT NOT NULL DEFAULT '[]';
+"#,
+];
+
+const DEPLOYMENT_COLUMNS: &str = "contract_name, contract_address, network, code_id, user_address, tx_hash, date, contract_type, external_abi, hidden";
+
+const JOB_COLUMNS: &str = "job_id, code_id, address, status, build_log, build_log_truncated, date";
+
+const API_KEY_COLUMNS: &str = "key_id, key_hash, name, scopes, rate_limit_per_minute, usage_count, last_used_at, created_at, revoked, addresses";
+
+// Repository storing everything in a local SQLite database file
+pub struct SqliteRepo {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteRepo {
+    // Opens the database, creating it when it does not exist, and applies the pending migrations
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let connection = Connection::open(path)
+            .map_err(|err| format!("Error opening SQLite database {}: {}", path.display(), err))?;
+        let repo = SqliteRepo {
+            connection: Arc::new(Mutex::new(connection)),
+        };
+        repo.migrate()
+            .map_err(|err| format!("Error migrating SQLite database: {}", err))?;
+
+        info!(target: "compiler", "SQLite database {} opened", path.display());
+        Ok(repo)
+    }
+
+    // Applies the migrations newer than the schema version of the database
+    fn migrate(&self) -> RepoResult<()> {
+        let mut connection = self.connection()?;
+        let version: usize =
+            connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let transaction = connection.transaction()?;
+            transaction.execute_batch(migration)?;
+            transaction.pragma_update(None, "user_version", index + 1)?;
+            transaction.commit()?;
+            debug!(target: "compiler", "SQLite migration {} applied", index + 1);
+        }
+
+        Ok(())
+    }
+
+    fn connection(&self) -> RepoResult<MutexGuard<'_, Connection>> {
+        lock(&self.connection)
+    }
+
+    // Runs the queries on a blocking thread, rusqlite calls would block the async workers
+    async fn run<T, F>(&self, queries: F) -> RepoResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> RepoResult<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        spawn_blocking(move || queries(&*lock(&connection)?)).await?
+    }
+}
+
+fn lock(connection: &Mutex<Connection>) -> RepoResult<MutexGuard<'_, Connection>> {
+    connection
+        .lock()
+        .map_err(|_| "Error locking the SQLite connection".into())
+}
+
+// Enums are stored with their serde name
+fn to_text<T: serde::Serialize>(value: &T) -> rusqlite::Result<String> {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .ok_or(rusqlite::Error::InvalidQuery)
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(index: usize, text: &str) -> rusqlite::Result<T> {
+    serde_json::from_str(text).map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, err.into())
+    })
+}
+
+fn deployment_from_row(row: &Row) -> rusqlite::Result<Deployment> {
+    Ok(Deployment {
+        id: None,
+        contract_name: row.get(0)?,
+        contract_address: row.get(1)?,
+        network: row.get(2)?,
+        code_id: row.get(3)?,
+        user_address: row.get(4)?,
+        tx_hash: row.get(5)?,
+        date: row.get(6)?,
+        contract_type: row.get(7)?,
+        external_abi: row.get(8)?,
+        hidden: row.get(9)?,
+    })
+}
+
+fn job_from_row(row: &Row) -> rusqlite::Result<Job> {
+    let status: String = row.get(3)?;
+    Ok(Job {
+        id: None,
+        job_id: row.get(0)?,
+        code_id: row.get(1)?,
+        address: row.get(2)?,
+        status: from_json(3, &format!("\"{}\"", status))?,
+        build_log: row.get(4)?,
+        build_log_truncated: row.get(5)?,
+        date: row.get(6)?,
+    })
+}
+
+fn api_key_from_row(row: &Row) -> rusqlite::Result<ApiKey> {
+    let scopes: String = row.get(3)?;
+    let usage_count: i64 = row.get(5)?;
+    let addresses: String = row.get(9)?;
+    Ok(ApiKey {
+        id: None,
+        key_id: row.get(0)?,
+        key_hash: row.get(1)?,
+        name: row.get(2)?,
+        scopes: from_json(3, &scopes)?,
+        addresses: from_json(9, &addresses)?,
+        rate_limit_per_minute: row.get(4)?,
+        usage_count: usage_count as u64,
+        last_used_at: row.get(6)?,
+        created_at: row.get(7)?,
+        revoked: row.get(8)?,
+    })
+}
+
+#[rocket::async_trait]
+impl ContractRepository for SqliteRepo {
+    // The first contract stored for a code id is kept
+    async fn create_contract(&self, new_contract: &Contract) -> RepoResult<()> {
+        let new_contract = new_contract.clone();
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT OR IGNORE INTO contracts (code_id, metadata, wasm) VALUES (?1, ?2, ?3)",
+                params![
+                    new_contract.code_id,
+                    new_contract.metadata.to_string(),
+                    new_contract.wasm
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_contract_by_hash(&self, hash: &str) -> RepoResult<Option<Contract>> {
+        let hash = hash.to_string();
+        self.run(move |connection| {
+            let contract = connection
+                .query_row(
+                    "SELECT code_id, metadata, wasm FROM contracts WHERE code_id = ?1",
+                    params![hash],
+                    |row| {
+                        Ok(Contract {
+                            id: None,
+                            code_id: row.get(0)?,
+                            metadata: from_json(1, &row.get::<_, String>(1)?)?,
+                            wasm: row.get(2)?,
+                            legacy_metadata: None,
+                            validation: None,
+                            size_report: None,
+                        })
+                    },
+                )
+                .optional()?;
+            Ok(contract)
+        })
+        .await
+    }
+}
+
+#[rocket::async_trait]
+impl DeploymentRepository for SqliteRepo {
+    async fn create_deployment(&self, new_deployment: &Deployment) -> RepoResult<()> {
+        let new_deployment = new_deployment.clone();
+        self.run(move |connection| {
+            connection.execute(
+                &format!(
+                    "INSERT INTO deployments ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    DEPLOYMENT_COLUMNS
+                ),
+                params![
+                    new_deployment.contract_name,
+                    new_deployment.contract_address,
+                    new_deployment.network,
+                    new_deployment.code_id,
+                    new_deployment.user_address,
+                    new_deployment.tx_hash,
+                    new_deployment.date,
+                    new_deployment.contract_type,
+                    new_deployment.external_abi,
+                    new_deployment.hidden,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Only the first matching deployment is updated
+    async fn update_deployment(&self, update_deployment: &UpdateDeployMessage) -> RepoResult<bool> {
+        let update_deployment = update_deployment.clone();
+        self.run(move |connection| {
+            let updated = connection.execute(
+                "UPDATE deployments SET contract_name = ?1, hidden = ?2 WHERE id = (
+                    SELECT id FROM deployments
+                    WHERE contract_address = ?3 AND network = ?4 AND user_address = ?5
+                    ORDER BY id LIMIT 1
+                )",
+                params![
+                    update_deployment.contract_name,
+                    update_deployment.hidden,
+                    update_deployment.contract_address,
+                    update_deployment.network,
+                    update_deployment.user_address,
+                ],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    // Empty filters match every network and contract address
+    async fn get_deployments(
+        &self,
+        deployment_message: &GetDeploymentsMessage,
+    ) -> RepoResult<Vec<Deployment>> {
+        let deployment_message = deployment_message.clone();
+        self.run(move |connection| {
+            let mut statement = connection.prepare(&format!(
+                "SELECT {} FROM deployments
+                WHERE user_address = ?1
+                    AND (IFNULL(?2, '') = '' OR network = ?2)
+                    AND (IFNULL(?3, '') = '' OR contract_address = ?3)
+                ORDER BY id",
+                DEPLOYMENT_COLUMNS
+            ))?;
+
+            let deployments = statement
+                .query_map(
+                    params![
+                        deployment_message.user_address,
+                        deployment_message.network,
+                        deployment_message.contract_address,
+                    ],
+                    deployment_from_row,
+                )?
+                .collect::<Result<Vec<Deployment>, _>>()?;
+            Ok(deployments)
+        })
+        .await
+    }
+}
+
+#[rocket::async_trait]
+impl JobRepository for SqliteRepo {
+    async fn create_job(&self, new_job: &Job) -> RepoResult<()> {
+        let new_job = new_job.clone();
+        self.run(move |connection| {
+            connection.execute(
+                &format!(
+                    "INSERT INTO jobs ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    JOB_COLUMNS
+                ),
+                params![
+                    new_job.job_id,
+                    new_job.code_id,
+                    new_job.address,
+                    to_text(&new_job.status)?,
+                    new_job.build_log,
+                    new_job.build_log_truncated,
+                    new_job.date,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_job(&self, job_id: &str) -> RepoResult<Option<Job>> {
+        let job_id = job_id.to_string();
+        self.run(move |connection| {
+            let job = connection
+                .query_row(
+                    &format!("SELECT {} FROM jobs WHERE job_id = ?1", JOB_COLUMNS),
+                    params![job_id],
+                    job_from_row,
+                )
+                .optional()?;
+            Ok(job)
+        })
+        .await
+    }
+}
+
+#[rocket::async_trait]
+impl ApiKeyRepository for SqliteRepo {
+    async fn create_api_key(&self, new_api_key: &ApiKey) -> RepoResult<()> {
+        let new_api_key = new_api_key.clone();
+        self.run(move |connection| {
+            connection.execute(
+                &format!(
+                    "INSERT INTO api_keys ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    API_KEY_COLUMNS
+                ),
+                params![
+                    new_api_key.key_id,
+                    new_api_key.key_hash,
+                    new_api_key.name,
+                    serde_json::to_string(&new_api_key.scopes)?,
+                    new_api_key.rate_limit_per_minute,
+                    new_api_key.usage_count as i64,
+                    new_api_key.last_used_at,
+                    new_api_key.created_at,
+                    new_api_key.revoked,
+                    serde_json::to_string(&new_api_key.addresses)?,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_api_key_by_hash(&self, key_hash: &str) -> RepoResult<Option<ApiKey>> {
+        let key_hash = key_hash.to_string();
+        self.run(move |connection| {
+            let api_key = connection
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM api_keys WHERE key_hash = ?1",
+                        API_KEY_COLUMNS
+                    ),
+                    params![key_hash],
+                    api_key_from_row,
+                )
+                .optional()?;
+            Ok(api_key)
+        })
+        .await
+    }
+
+    async fn get_api_keys(&self) -> RepoResult<Vec<ApiKey>> {
+        self.run(|connection| {
+            let mut statement = connection.prepare(&format!(
+                "SELECT {} FROM api_keys ORDER BY created_at",
+                API_KEY_COLUMNS
+            ))?;
+
+            // Keys that can not be read are skipped, as the mongo repository does
+            let api_keys = statement
+                .query_map([], api_key_from_row)?
+                .filter_map(|api_key| api_key.ok())
+                .collect();
+            Ok(api_keys)
+        })
+        .await
+    }
+
+    async fn revoke_api_key(&self, key_id: &str) -> RepoResult<bool> {
+        let key_id = key_id.to_string();
+        self.run(move |connection| {
+            let updated = connection.execute(
+                "UPDATE api_keys SET revoked = 1 WHERE key_id = ?1",
+                params![key_id],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn record_api_key_usage(&self, key_id: &str, date: &str) -> RepoResult<()> {
+        let (key_id, date) = (key_id.to_string(), date.to_string());
+        self.run(move |connection| {
+            connection.execute(
+                "UPDATE api_keys SET usage_count = usage_count + 1, last_used_at = ?2 WHERE key_id = ?1",
+                params![key_id, date],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[rocket::async_trait]
+impl QuotaRepository for SqliteRepo {
+    // The counter is shared by every process using the database file
+    async fn increment_compile_quota(&self, quota_key: &str, day: &str) -> RepoResult<u32> {
+        let (quota_key, day) = (quota_key.to_string(), day.to_string());
+        self.run(move |connection| {
+            let count = connection.query_row(
+                "INSERT INTO compile_quotas (quota_key, day, count) VALUES (?1, ?2, 1)
+                ON CONFLICT (quota_key, day) DO UPDATE SET count = count + 1
+                RETURNING count",
+                params![quota_key, day],
+                |row| row.get(0),
+            )?;
+            Ok(count)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/repository/sqlite_repo_tests.rs"]
+mod sqlite_repo_test;
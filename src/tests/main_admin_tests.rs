@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod admin_test {
+    use super::super::*;
+    use crate::models::api_models::{MintedApiKey, ServerResponse};
+    use crate::test_server::rocket;
+    use crate::utils::api_keys::{ADMIN_TOKEN_HEADER, API_KEY_HEADER};
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+
+    const ADMIN_TOKEN: &str = "test-admin-token";
+
+    #[test]
+    fn admin_routes_require_the_admin_token() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post("/admin/api-keys")
+            .header(Header::new(ADMIN_TOKEN_HEADER, "wrong-token"))
+            .body(r#"{ "name": "ci", "scopes": ["compile"] }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        std::mem::drop(response);
+        client.terminate();
+    }
+
+    #[test]
+    fn api_key_name_and_scopes_are_required() {
+        std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post("/admin/api-keys")
+            .header(Header::new(ADMIN_TOKEN_HEADER, ADMIN_TOKEN))
+            .body(r#"{ "name": " ", "scopes": [] }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("API key name and scopes are required."));
+        client.terminate();
+    }
+
+    #[test]
+    fn minted_api_key_is_scoped_and_revocable() {
+        std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+        let response: ServerResponse<MintedApiKey> = client
+            .post("/admin/api-keys")
+            .header(Header::new(ADMIN_TOKEN_HEADER, ADMIN_TOKEN))
+            .body(r#"{ "name": "ci", "scopes": ["read"] }"#)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        let minted = response.data.unwrap();
+
+        // The listed keys do not contain their hash
+        let response = client
+            .get("/admin/api-keys")
+            .header(Header::new(ADMIN_TOKEN_HEADER, ADMIN_TOKEN))
+            .dispatch()
+            .into_string()
+            .unwrap();
+        assert!(response.contains(&minted.key_id));
+        assert!(!response.contains("key_hash"));
+
+        // The key can read but not compile
+        let response = client
+            .get("/deployments?user_address=")
+            .header(Header::new(API_KEY_HEADER, minted.api_key.clone()))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        std::mem::drop(response);
+
+        let response = client
+            .post(uri!("/contract"))
+            .header(Header::new(API_KEY_HEADER, minted.api_key.clone()))
+            .body(r#"{ "address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "code": "something", "features": ["psp22"] }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("API key is missing the compile scope."));
+
+        // Revoked keys are rejected
+        let response = client
+            .delete(format!("/admin/api-keys/{}", minted.key_id))
+            .header(Header::new(ADMIN_TOKEN_HEADER, ADMIN_TOKEN))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        std::mem::drop(response);
+
+        let response = client
+            .get("/deployments?user_address=")
+            .header(Header::new(API_KEY_HEADER, minted.api_key))
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response.into_string().unwrap().contains("API key revoked."));
+        client.terminate();
+    }
+
+    #[test]
+    fn write_deployments_keys_are_bound_to_their_addresses() {
+        std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let deployment = |user_address: &str| {
+            format!(
+                r#"{{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "rococo", "code_id": "some_id", "user_address": "{}", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }}"#,
+                user_address
+            )
+        };
+
+        let response = client
+            .post("/admin/api-keys")
+            .header(Header::new(ADMIN_TOKEN_HEADER, ADMIN_TOKEN))
+            .body(r#"{ "name": "indexer", "scopes": ["write_deployments"] }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        std::mem::drop(response);
+
+        let response: ServerResponse<MintedApiKey> = client
+            .post("/admin/api-keys")
+            .header(Header::new(ADMIN_TOKEN_HEADER, ADMIN_TOKEN))
+            .body(r#"{ "name": "indexer", "scopes": ["write_deployments"], "addresses": ["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"] }"#)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        let minted = response.data.unwrap();
+
+        let response = client
+            .post(uri!("/deployments"))
+            .header(Header::new(API_KEY_HEADER, minted.api_key.clone()))
+            .body(deployment(
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            ))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        std::mem::drop(response);
+
+        // Deployments of the addresses outside the list of the key are rejected
+        let response = client
+            .post(uri!("/deployments"))
+            .header(Header::new(API_KEY_HEADER, minted.api_key))
+            .body(deployment(
+                "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty",
+            ))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("API key is not allowed to write the deployments of the user address."));
+        client.terminate();
+    }
+}
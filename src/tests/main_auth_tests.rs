@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod auth_test {
+    use super::super::*;
+    use crate::models::api_models::{AuthChallenge, ServerResponse};
+    use crate::test_server::rocket;
+    use crate::test_signer::TestSigner;
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+
+    fn get_challenge(client: &Client, signer: &TestSigner) -> AuthChallenge {
+        let response: ServerResponse<AuthChallenge> = client
+            .get(format!("/auth/challenge?address={}", signer.address))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        response.data.unwrap()
+    }
+
+    #[test]
+    fn get_challenge_invalid_address_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client.get("/auth/challenge?address=asdf").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Address is not valid"));
+        client.terminate();
+    }
+
+    #[test]
+    fn login_invalid_signature_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let challenge = get_challenge(&client, &signer);
+        let body = format!(
+            r#"{{ "address": "{}", "nonce": "{}", "signature": "{}" }}"#,
+            signer.address,
+            challenge.nonce,
+            TestSigner::new(2).sign(challenge.message.as_bytes())
+        );
+        let response = client.post("/auth/login").body(body).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Invalid signature."));
+        client.terminate();
+    }
+
+    #[test]
+    fn login_reused_challenge_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let challenge = get_challenge(&client, &signer);
+        let body = format!(
+            r#"{{ "address": "{}", "nonce": "{}", "signature": "{}" }}"#,
+            signer.address,
+            challenge.nonce,
+            signer.sign(challenge.message.as_bytes())
+        );
+
+        let response = client.post("/auth/login").body(body.clone()).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        std::mem::drop(response);
+
+        let response = client.post("/auth/login").body(body).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Unknown or expired challenge."));
+        client.terminate();
+    }
+
+    #[test]
+    fn session_token_authenticates_requests() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let token = signer.sign_in(&client);
+        let body = format!(
+            r#"{{ "contract_address": "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", "network": "unknown", "user_address": "{}", "hidden": true }}"#,
+            signer.address
+        );
+        let response = client
+            .patch(uri!("/deployments"))
+            .header(Header::new("Authorization", format!("Bearer {}", token)))
+            .body(body)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Network not supported: unknown."));
+        client.terminate();
+    }
+
+    #[test]
+    fn invalid_session_token_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .patch(uri!("/deployments"))
+            .header(Header::new("Authorization", "Bearer not-a-token"))
+            .body(r#"{ }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Invalid session token."));
+        client.terminate();
+    }
+}
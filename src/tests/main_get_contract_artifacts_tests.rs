@@ -0,0 +1,315 @@
+#[cfg(test)]
+mod get_contract_artifacts_test {
+    use super::super::*;
+    use crate::models::api_models::ServerResponse;
+    use crate::models::db_models::Contract;
+    use crate::repository::Database;
+    use crate::test_server::rocket;
+    use crate::utils::artifacts::{etag, IMMUTABLE_CACHE_CONTROL};
+    use crate::utils::contract_abi::ContractAbi;
+    use crate::utils::scale_codec::{DecodedData, EncodedCall};
+    use crate::utils::storage_layout::{StorageLayout, UpgradeCompatibility, Verdict};
+    use crate::utils::wasm_text::ContractWat;
+    use rocket::http::{ContentType, Header, Status};
+    use rocket::local::asynchronous::Client;
+
+    const CODE_ID: &str = "d066340268269918605ad56139b35f4c2e421349b380166535f6ab17beeaf1fc";
+    const METADATA: &str = include_str!("fixtures/psp22_metadata.json");
+
+    // Client with a contract stored in the memory backend
+    async fn client() -> Client {
+        let client = Client::tracked(rocket())
+            .await
+            .expect("valid rocket instance");
+        let contract = Contract {
+            id: None,
+            code_id: CODE_ID.to_string(),
+            metadata: serde_json::from_str(METADATA).unwrap(),
+            wasm: b"\0asm\x01\0\0\0".to_vec(),
+            legacy_metadata: None,
+            validation: None,
+            size_report: None,
+        };
+        client
+            .rocket()
+            .state::<Database>()
+            .unwrap()
+            .create_contract(&contract)
+            .await
+            .unwrap();
+        client
+    }
+
+    #[rocket::async_test]
+    async fn wasm_is_served_raw() {
+        let client = client().await;
+        let response = client
+            .get(format!("/contract/{}/wasm", CODE_ID))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.content_type(),
+            Some(ContentType::new("application", "wasm"))
+        );
+        assert_eq!(
+            response.headers().get_one("Cache-Control"),
+            Some(IMMUTABLE_CACHE_CONTROL)
+        );
+        let etag_header = response.headers().get_one("ETag").map(String::from);
+        assert_eq!(etag_header, Some(etag(b"\0asm\x01\0\0\0")));
+        assert_eq!(
+            response.into_bytes().await.unwrap(),
+            b"\0asm\x01\0\0\0".to_vec()
+        );
+    }
+
+    #[rocket::async_test]
+    async fn metadata_is_served_as_json() {
+        let client = client().await;
+        let response = client
+            .get(format!("/contract/{}/metadata.json", CODE_ID))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        let metadata: serde_json::Value = response.into_json().await.unwrap();
+        assert_eq!(
+            metadata,
+            serde_json::from_str::<serde_json::Value>(METADATA).unwrap()
+        );
+    }
+
+    #[rocket::async_test]
+    async fn bundle_embeds_the_wasm() {
+        let client = client().await;
+        let response = client
+            .get(format!("/contract/{}/bundle.contract", CODE_ID))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        let bundle: serde_json::Value = response.into_json().await.unwrap();
+        assert_eq!(bundle["source"]["wasm"], "0x0061736d01000000");
+        assert_eq!(
+            bundle["source"]["hash"],
+            "0x481c66073400c0d24a4105fa7a82d47957485235ef10aaf1ef0635bece103e2a"
+        );
+        assert_eq!(bundle["contract"]["name"], "my_psp22");
+    }
+
+    #[rocket::async_test]
+    async fn matching_etag_is_not_modified() {
+        let client = client().await;
+        let response = client
+            .get(format!("/contract/{}/wasm", CODE_ID))
+            .header(Header::new(
+                "If-None-Match",
+                format!("\"other\", {}", etag(b"\0asm\x01\0\0\0")),
+            ))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::NotModified);
+        assert!(response.headers().get_one("ETag").is_some());
+        assert!(response.into_bytes().await.unwrap_or_default().is_empty());
+
+        let response = client
+            .get(format!("/contract/{}/wasm", CODE_ID))
+            .header(Header::new("If-None-Match", "\"other\""))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[rocket::async_test]
+    async fn legacy_metadata_is_opt_in() {
+        let client = client().await;
+        let response = client
+            .get(format!("/contract?code_id={}", CODE_ID))
+            .dispatch()
+            .await;
+        let json: ServerResponse<Contract> = response.into_json().await.unwrap();
+        let contract = json.data.unwrap();
+        assert_eq!(contract.metadata["contract"]["name"], "my_psp22");
+        assert_eq!(contract.legacy_metadata, None);
+
+        let response = client
+            .get(format!(
+                "/contract?code_id={}&legacy_metadata=true",
+                CODE_ID
+            ))
+            .dispatch()
+            .await;
+        let json: ServerResponse<Contract> = response.into_json().await.unwrap();
+        let legacy_metadata = json.data.unwrap().legacy_metadata.unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&legacy_metadata).unwrap(),
+            contract.metadata
+        );
+    }
+
+    #[rocket::async_test]
+    async fn abi_is_resolved_from_the_metadata() {
+        let client = client().await;
+        let response = client
+            .get(format!("/contract/{}/abi", CODE_ID))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        let json: ServerResponse<ContractAbi> = response.into_json().await.unwrap();
+        let abi = json.data.unwrap();
+        assert_eq!(abi.name, "my_psp22");
+        assert_eq!(abi.constructors[0].label, "new");
+        assert_eq!(abi.messages.len(), 5);
+    }
+
+    #[rocket::async_test]
+    async fn call_data_is_encoded_and_decoded() {
+        let client = client().await;
+        let response = client
+            .post(format!("/contract/{}/encode", CODE_ID))
+            .header(ContentType::JSON)
+            .body(r#"{ "message": "PSP22::balance_of", "args": { "owner": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY" } }"#)
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        let json: ServerResponse<EncodedCall> = response.into_json().await.unwrap();
+        let encoded = json.data.unwrap();
+        assert_eq!(
+            encoded.data,
+            "0x6568382fd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+
+        let response = client
+            .post(format!("/contract/{}/decode", CODE_ID))
+            .header(ContentType::JSON)
+            .body(format!(
+                r#"{{ "kind": "call", "data": "{}" }}"#,
+                encoded.data
+            ))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        let json: ServerResponse<DecodedData> = response.into_json().await.unwrap();
+        let decoded = json.data.unwrap();
+        assert_eq!(decoded.label, "PSP22::balance_of");
+        assert_eq!(
+            decoded.value["owner"],
+            "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+    }
+
+    #[rocket::async_test]
+    async fn invalid_calls_are_unprocessable() {
+        let client = client().await;
+        for body in [
+            r#"{ "args": {} }"#,
+            r#"{ "constructor": "new", "message": "deposit" }"#,
+            r#"{ "message": "missing" }"#,
+            r#"{ "constructor": "new", "args": { "initial_supply": true } }"#,
+        ] {
+            let response = client
+                .post(format!("/contract/{}/encode", CODE_ID))
+                .header(ContentType::JSON)
+                .body(body)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        }
+
+        let response = client
+            .post(format!("/contract/{}/decode", CODE_ID))
+            .header(ContentType::JSON)
+            .body(r#"{ "kind": "return", "data": "0x00" }"#)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[rocket::async_test]
+    async fn wasm_is_disassembled() {
+        let client = client().await;
+        let response = client
+            .get(format!("/contract/{}/wat", CODE_ID))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        let json: ServerResponse<ContractWat> = response.into_json().await.unwrap();
+        let contract_wat = json.data.unwrap();
+        assert!(contract_wat.wat.starts_with("(module"));
+        assert!(contract_wat.exports.is_empty());
+
+        let response = client
+            .get(format!("/contract/{}/wat?function=call", CODE_ID))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response
+            .into_string()
+            .await
+            .unwrap()
+            .contains("Function call not found."));
+    }
+
+    #[rocket::async_test]
+    async fn storage_layouts_are_compared() {
+        let client = client().await;
+        let response = client
+            .get(format!("/contract/{}/storage", CODE_ID))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        let json: ServerResponse<StorageLayout> = response.into_json().await.unwrap();
+        let layout = json.data.unwrap();
+        assert_eq!(layout.cells.len(), 2);
+        assert_eq!(layout.cells[1].path, "balances");
+
+        let response = client
+            .get(format!("/contract/{}/upgrade/{}", CODE_ID, CODE_ID))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let json: ServerResponse<UpgradeCompatibility> = response.into_json().await.unwrap();
+        assert_eq!(json.data.unwrap().verdict, Verdict::Compatible);
+
+        let response = client
+            .get(format!("/contract/{}/upgrade/1", CODE_ID))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn missing_contract_is_not_found() {
+        let client = client().await;
+        for artifact in [
+            "wasm",
+            "metadata.json",
+            "bundle.contract",
+            "abi",
+            "wat",
+            "storage",
+        ] {
+            let response = client
+                .get(format!("/contract/1/{}", artifact))
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::NotFound);
+            assert!(response
+                .into_string()
+                .await
+                .unwrap()
+                .contains("Contract not found."));
+        }
+    }
+}
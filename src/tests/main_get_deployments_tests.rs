@@ -1,8 +1,8 @@
 #[cfg(test)]
 mod get_deployments_test {
     use super::super::*;
-    use crate::MongoRepo;
-    use mongodb::bson::doc;
+    use crate::test_server::rocket;
+    use crate::test_signer::TestSigner;
     use rocket::http::Status;
     use rocket::local::blocking::Client;
 
@@ -57,15 +57,11 @@ mod get_deployments_test {
         client.terminate();
     }
 
-    // Since the database is not mocked, this test will fail if the user has something already deployed
     #[test]
     fn get_deployments_no_user_address_leads_to_empty_data_no_error() {
         let unused = "5FrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let url = format!(
-            "/deployments?user_address={}&network={}",
-            unused, "some_network"
-        );
+        let url = format!("/deployments?user_address={}&network={}", unused, "rococo");
         let response = client.get(url).dispatch();
         assert_eq!(response.status(), Status::Ok);
         assert_eq!(response.status(), Status::Ok);
@@ -79,11 +75,17 @@ mod get_deployments_test {
     #[test]
     fn get_deployments_matching_routes_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let db = client.rocket().state::<MongoRepo>().unwrap();
-        client.post(uri!("/deployments")).body(r#"{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutnn", "network": "some_network", "code_id": "some_impossible_id", "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }"#).dispatch();
+        let signer = TestSigner::new(1);
+        let body = format!(
+            r#"{{ "contract_address": "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", "network": "rococo", "code_id": "some_impossible_id", "user_address": "{}", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }}"#,
+            signer.address
+        );
+        signer
+            .sign_request(client.post(uri!("/deployments")), body)
+            .dispatch();
         let url = format!(
             "/deployments?user_address={}&network={}",
-            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "some_network"
+            signer.address, "rococo"
         );
         let response = client.get(url).dispatch();
 
@@ -91,12 +93,7 @@ mod get_deployments_test {
         assert!(response
             .into_string()
             .unwrap()
-            .contains("{\"contract_name\":null,\"contract_address\":\"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutnn\",\"network\":\"some_network\",\"code_id\":\"some_impossible_id\",\"user_address\":\"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY\",\"tx_hash\":null,\"date\":\"2021-03-03T15:00:00.000Z\",\"contract_type\":\"custom\",\"external_abi\":null,\"hidden\":false}"));
-        let db_res = db.deployments.delete_one(
-            doc! {"contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutnn","network": "some_network", "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"},
-            None,
-        );
-        assert!(db_res.is_ok());
+            .contains(&format!("{{\"contract_name\":null,\"contract_address\":\"5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty\",\"network\":\"rococo\",\"code_id\":\"some_impossible_id\",\"user_address\":\"{}\",\"tx_hash\":null,\"date\":\"2021-03-03T15:00:00.000Z\",\"contract_type\":\"custom\",\"external_abi\":null,\"hidden\":false}}", signer.address)));
         client.terminate();
     }
 }
@@ -1,30 +1,34 @@
 #[cfg(test)]
 mod post_contract_test {
     use super::super::*;
-    use crate::models::api_models::ServerResponse;
+    use crate::api::contract_api::{contract_code_id, hash_code};
+    use crate::models::api_models::{OptimizationLevel, ServerResponse, WizardMessage};
     use crate::models::db_models::Contract;
-    use crate::MongoRepo;
-    use mongodb::bson::doc;
-    use rocket::http::Status;
+    use crate::repository::Database;
+    use crate::test_server::rocket;
+    use crate::test_signer::TestSigner;
+    use rocket::http::{ContentType, Header, Status};
     use rocket::local::blocking::Client;
 
     const VALID_INK_SC: &str = r#"#![cfg_attr(not(feature = \"std\"), no_std)] #![feature(min_specialization)] #[openbrush::contract] pub mod my_psp21 { use openbrush::contracts::psp22::*; use openbrush::traits::Storage; #[ink(storage)] #[derive(Default, Storage)] pub struct Contract { #[storage_field] psp22: psp22::Data, } impl PSP22 for Contract {} impl Contract { #[ink(constructor)] pub fn new(initial_supply: Balance) -> Self { let mut _instance = Self::default(); _instance._mint_to(_instance.env().caller(), initial_supply); _instance } } }"#;
 
     #[test]
-    fn post_contract_missing_address_error() {
+    fn post_contract_unauthenticated_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
         let response = client.post(uri!("/contract")).body(r#"{ }"#).dispatch();
-        assert_eq!(response.status(), Status::UnprocessableEntity);
-        std::mem::drop(response);
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Missing session token or request signature."));
         client.terminate();
     }
 
     #[test]
     fn post_contract_missing_no_code_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let response = client
-            .post(uri!("/contract"))
-            .body(r#"{ "address": "asdf" }"#)
+        let response = TestSigner::new(1)
+            .sign_request(client.post(uri!("/contract")), r#"{ "address": "asdf" }"#)
             .dispatch();
         assert_eq!(response.status(), Status::UnprocessableEntity);
         std::mem::drop(response);
@@ -34,9 +38,11 @@ mod post_contract_test {
     #[test]
     fn post_contract_missing_no_features_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let response = client
-            .post(uri!("/contract"))
-            .body(r#"{ "address": "address", "code": "something" }"#)
+        let response = TestSigner::new(1)
+            .sign_request(
+                client.post(uri!("/contract")),
+                r#"{ "address": "address", "code": "something" }"#,
+            )
             .dispatch();
         assert_eq!(response.status(), Status::UnprocessableEntity);
         std::mem::drop(response);
@@ -46,9 +52,11 @@ mod post_contract_test {
     #[test]
     fn post_contract_expected_features_to_be_an_array() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let response = client
-            .post(uri!("/contract"))
-            .body(r#"{ "address": "address", "code": "something", "features": "asdf" }"#)
+        let response = TestSigner::new(1)
+            .sign_request(
+                client.post(uri!("/contract")),
+                r#"{ "address": "address", "code": "something", "features": "asdf" }"#,
+            )
             .dispatch();
         assert_eq!(response.status(), Status::UnprocessableEntity);
         std::mem::drop(response);
@@ -56,26 +64,28 @@ mod post_contract_test {
     }
 
     #[test]
-    fn post_contract_expects_a_valid_len_address() {
+    fn post_contract_ignores_the_body_address() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let token = signer.sign_in(&client);
         let response = client
             .post(uri!("/contract"))
+            .header(Header::new("Authorization", format!("Bearer {}", token)))
             .body(r#"{ "address": "address", "code": "something", "features": ["asdf"] }"#)
             .dispatch();
         assert_eq!(response.status(), Status::InternalServerError);
         assert!(response
             .into_string()
             .unwrap()
-            .contains("Address is not valid."));
+            .contains("Feature not allowed"));
         client.terminate();
     }
 
     #[test]
     fn post_contract_expects_a_valid_feature() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let response = client
-            .post(uri!("/contract"))
-            .body(r#"{ "address": "4GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "code": "something", "features": ["asdf"] }"#)
+        let response = TestSigner::new(1)
+            .sign_request(client.post(uri!("/contract")), r#"{ "address": "4GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "code": "something", "features": ["asdf"] }"#)
             .dispatch();
         assert_eq!(response.status(), Status::InternalServerError);
         assert!(response
@@ -93,32 +103,150 @@ mod post_contract_test {
             r#"{{ "address": "4GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "code": "{}", "features": ["psp22"] }}"#,
             one_mb_string
         );
-        let response = client.post(uri!("/contract")).body(body).dispatch();
+        let response = TestSigner::new(1)
+            .sign_request(client.post(uri!("/contract")), body)
+            .dispatch();
         assert_eq!(response.status(), Status::InternalServerError);
         let res_str = response.into_string().unwrap();
         assert!(res_str.contains("Code size too big."));
         client.terminate();
     }
 
+    #[test]
+    fn post_contract_expects_a_supported_network() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = TestSigner::new(1)
+            .sign_request(client.post(uri!("/contract")), r#"{ "address": "4GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "code": "something", "features": ["psp22"], "network": "some_network" }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Network not supported: some_network."));
+        client.terminate();
+    }
+
+    #[test]
+    fn post_contract_expects_a_known_optimization_level() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = TestSigner::new(1)
+            .sign_request(client.post(uri!("/contract")), r#"{ "address": "4GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "code": "something", "features": ["psp22"], "build": { "optimization": "O9" } }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        std::mem::drop(response);
+        client.terminate();
+    }
+
+    #[test]
+    fn build_options_are_part_of_the_code_id() {
+        let mut wizard_message: WizardMessage = serde_json::from_str(
+            r#"{ "code": "something", "features": ["psp22"], "build": { "optimization": "Oz" } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            contract_code_id(&wizard_message),
+            hash_code(&wizard_message.code)
+        );
+
+        wizard_message.build.optimization = OptimizationLevel::Disabled;
+        let unoptimized = contract_code_id(&wizard_message);
+        assert_ne!(unoptimized, hash_code(&wizard_message.code));
+
+        wizard_message.build.keep_debug_names = true;
+        assert_ne!(contract_code_id(&wizard_message), unoptimized);
+    }
+
+    #[test]
+    fn generate_contract_expects_a_supported_spec() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post(uri!("/contract/generate"))
+            .body(r#"{ "standard": "psp22", "name": "My Token" }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        std::mem::drop(response);
+
+        let response = TestSigner::new(1)
+            .sign_request(
+                client.post(uri!("/contract/generate")),
+                r#"{ "standard": "psp34", "name": "My Token", "decimals": 6 }"#,
+            )
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("decimals is not used by psp34, only by psp22."));
+        client.terminate();
+    }
+
+    #[test]
+    fn generated_source_is_rendered() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post(uri!("/contract/generate/source"))
+            .header(ContentType::JSON)
+            .body(r#"{ "standard": "psp22", "extensions": ["ownable"], "name": "My Token", "symbol": "MTK", "initial_supply": "1000" }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let json: ServerResponse<String> = response.into_json().unwrap();
+        let code = json.data.unwrap();
+        assert!(code.contains("pub mod my_token {"));
+        assert!(code.contains("impl Ownable for Contract {}"));
+        assert!(code.contains("._mint_to(_instance.env().caller(), 1000)"));
+        client.terminate();
+    }
+
     #[test]
     fn post_contract_expects_code_is_ok() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let db = client.rocket().state::<MongoRepo>().unwrap();
         let body = format!(
             r#"{{ "address": "4GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "code": "{}", "features": ["psp22"] }}"#,
             VALID_INK_SC
         );
-        let response = client.post(uri!("/contract")).body(body).dispatch();
+        let response = TestSigner::new(1)
+            .sign_request(client.post(uri!("/contract")), body)
+            .dispatch();
         assert_eq!(response.status(), Status::Ok);
 
         let json: ServerResponse<Contract> = response.into_json().unwrap();
         let contract = json.data.unwrap();
 
-        let db_res = db
-            .contracts
-            .delete_one(doc! {"code_id": contract.code_id}, None)
+        // The compiled contract is stored
+        let url = format!("/contract?code_id={}", contract.code_id);
+        assert_eq!(client.get(url).dispatch().status(), Status::Ok);
+        client.terminate();
+    }
+
+    #[test]
+    fn post_contract_stored_contract_invalid_for_the_network_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let body = r#"{ "address": "4GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "code": "stored", "features": ["psp22"], "network": "rococo" }"#;
+        let wizard_message: WizardMessage = serde_json::from_str(body).unwrap();
+        let contract = Contract {
+            id: None,
+            code_id: contract_code_id(&wizard_message),
+            metadata: serde_json::Value::Null,
+            wasm: b"\0asm\x01\0\0\0".to_vec(),
+            legacy_metadata: None,
+            validation: None,
+            size_report: None,
+        };
+        let db = client.rocket().state::<Database>().unwrap();
+        rocket::tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(db.create_contract(&contract))
             .unwrap();
-        assert_eq!(db_res.deleted_count, 1);
+
+        let response = TestSigner::new(1)
+            .sign_request(client.post(uri!("/contract")), body)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Contract rejected by the wasm validation"));
         client.terminate();
     }
 }
@@ -1,15 +1,75 @@
 #[cfg(test)]
 mod post_deployments_test {
     use super::super::*;
-    use crate::MongoRepo;
-    use mongodb::bson::doc;
+    use crate::models::api_models::ServerResponse;
+    use crate::models::db_models::Deployment;
+    use crate::test_server::rocket;
+    use crate::test_signer::TestSigner;
     use rocket::http::Status;
     use rocket::local::blocking::Client;
 
+    // Deployments of the user in rococo, as returned by the API
+    fn get_deployments(client: &Client, user_address: &str) -> Vec<Deployment> {
+        let url = format!("/deployments?user_address={}&network=rococo", user_address);
+        let response: ServerResponse<Vec<Deployment>> =
+            client.get(url).dispatch().into_json().unwrap();
+        response.data.unwrap()
+    }
+
     #[test]
-    fn post_deployments_missing_address_error() {
+    fn post_deployments_missing_signature_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
         let response = client.post(uri!("/deployments")).body(r#"{ }"#).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Missing session token or request signature."));
+        client.terminate();
+    }
+
+    #[test]
+    fn post_deployments_replayed_signature_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let request = signer.sign_request(client.post(uri!("/deployments")), r#"{ }"#);
+
+        let response = request.clone().dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        std::mem::drop(response);
+
+        let response = request.dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Signature nonce already used."));
+        client.terminate();
+    }
+
+    #[test]
+    fn post_deployments_tampered_body_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let response = signer
+            .sign_request(client.post(uri!("/deployments")), r#"{ }"#)
+            .body(r#"{ "contract_address": "some_address" }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Request body does not match the signature."));
+        client.terminate();
+    }
+
+    #[test]
+    fn post_deployments_missing_address_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let response = signer
+            .sign_request(client.post(uri!("/deployments")), r#"{ }"#)
+            .dispatch();
         assert_eq!(response.status(), Status::UnprocessableEntity);
         assert!(response.into_string().unwrap().contains(
             "The request was well-formed but was unable to be followed due to semantic errors."
@@ -20,9 +80,12 @@ mod post_deployments_test {
     #[test]
     fn post_deployments_missing_network_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let response = client
-            .post(uri!("/deployments"))
-            .body(r#"{ "contract_address": "some_address" }"#)
+        let signer = TestSigner::new(1);
+        let response = signer
+            .sign_request(
+                client.post(uri!("/deployments")),
+                r#"{ "contract_address": "some_address" }"#,
+            )
             .dispatch();
         assert_eq!(response.status(), Status::UnprocessableEntity);
         assert!(response.into_string().unwrap().contains(
@@ -34,9 +97,12 @@ mod post_deployments_test {
     #[test]
     fn post_deployments_missing_code_id_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let response = client
-            .post(uri!("/deployments"))
-            .body(r#"{ "contract_address": "some_address", "network": "some_network" }"#)
+        let signer = TestSigner::new(1);
+        let response = signer
+            .sign_request(
+                client.post(uri!("/deployments")),
+                r#"{ "contract_address": "some_address", "network": "rococo" }"#,
+            )
             .dispatch();
         assert_eq!(response.status(), Status::UnprocessableEntity);
         assert!(response.into_string().unwrap().contains(
@@ -48,7 +114,8 @@ mod post_deployments_test {
     #[test]
     fn post_deployments_missing_user_address_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let response = client.post(uri!("/deployments")).body(r#"{ "contract_address": "some_address", "network": "some_network", "code_id": "some_id" }"#).dispatch();
+        let signer = TestSigner::new(1);
+        let response = signer.sign_request(client.post(uri!("/deployments")), r#"{ "contract_address": "some_address", "network": "rococo", "code_id": "some_id" }"#).dispatch();
         assert_eq!(response.status(), Status::UnprocessableEntity);
         assert!(response.into_string().unwrap().contains(
             "The request was well-formed but was unable to be followed due to semantic errors."
@@ -59,65 +126,166 @@ mod post_deployments_test {
     #[test]
     fn post_deployments_invalid_address_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let response = client.post(uri!("/deployments")).body(r#"{ "contract_address": "some_address", "network": "some_network", "code_id": "some_id", "user_address": "some_user_address", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }"#).dispatch();
+        let signer = TestSigner::new(1);
+        let response = signer.sign_request(client.post(uri!("/deployments")), r#"{ "contract_address": "some_address", "network": "rococo", "code_id": "some_id", "user_address": "some_user_address", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }"#).dispatch();
         assert_eq!(response.status(), Status::InternalServerError);
         assert!(response
             .into_string()
             .unwrap()
-            .contains("Invalid address length"));
+            .contains("Invalid user_address: invalid base58 encoding."));
         client.terminate();
     }
 
     #[test]
     fn post_deployments_contract_address_error() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let response = client.post(uri!("/deployments")).body(r#"{ "contract_address": "some_address", "network": "some_network", "code_id": "some_id", "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "date":"2021-03-03T15:00:00.000Z", "contract_type": "psp22" }"#).dispatch();
+        let signer = TestSigner::new(1);
+        let body = format!(
+            r#"{{ "contract_address": "some_address", "network": "rococo", "code_id": "some_id", "user_address": "{}", "date":"2021-03-03T15:00:00.000Z", "contract_type": "psp22" }}"#,
+            signer.address
+        );
+        let response = signer
+            .sign_request(client.post(uri!("/deployments")), body)
+            .dispatch();
         assert_eq!(response.status(), Status::InternalServerError);
         assert!(response
             .into_string()
             .unwrap()
-            .contains("Invalid address length"));
+            .contains("Invalid contract_address: invalid base58 encoding."));
+        client.terminate();
+    }
+
+    #[test]
+    fn post_deployments_unknown_network_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let body = format!(
+            r#"{{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "some_network", "code_id": "some_id", "user_address": "{}", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }}"#,
+            signer.address
+        );
+        let response = signer
+            .sign_request(client.post(uri!("/deployments")), body)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Network not supported: some_network."));
+        client.terminate();
+    }
+
+    #[test]
+    fn post_deployments_signed_by_another_address_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let response = signer.sign_request(client.post(uri!("/deployments")), r#"{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "rococo", "code_id": "some_id", "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }"#).dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Signature address does not match the user address."));
         client.terminate();
     }
 
     #[test]
     fn post_deployments_empty_data_is_ok() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let db = client.rocket().state::<MongoRepo>().unwrap();
-        let response = client.post(uri!("/deployments")).body(r#"{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "some_network", "code_id": "some_id", "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }"#).dispatch();
+        let signer = TestSigner::new(1);
+        let body = format!(
+            r#"{{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "rococo", "code_id": "some_id", "user_address": "{}", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }}"#,
+            signer.address
+        );
+        let response = signer
+            .sign_request(client.post(uri!("/deployments")), body)
+            .dispatch();
         // status ok means that the deployment was stored in the database
         assert_eq!(response.status(), Status::Ok);
 
-        let db_res = db.deployments.delete_one(
-            doc! {"contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY","network": "some_network", "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"},
-            None,
-        );
-        assert!(db_res.is_ok());
+        let deployments = get_deployments(&client, &signer.address);
+        assert_eq!(deployments.len(), 1);
         std::mem::drop(response);
         client.terminate();
     }
 
+    #[test]
+    fn post_deployments_with_salt_of_unknown_contract_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let body = format!(
+            r#"{{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "rococo", "code_id": "some_id", "user_address": "{}", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom", "salt": "0x01", "constructor_data": "0x9bae9d5e" }}"#,
+            signer.address
+        );
+        let response = signer
+            .sign_request(client.post(uri!("/deployments")), body)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("contract some_id was not compiled by this service"));
+
+        assert!(get_deployments(&client, &signer.address).is_empty());
+        client.terminate();
+    }
+
     #[test]
     fn patch_deployments_update_is_ok() {
         let client = Client::tracked(rocket()).expect("valid rocket instance");
-        let db = client.rocket().state::<MongoRepo>().unwrap();
+        let signer = TestSigner::new(1);
 
-        let response = client.post(uri!("/deployments")).body(r#"{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "some_network", "code_id": "some_id", "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }"#).dispatch();
+        let body = format!(
+            r#"{{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "rococo", "code_id": "some_id", "user_address": "{}", "date":"2021-03-03T15:00:00.000Z", "contract_type":"custom" }}"#,
+            signer.address
+        );
+        let response = signer
+            .sign_request(client.post(uri!("/deployments")), body)
+            .dispatch();
         // status ok means that the deployment was stored in the database
         assert_eq!(response.status(), Status::Ok);
         std::mem::drop(response);
 
-        let response = client.patch(uri!("/deployments")).body(r#"{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "some_network",  "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",  "contract_name":"name", "hidden": true}"#).dispatch();
+        let body = format!(
+            r#"{{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "rococo",  "user_address": "{}",  "contract_name":"name", "hidden": true}}"#,
+            signer.address
+        );
+        let response = signer
+            .sign_request(client.patch(uri!("/deployments")), body)
+            .dispatch();
         // status ok means that the deployment was updated in the database
         assert_eq!(response.status(), Status::Ok);
         std::mem::drop(response);
 
-        // Cleanup
-        let db_res = db.deployments.delete_one(
-            doc! {"contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY","network": "some_network", "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"},
-            None,
+        let deployments = get_deployments(&client, &signer.address);
+        assert_eq!(deployments[0].contract_name, Some(String::from("name")));
+        assert!(deployments[0].hidden);
+        client.terminate();
+    }
+
+    #[test]
+    fn patch_deployments_not_found_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let signer = TestSigner::new(1);
+        let body = format!(
+            r#"{{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "rococo",  "user_address": "{}",  "contract_name":"name", "hidden": true}}"#,
+            signer.address
         );
-        assert!(db_res.is_ok());
+        let response = signer
+            .sign_request(client.patch(uri!("/deployments")), body)
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("Deployment not found."));
+        client.terminate();
+    }
+
+    #[test]
+    fn patch_deployments_missing_signature_error() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client.patch(uri!("/deployments")).body(r#"{ "contract_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "network": "rococo",  "user_address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",  "contract_name":"name", "hidden": true}"#).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        std::mem::drop(response);
         client.terminate();
     }
 }
@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod memory_repo_tests {
+    use super::super::*;
+    use crate::models::db_models::{ApiKeyScope, JobStatus};
+    use crate::utils::compiler::BuildLog;
+
+    fn deployment(user_address: &str, network: &str, contract_address: &str) -> Deployment {
+        Deployment {
+            id: None,
+            contract_name: None,
+            contract_address: contract_address.to_string(),
+            network: network.to_string(),
+            code_id: String::from("code"),
+            user_address: user_address.to_string(),
+            tx_hash: None,
+            date: String::from("2023-01-01T00:00:00.000Z"),
+            contract_type: String::from("custom"),
+            external_abi: None,
+            hidden: false,
+        }
+    }
+
+    fn query(network: Option<&str>, contract_address: Option<&str>) -> GetDeploymentsMessage {
+        GetDeploymentsMessage {
+            user_address: String::from("alice"),
+            network: network.map(String::from),
+            contract_address: contract_address.map(String::from),
+        }
+    }
+
+    #[rocket::async_test]
+    async fn first_contract_of_a_code_id_is_kept() {
+        let repo = MemoryRepo::new();
+        let contract = Contract {
+            id: None,
+            code_id: String::from("code"),
+            metadata: serde_json::json!({"name": "first"}),
+            wasm: vec![0, 97, 115, 109],
+            legacy_metadata: None,
+            validation: None,
+            size_report: None,
+        };
+        repo.create_contract(&contract).await.unwrap();
+        repo.create_contract(&Contract {
+            metadata: serde_json::json!({"name": "second"}),
+            ..contract.clone()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            repo.get_contract_by_hash("code").await.unwrap(),
+            Some(contract)
+        );
+        assert_eq!(repo.get_contract_by_hash("other").await.unwrap(), None);
+    }
+
+    #[rocket::async_test]
+    async fn deployments_are_filtered() {
+        let repo = MemoryRepo::new();
+        repo.create_deployment(&deployment("alice", "rococo", "a"))
+            .await
+            .unwrap();
+        repo.create_deployment(&deployment("alice", "shibuya", "b"))
+            .await
+            .unwrap();
+        repo.create_deployment(&deployment("bob", "rococo", "c"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repo.get_deployments(&query(None, None))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            repo.get_deployments(&query(Some(""), Some("")))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            repo.get_deployments(&query(Some("rococo"), None))
+                .await
+                .unwrap()[0]
+                .contract_address,
+            "a"
+        );
+        assert!(repo
+            .get_deployments(&query(Some("rococo"), Some("b")))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[rocket::async_test]
+    async fn deployment_update_reports_missing_deployments() {
+        let repo = MemoryRepo::new();
+        repo.create_deployment(&deployment("alice", "rococo", "a"))
+            .await
+            .unwrap();
+
+        let mut update = UpdateDeployMessage {
+            contract_address: String::from("a"),
+            network: String::from("rococo"),
+            user_address: String::from("alice"),
+            contract_name: Some(String::from("name")),
+            hidden: true,
+        };
+        assert!(repo.update_deployment(&update).await.unwrap());
+
+        let stored = &repo.get_deployments(&query(None, None)).await.unwrap()[0];
+        assert_eq!(stored.contract_name, Some(String::from("name")));
+        assert!(stored.hidden);
+
+        update.network = String::from("shibuya");
+        assert!(!repo.update_deployment(&update).await.unwrap());
+    }
+
+    #[rocket::async_test]
+    async fn jobs_are_found_by_id() {
+        let repo = MemoryRepo::new();
+        let job = Job::new(
+            "job",
+            "code",
+            "alice",
+            JobStatus::Succeeded,
+            &BuildLog::default(),
+        );
+        repo.create_job(&job).await.unwrap();
+
+        assert_eq!(repo.get_job("job").await.unwrap(), Some(job));
+        assert_eq!(repo.get_job("other").await.unwrap(), None);
+    }
+
+    #[rocket::async_test]
+    async fn api_keys_are_revoked_and_count_usage() {
+        let repo = MemoryRepo::new();
+        repo.create_api_key(&ApiKey::new(
+            "id",
+            "hash",
+            "ci",
+            vec![ApiKeyScope::Read],
+            60,
+        ))
+        .await
+        .unwrap();
+
+        repo.record_api_key_usage("id", "2023-01-01T00:00:00Z")
+            .await
+            .unwrap();
+        assert!(repo.revoke_api_key("id").await.unwrap());
+        assert!(!repo.revoke_api_key("other").await.unwrap());
+
+        let api_key = repo.get_api_key_by_hash("hash").await.unwrap().unwrap();
+        assert!(api_key.revoked);
+        assert_eq!(api_key.usage_count, 1);
+        assert_eq!(
+            api_key.last_used_at,
+            Some(String::from("2023-01-01T00:00:00Z"))
+        );
+        assert_eq!(repo.get_api_keys().await.unwrap().len(), 1);
+    }
+
+    #[rocket::async_test]
+    async fn compile_quotas_are_counted_per_day() {
+        let repo = MemoryRepo::new();
+
+        assert_eq!(
+            repo.increment_compile_quota("alice", "2023-01-01")
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            repo.increment_compile_quota("alice", "2023-01-01")
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            repo.increment_compile_quota("bob", "2023-01-01")
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            repo.increment_compile_quota("alice", "2023-01-02")
+                .await
+                .unwrap(),
+            1
+        );
+    }
+}
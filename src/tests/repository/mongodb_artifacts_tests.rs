@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod mongodb_artifacts_tests {
+    use super::super::*;
+    use mongodb::bson::{oid::ObjectId, to_document, Bson};
+
+    fn wasm() -> Vec<u8> {
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        wasm.extend([7u8; 4096]);
+        wasm
+    }
+
+    #[test]
+    fn compressed_artifacts_are_restored() {
+        let (encoding, encoded) = encode(&wasm(), ArtifactCompression::Zstd).unwrap();
+
+        assert_eq!(encoding, ArtifactEncoding::Zstd);
+        assert!(encoded.len() < wasm().len());
+        assert_eq!(
+            decode(encoding, &encoded, wasm().len() as i64).unwrap(),
+            wasm()
+        );
+    }
+
+    #[test]
+    fn artifacts_are_kept_raw_when_compression_does_not_help() {
+        let (encoding, encoded) = encode(b"\0asm", ArtifactCompression::Zstd).unwrap();
+        assert_eq!(encoding, ArtifactEncoding::Raw);
+        assert_eq!(encoded, b"\0asm");
+
+        let (encoding, encoded) = encode(&wasm(), ArtifactCompression::None).unwrap();
+        assert_eq!(encoding, ArtifactEncoding::Raw);
+        assert_eq!(encoded, wasm());
+    }
+
+    #[test]
+    fn size_mismatch_is_an_error() {
+        let (encoding, encoded) = encode(&wasm(), ArtifactCompression::Zstd).unwrap();
+
+        assert!(decode(encoding, &encoded, 10).is_err());
+        assert!(decode(ArtifactEncoding::Zstd, b"not zstd", 8).is_err());
+    }
+
+    #[test]
+    fn large_artifacts_are_stored_in_gridfs() {
+        let config = ArtifactConfig {
+            compression: ArtifactCompression::Zstd,
+            gridfs_threshold: 1024,
+        };
+
+        assert!(!is_stored_in_gridfs(1024, &config));
+        assert!(is_stored_in_gridfs(1025, &config));
+    }
+
+    // The client only connects when the GridFS bucket is used
+    async fn store(gridfs_threshold: usize) -> ArtifactStore {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let config = ArtifactConfig {
+            compression: ArtifactCompression::Zstd,
+            gridfs_threshold,
+        };
+        ArtifactStore::new(&client.database("ContractWizard"), "Artifacts", &config)
+    }
+
+    #[rocket::async_test]
+    async fn contracts_are_restored_from_their_documents() {
+        let store = store(1024 * 1024).await;
+        let contract = Contract {
+            id: None,
+            code_id: String::from("code_id"),
+            metadata: serde_json::json!({"source": {"hash": "0x01"}, "types": [{"id": 0}]}),
+            wasm: wasm(),
+            legacy_metadata: None,
+            validation: None,
+            size_report: None,
+        };
+
+        let document = store.to_document(&contract).await.unwrap();
+        let metadata = document.metadata.clone().unwrap();
+        assert_eq!(
+            metadata.get_document("source").unwrap().get_str("hash"),
+            Ok("0x01")
+        );
+        assert!(document.metadata_artifact.is_none());
+        assert!(document.wasm.data.is_some());
+
+        assert_eq!(store.to_contract(document).await.unwrap(), contract);
+    }
+
+    #[test]
+    fn artifacts_are_serialized_as_binary() {
+        let artifact = StoredArtifact {
+            encoding: ArtifactEncoding::Raw,
+            size: 4,
+            data: Some(Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: b"\0asm".to_vec(),
+            }),
+            gridfs_id: None,
+        };
+        let document = to_document(&artifact).unwrap();
+
+        assert!(matches!(document.get("data"), Some(Bson::Binary(_))));
+        assert_eq!(document.get_str("encoding").unwrap(), "raw");
+        assert!(!document.contains_key("gridfs_id"));
+
+        let artifact = StoredArtifact {
+            data: None,
+            gridfs_id: Some(ObjectId::new()),
+            ..artifact
+        };
+        let document = to_document(&artifact).unwrap();
+        assert!(matches!(document.get("gridfs_id"), Some(Bson::ObjectId(_))));
+        assert!(!document.contains_key("data"));
+    }
+}
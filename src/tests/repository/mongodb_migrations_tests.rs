@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod mongodb_migrations_tests {
+    use super::super::*;
+    use crate::utils::network_registry::Network;
+
+    #[test]
+    fn versions_are_sequential() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, index + 1);
+        }
+    }
+
+    #[test]
+    fn only_newer_migrations_are_pending() {
+        assert_eq!(pending_migrations(0).count(), MIGRATIONS.len());
+
+        let pending: Vec<u32> = pending_migrations(2).map(|m| m.version).collect();
+        assert_eq!(pending, vec![3, 4, 5, 6, 7]);
+
+        let latest = MIGRATIONS.len() as u32;
+        assert_eq!(pending_migrations(latest).count(), 0);
+    }
+
+    #[test]
+    fn network_names_are_renamed_to_the_registry_id() {
+        let rococo = Network {
+            id: String::from("rococo"),
+            name: String::from("Rococo Contracts"),
+            aliases: vec![String::from("rococo-testnet")],
+            ss58_prefix: 42,
+            max_code_size: 131072,
+            toolchain_profiles: vec![],
+            explorer: Default::default(),
+        };
+        let networks = NetworkRegistry::new(vec![rococo]).unwrap();
+        let names: Vec<String> = ["rococo", "Rococo", "rococo-testnet", "unknown"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        assert_eq!(
+            network_renames(&names, &networks),
+            vec![
+                (String::from("Rococo"), String::from("rococo")),
+                (String::from("rococo-testnet"), String::from("rococo")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod sqlite_repo_tests {
+    use super::super::*;
+    use crate::models::db_models::{ApiKeyScope, JobStatus};
+    use crate::utils::compiler::BuildLog;
+    use std::fs;
+    use uuid::Uuid;
+
+    fn repo() -> SqliteRepo {
+        SqliteRepo::open(Path::new(":memory:")).unwrap()
+    }
+
+    fn deployment(user_address: &str, network: &str, contract_address: &str) -> Deployment {
+        Deployment {
+            id: None,
+            contract_name: None,
+            contract_address: contract_address.to_string(),
+            network: network.to_string(),
+            code_id: String::from("code"),
+            user_address: user_address.to_string(),
+            tx_hash: Some(String::from("0x01")),
+            date: String::from("2023-01-01T00:00:00.000Z"),
+            contract_type: String::from("custom"),
+            external_abi: None,
+            hidden: false,
+        }
+    }
+
+    fn query(network: Option<&str>, contract_address: Option<&str>) -> GetDeploymentsMessage {
+        GetDeploymentsMessage {
+            user_address: String::from("alice"),
+            network: network.map(String::from),
+            contract_address: contract_address.map(String::from),
+        }
+    }
+
+    #[rocket::async_test]
+    async fn migrations_are_applied_once() {
+        let path = std::env::temp_dir().join(format!("{}.db", Uuid::new_v4()));
+
+        let repo = SqliteRepo::open(&path).unwrap();
+        repo.create_deployment(&deployment("alice", "rococo", "a"))
+            .await
+            .unwrap();
+        std::mem::drop(repo);
+
+        // Reopening keeps the data and does not run the migrations again
+        let repo = SqliteRepo::open(&path).unwrap();
+        let version: usize = repo
+            .connection()
+            .unwrap()
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        assert_eq!(
+            repo.get_deployments(&query(None, None))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        std::mem::drop(repo);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[rocket::async_test]
+    async fn wasm_is_stored_as_a_blob() {
+        let repo = repo();
+        let contract = Contract {
+            id: None,
+            code_id: String::from("code"),
+            metadata: serde_json::json!({"source": {"hash": "0x01"}}),
+            wasm: vec![0, 97, 115, 109, 1, 0, 0, 0],
+            legacy_metadata: None,
+            validation: None,
+            size_report: None,
+        };
+        repo.create_contract(&contract).await.unwrap();
+        repo.create_contract(&Contract {
+            wasm: Vec::new(),
+            ..contract.clone()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            repo.get_contract_by_hash("code").await.unwrap(),
+            Some(contract)
+        );
+        assert_eq!(repo.get_contract_by_hash("other").await.unwrap(), None);
+    }
+
+    #[rocket::async_test]
+    async fn deployments_are_filtered_as_in_mongo() {
+        let repo = repo();
+        repo.create_deployment(&deployment("alice", "rococo", "a"))
+            .await
+            .unwrap();
+        repo.create_deployment(&deployment("alice", "shibuya", "b"))
+            .await
+            .unwrap();
+        repo.create_deployment(&deployment("bob", "rococo", "c"))
+            .await
+            .unwrap();
+
+        let all = repo.get_deployments(&query(None, None)).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0], deployment("alice", "rococo", "a"));
+        assert_eq!(
+            repo.get_deployments(&query(Some(""), Some("")))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            repo.get_deployments(&query(Some("shibuya"), None))
+                .await
+                .unwrap()[0]
+                .contract_address,
+            "b"
+        );
+        assert!(repo
+            .get_deployments(&query(Some("rococo"), Some("b")))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[rocket::async_test]
+    async fn only_the_first_matching_deployment_is_updated() {
+        let repo = repo();
+        repo.create_deployment(&deployment("alice", "rococo", "a"))
+            .await
+            .unwrap();
+        repo.create_deployment(&deployment("alice", "rococo", "a"))
+            .await
+            .unwrap();
+
+        let mut update = UpdateDeployMessage {
+            contract_address: String::from("a"),
+            network: String::from("rococo"),
+            user_address: String::from("alice"),
+            contract_name: Some(String::from("name")),
+            hidden: true,
+        };
+        assert!(repo.update_deployment(&update).await.unwrap());
+
+        let stored = repo.get_deployments(&query(None, None)).await.unwrap();
+        assert_eq!(stored[0].contract_name, Some(String::from("name")));
+        assert!(stored[0].hidden);
+        assert!(!stored[1].hidden);
+
+        update.user_address = String::from("bob");
+        assert!(!repo.update_deployment(&update).await.unwrap());
+    }
+
+    #[rocket::async_test]
+    async fn jobs_are_found_by_id() {
+        let repo = repo();
+        let job = Job::new(
+            "job",
+            "code",
+            "alice",
+            JobStatus::Failed,
+            &BuildLog::default(),
+        );
+        repo.create_job(&job).await.unwrap();
+
+        assert_eq!(repo.get_job("job").await.unwrap(), Some(job));
+        assert_eq!(repo.get_job("other").await.unwrap(), None);
+    }
+
+    #[rocket::async_test]
+    async fn api_keys_are_revoked_and_count_usage() {
+        let repo = repo();
+        let api_key = ApiKey {
+            addresses: vec![String::from(
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            )],
+            ..ApiKey::new(
+                "id",
+                "hash",
+                "ci",
+                vec![ApiKeyScope::Compile, ApiKeyScope::WriteDeployments],
+                60,
+            )
+        };
+        repo.create_api_key(&api_key).await.unwrap();
+
+        repo.record_api_key_usage("id", "2023-01-01T00:00:00Z")
+            .await
+            .unwrap();
+        assert!(repo.revoke_api_key("id").await.unwrap());
+        assert!(!repo.revoke_api_key("other").await.unwrap());
+
+        let stored = repo.get_api_key_by_hash("hash").await.unwrap().unwrap();
+        assert_eq!(stored.scopes, api_key.scopes);
+        assert_eq!(stored.addresses, api_key.addresses);
+        assert!(stored.revoked);
+        assert_eq!(stored.usage_count, 1);
+        assert_eq!(
+            stored.last_used_at,
+            Some(String::from("2023-01-01T00:00:00Z"))
+        );
+        assert_eq!(repo.get_api_keys().await.unwrap().len(), 1);
+    }
+
+    #[rocket::async_test]
+    async fn compile_quotas_are_counted_per_day() {
+        let repo = repo();
+
+        assert_eq!(
+            repo.increment_compile_quota("alice", "2023-01-01")
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            repo.increment_compile_quota("alice", "2023-01-01")
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            repo.increment_compile_quota("bob", "2023-01-01")
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            repo.increment_compile_quota("alice", "2023-01-02")
+                .await
+                .unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,13 @@
+use crate::server;
+use crate::utils::config::AppConfig;
+use rocket::{Build, Rocket};
+
+// Server used by the route tests, the data is kept in memory so no database is needed
+pub fn rocket() -> Rocket<Build> {
+    dotenv::dotenv().ok();
+    server(
+        AppConfig::figment(rocket::Config::figment())
+            .merge(("storage", "memory"))
+            .merge(("auth.session_token_secret", "test secret")),
+    )
+}
@@ -0,0 +1,106 @@
+use crate::models::api_models::{AuthChallenge, ServerResponse, SessionToken};
+use crate::utils::address_signature::{
+    body_hash, canonical_payload, unix_timestamp, SIGNATURE_ADDRESS_HEADER,
+    SIGNATURE_BODY_HASH_HEADER, SIGNATURE_HEADER, SIGNATURE_NONCE_HEADER,
+    SIGNATURE_TIMESTAMP_HEADER,
+};
+use crate::utils::sanity_check::{SS58_CHECKSUM_LEN, SS58_CHECKSUM_PREFIX};
+use blake2::{Blake2b512, Digest};
+use rocket::http::Header;
+use rocket::local::blocking::{Client, LocalRequest};
+use schnorrkel::{ExpansionMode, Keypair, MiniSecretKey};
+use uuid::Uuid;
+
+// sr25519 wallet used by the tests to sign write requests
+pub struct TestSigner {
+    pub keypair: Keypair,
+    pub address: String,
+}
+
+impl TestSigner {
+    // Creates a deterministic wallet from the seed, with an address for the generic substrate prefix
+    pub fn new(seed: u8) -> Self {
+        let keypair = MiniSecretKey::from_bytes(&[seed; 32])
+            .expect("valid mini secret key")
+            .expand_to_keypair(ExpansionMode::Ed25519);
+        let address = encode_ss58_address(42, &keypair.public.to_bytes());
+
+        TestSigner { keypair, address }
+    }
+
+    // Signs the message and returns the hex encoded signature
+    pub fn sign(&self, message: &[u8]) -> String {
+        let signature = self.keypair.sign_simple(b"substrate", message);
+        format!("0x{}", hex::encode(signature.to_bytes()))
+    }
+
+    // Sets the body of the request and adds the signature headers covering it
+    pub fn sign_request<'c, B: AsRef<[u8]>>(
+        &self,
+        request: LocalRequest<'c>,
+        body: B,
+    ) -> LocalRequest<'c> {
+        let timestamp = unix_timestamp();
+        let nonce = Uuid::new_v4().to_string();
+        let body_hash = body_hash(body.as_ref());
+        let payload = canonical_payload(
+            request.inner().method().as_str(),
+            request.inner().uri().path().as_str(),
+            &self.address,
+            timestamp,
+            &nonce,
+            &body_hash,
+        );
+        let signature = self.sign(payload.as_bytes());
+
+        request
+            .body(body)
+            .header(Header::new(SIGNATURE_ADDRESS_HEADER, self.address.clone()))
+            .header(Header::new(SIGNATURE_HEADER, signature))
+            .header(Header::new(
+                SIGNATURE_TIMESTAMP_HEADER,
+                timestamp.to_string(),
+            ))
+            .header(Header::new(SIGNATURE_NONCE_HEADER, nonce))
+            .header(Header::new(SIGNATURE_BODY_HASH_HEADER, body_hash))
+    }
+
+    // Signs in through the challenge flow and returns the session token
+    pub fn sign_in(&self, client: &Client) -> String {
+        let challenge: ServerResponse<AuthChallenge> = client
+            .get(format!("/auth/challenge?address={}", self.address))
+            .dispatch()
+            .into_json()
+            .expect("valid challenge response");
+        let challenge = challenge.data.expect("challenge created");
+
+        let body = format!(
+            r#"{{ "address": "{}", "nonce": "{}", "signature": "{}" }}"#,
+            self.address,
+            challenge.nonce,
+            self.sign(challenge.message.as_bytes())
+        );
+        let session: ServerResponse<SessionToken> = client
+            .post("/auth/login")
+            .body(body)
+            .dispatch()
+            .into_json()
+            .expect("valid login response");
+
+        session.data.expect("session started").token
+    }
+}
+
+// Encodes an account id as an SS58 address with a one byte network prefix
+pub fn encode_ss58_address(prefix: u8, account_id: &[u8; 32]) -> String {
+    let mut data = vec![prefix];
+    data.extend_from_slice(account_id);
+
+    let hash = Blake2b512::new()
+        .chain_update(SS58_CHECKSUM_PREFIX)
+        .chain_update(&data)
+        .finalize();
+    data.extend_from_slice(&hash[..SS58_CHECKSUM_LEN]);
+
+    bs58::encode(data).into_string()
+}
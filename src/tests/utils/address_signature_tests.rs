@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod address_signature_tests {
+    use super::super::*;
+    use crate::test_signer::{encode_ss58_address, TestSigner};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    const NOW: u64 = 1_683_000_000;
+
+    #[test]
+    fn canonical_payload_has_one_field_per_line() {
+        let payload = canonical_payload("post", "/deployments", "5Grw", 1683000000, "abc", "E3B0");
+        assert_eq!(payload, "POST\n/deployments\n5Grw\n1683000000\nabc\ne3b0");
+    }
+
+    #[test]
+    fn body_hash_is_the_hex_sha256_of_the_body() {
+        assert_eq!(
+            body_hash(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_ne!(body_hash(b"{ }"), body_hash(b"{}"));
+    }
+
+    #[test]
+    fn verifies_sr25519_signature() {
+        let signer = TestSigner::new(1);
+        let signature = signer.sign(b"message");
+
+        let result = verify_signature(
+            &signer.address,
+            b"message",
+            &signature,
+            SignatureType::Sr25519,
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn verifies_sr25519_signature_of_wrapped_message() {
+        let signer = TestSigner::new(1);
+        let signature = signer.sign(b"<Bytes>message</Bytes>");
+
+        let result = verify_signature(
+            &signer.address,
+            b"message",
+            &signature,
+            SignatureType::Sr25519,
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn verifies_ed25519_signature() {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let address = encode_ss58_address(42, &signing_key.verifying_key().to_bytes());
+        let signature = hex::encode(signing_key.sign(b"message").to_bytes());
+
+        let result = verify_signature(&address, b"message", &signature, SignatureType::Ed25519);
+        assert_eq!(result, Ok(()));
+
+        let result = verify_signature(&address, b"message", &signature, SignatureType::Sr25519);
+        assert_eq!(result, Err(SignatureError::InvalidSignature));
+    }
+
+    #[test]
+    fn rejects_signature_of_another_address() {
+        let signer = TestSigner::new(1);
+        let other = TestSigner::new(2);
+        let signature = signer.sign(b"message");
+
+        let result = verify_signature(
+            &other.address,
+            b"message",
+            &signature,
+            SignatureType::Sr25519,
+        );
+        assert_eq!(result, Err(SignatureError::InvalidSignature));
+    }
+
+    #[test]
+    fn rejects_signature_of_another_message() {
+        let signer = TestSigner::new(1);
+        let signature = signer.sign(b"message");
+
+        let result = verify_signature(
+            &signer.address,
+            b"another message",
+            &signature,
+            SignatureType::Sr25519,
+        );
+        assert_eq!(result, Err(SignatureError::InvalidSignature));
+    }
+
+    #[test]
+    fn rejects_unsupported_signature_type() {
+        assert_eq!(
+            SignatureType::parse("ecdsa"),
+            Err(SignatureError::UnsupportedSignatureType(String::from(
+                "ecdsa"
+            )))
+        );
+        assert_eq!(SignatureType::parse("Sr25519"), Ok(SignatureType::Sr25519));
+    }
+
+    #[test]
+    fn rejects_replayed_nonce() {
+        let nonces = NonceStore::new();
+
+        assert_eq!(nonces.use_nonce("5Grw", "abc", NOW), Ok(()));
+        assert_eq!(
+            nonces.use_nonce("5Grw", "abc", NOW + 1),
+            Err(SignatureError::Replayed)
+        );
+        // The same nonce can be used by another address
+        assert_eq!(nonces.use_nonce("5FHn", "abc", NOW + 1), Ok(()));
+    }
+
+    #[test]
+    fn forgets_nonces_after_the_signature_expired() {
+        let nonces = NonceStore::new();
+
+        assert_eq!(nonces.use_nonce("5Grw", "abc", NOW), Ok(()));
+        let later = NOW + 2 * SIGNATURE_MAX_AGE_SECS + 1;
+        assert_eq!(nonces.use_nonce("5Grw", "def", later), Ok(()));
+        assert_eq!(nonces.used_nonces.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod api_keys_tests {
+    use super::super::*;
+
+    const NOW: u64 = 1_683_000_000;
+
+    fn api_key(scopes: Vec<ApiKeyScope>, rate_limit_per_minute: u32) -> ApiKey {
+        ApiKey::new("0123456789ab", "hash", "ci", scopes, rate_limit_per_minute)
+    }
+
+    #[test]
+    fn generated_keys_are_unique_and_contain_their_id() {
+        let (key_id, secret_key) = generate_api_key();
+        let (other_key_id, other_secret_key) = generate_api_key();
+
+        assert_eq!(key_id.len(), 12);
+        assert!(secret_key.starts_with(&format!("ck_{}_", key_id)));
+        assert_ne!(key_id, other_key_id);
+        assert_ne!(secret_key, other_secret_key);
+    }
+
+    #[test]
+    fn hash_is_stable_and_hides_the_key() {
+        let (_, secret_key) = generate_api_key();
+
+        assert_eq!(hash_api_key(&secret_key), hash_api_key(&secret_key));
+        assert_eq!(hash_api_key(&secret_key).len(), 64);
+        assert!(!hash_api_key(&secret_key).contains(&secret_key));
+    }
+
+    #[test]
+    fn rate_limiter_rejects_requests_over_the_limit() {
+        let rate_limiter = ApiKeyRateLimiter::new();
+        let api_key = api_key(vec![ApiKeyScope::Compile], 2);
+
+        assert_eq!(rate_limiter.check(&api_key, NOW), Ok(()));
+        assert_eq!(rate_limiter.check(&api_key, NOW), Ok(()));
+        assert_eq!(
+            rate_limiter.check(&api_key, NOW),
+            Err(ApiKeyError::RateLimited)
+        );
+    }
+
+    #[test]
+    fn rate_limiter_resets_every_minute() {
+        let rate_limiter = ApiKeyRateLimiter::new();
+        let api_key = api_key(vec![ApiKeyScope::Compile], 1);
+        let minute_start = NOW - NOW % 60;
+
+        assert_eq!(rate_limiter.check(&api_key, minute_start), Ok(()));
+        assert_eq!(
+            rate_limiter.check(&api_key, minute_start + 59),
+            Err(ApiKeyError::RateLimited)
+        );
+        assert_eq!(rate_limiter.check(&api_key, minute_start + 60), Ok(()));
+    }
+
+    #[test]
+    fn service_caller_needs_the_scope() {
+        let caller = Caller::Service(api_key(vec![ApiKeyScope::Read], 10));
+
+        assert!(caller.require::<String>(ApiKeyScope::Read).is_ok());
+        let err = caller.require::<String>(ApiKeyScope::Compile).unwrap_err();
+        assert_eq!(err.0, Status::Forbidden);
+        assert_eq!(
+            err.1 .0,
+            ServerResponse::new_error(String::from("API key is missing the compile scope."))
+        );
+    }
+
+    #[test]
+    fn service_caller_writes_deployments_of_its_addresses() {
+        let address = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let other_address = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+        // The same account encoded with the polkadot prefix
+        let caller = Caller::Service(ApiKey {
+            addresses: vec![String::from(
+                "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5",
+            )],
+            ..api_key(vec![ApiKeyScope::WriteDeployments], 10)
+        });
+        assert!(caller.check_owns::<String>(address).is_ok());
+        let err = caller.check_owns::<String>(other_address).unwrap_err();
+        assert_eq!(err.0, Status::Forbidden);
+
+        // Keys created without addresses can't write any deployment
+        let caller = Caller::Service(api_key(vec![ApiKeyScope::WriteDeployments], 10));
+        assert!(caller.check_owns::<String>(address).is_err());
+
+        let caller = Caller::Service(ApiKey {
+            addresses: vec![address.to_string()],
+            ..api_key(vec![ApiKeyScope::Compile], 10)
+        });
+        assert!(caller.check_owns::<String>(address).is_err());
+    }
+
+    #[test]
+    fn anonymous_requests_do_not_need_scopes() {
+        assert!(OptionalApiKey(None)
+            .require::<String>(ApiKeyScope::Read)
+            .is_ok());
+        assert!(
+            OptionalApiKey(Some(api_key(vec![ApiKeyScope::Compile], 10)))
+                .require::<String>(ApiKeyScope::Read)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod artifacts_tests {
+    use super::super::*;
+
+    fn contract(metadata: Value) -> Contract {
+        Contract {
+            id: None,
+            code_id: String::from("code_id"),
+            metadata,
+            wasm: vec![0, 97, 115, 109],
+            legacy_metadata: None,
+            validation: None,
+            size_report: None,
+        }
+    }
+
+    #[test]
+    fn etag_is_a_quoted_hash() {
+        let etag = etag(b"wasm");
+
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(etag.len(), 66);
+        assert_ne!(etag, super::super::etag(b"other"));
+    }
+
+    #[test]
+    fn if_none_match_uses_weak_comparison() {
+        let etag = etag(b"wasm");
+
+        assert!(IfNoneMatch(vec![etag.clone()]).matches(&etag));
+        assert!(IfNoneMatch(vec![format!("W/{}", etag)]).matches(&etag));
+        assert!(IfNoneMatch(vec![String::from("*")]).matches(&etag));
+        assert!(!IfNoneMatch(vec![String::from("\"other\"")]).matches(&etag));
+        assert!(!IfNoneMatch::default().matches(&etag));
+    }
+
+    #[test]
+    fn bundle_adds_the_wasm_to_the_source() {
+        let bundle =
+            contract_bundle(&contract(serde_json::json!({"source": {"hash": "0x01"}}))).unwrap();
+        let bundle: Value = serde_json::from_slice(&bundle).unwrap();
+
+        assert_eq!(bundle["source"]["wasm"], "0x0061736d");
+        assert_eq!(bundle["source"]["hash"], "0x01");
+    }
+
+    #[test]
+    fn bundle_requires_a_source_section() {
+        assert!(contract_bundle(&contract(Value::from("not an object"))).is_err());
+        assert!(contract_bundle(&contract(serde_json::json!({"contract": {}}))).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 #[cfg(test)]
-mod compiler_tests{
+mod compiler_tests {
     use super::super::*;
 
     extern crate dotenv;
@@ -16,20 +16,39 @@ mod compiler_tests{
         let queue = CompilationQueue::new();
         let compilation_queue = Arc::new(queue);
         let shutdown_flag = Arc::new(AtomicBool::new(false));
-        let compiler = Compiler::init(compilation_queue, shutdown_flag.clone());
+        let networks = NetworkRegistry::new(vec![crate::utils::network_registry::Network {
+            id: String::from("rococo"),
+            name: String::from("Rococo"),
+            aliases: vec![],
+            ss58_prefix: 42,
+            max_code_size: 131072,
+            toolchain_profiles: vec![],
+            explorer: Default::default(),
+        }])
+        .unwrap();
+        let compiler = Compiler::init(
+            &CompilerConfig::default(),
+            networks,
+            compilation_queue,
+            shutdown_flag.clone(),
+        );
 
-        let wizard_message = WizardMessage{
+        let wizard_message = WizardMessage {
+            network: None,
+            build: BuildOptions::default(),
             address: "ABC".to_string(),
             code: LIB_RS_CODE.to_string(),
             features: vec!["psp22".to_string(), "ownable".to_string()],
         };
 
         // Create lib.rs file
-        compiler.create_contract_files(&wizard_message)
+        compiler
+            .create_contract_files(&wizard_message)
             .expect("Could not create lib.rs file");
 
         // Compile contract
-        let result = compiler.compile_contract();
+        let mut build_log = BuildLog::default();
+        let result = compiler.compile_contract(&wizard_message.build, &mut build_log);
 
         // Check if the contract was compiled successfully
         assert!(result.is_ok());
@@ -42,6 +61,24 @@ mod compiler_tests{
         remove_file(&file_path).expect("Error deleting file");
     }
 
+    #[test]
+    fn build_log_is_kept_when_small() {
+        let build_log = BuildLog::from_output(b"Compiling contract\n", b"warning: unused\n");
+
+        assert_eq!(build_log.output, "Compiling contract\nwarning: unused\n");
+        assert!(!build_log.truncated);
+    }
+
+    #[test]
+    fn build_log_keeps_the_tail_when_too_long() {
+        let stdout = vec![b'a'; MAX_BUILD_LOG_SIZE];
+        let build_log = BuildLog::from_output(&stdout, b"error: build failed");
+
+        assert_eq!(build_log.output.len(), MAX_BUILD_LOG_SIZE);
+        assert!(build_log.output.ends_with("error: build failed"));
+        assert!(build_log.truncated);
+    }
+
     const LIB_RS_CODE: &str = "#![cfg_attr(not(feature = \"std\"), no_std)]
     #![feature(min_specialization)]
     
@@ -75,4 +112,4 @@ mod compiler_tests{
             }
         }
     }";
-}
\ No newline at end of file
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod config_tests {
+    use super::super::*;
+    use rocket::figment::providers::{Format, Toml};
+
+    const NETWORKS: &str = r#"
+        [[networks]]
+        id = "rococo"
+        name = "Rococo Contracts"
+        ss58_prefix = 42
+        max_code_size = 131072
+        toolchain_profiles = ["ink-v4"]
+    "#;
+
+    fn figment(toml: &str) -> Figment {
+        Figment::new()
+            .merge(Toml::string(NETWORKS))
+            .merge(Toml::string(toml))
+    }
+
+    #[test]
+    fn defaults_are_used_when_only_the_uri_is_set() {
+        let config = AppConfig::from_figment(&figment(
+            r#"
+            [mongo]
+            uri = "mongodb://localhost:27017"
+            "#,
+        ))
+        .unwrap();
+
+        assert_eq!(config.storage, StorageBackend::Mongo);
+        assert_eq!(config.mongo.uri, "mongodb://localhost:27017");
+        assert_eq!(config.mongo.database, "ContractWizard");
+        assert_eq!(config.mongo.collections, CollectionNames::default());
+        assert_eq!(
+            config.mongo.artifacts.compression,
+            ArtifactCompression::Zstd
+        );
+        assert_eq!(
+            config.mongo.artifacts.gridfs_threshold,
+            DEFAULT_GRIDFS_THRESHOLD
+        );
+        assert_eq!(config.compiler.cargo, "cargo");
+        assert_eq!(config.compiler.max_code_size, DEFAULT_MAX_CODE_SIZE);
+        assert_eq!(
+            config.compiler.allowed_features.len(),
+            DEFAULT_ALLOWED_FEATURES.len()
+        );
+        assert_eq!(config.auth, AuthConfig::default());
+        assert_eq!(config.cors, CorsConfig::default());
+        assert_eq!(config.rate_limit, RateLimitConfig::default());
+        assert_eq!(config.networks.len(), 1);
+    }
+
+    #[test]
+    fn relative_workspace_is_resolved() {
+        let config = AppConfig::from_figment(&figment(
+            r#"
+            [mongo]
+            uri = "mongodb://localhost:27017"
+            "#,
+        ))
+        .unwrap();
+
+        assert!(config.compiler.workspace.is_absolute());
+        assert!(config.compiler.workspace.ends_with("compilation_target"));
+    }
+
+    #[test]
+    fn values_are_read_from_the_figment() {
+        let config = AppConfig::from_figment(&figment(
+            r#"
+            [mongo]
+            uri = "mongodb://localhost:27017"
+            database = "Wizard"
+
+            [mongo.collections]
+            jobs = "BuildJobs"
+
+            [compiler]
+            cargo = "/usr/local/cargo/bin/cargo"
+            max_code_size = 100
+            allowed_features = ["psp22"]
+
+            [auth]
+            admin_token = "secret"
+            "#,
+        ))
+        .unwrap();
+
+        assert_eq!(config.mongo.database, "Wizard");
+        assert_eq!(config.mongo.collections.jobs, "BuildJobs");
+        assert_eq!(config.mongo.collections.contracts, "Contracts");
+        assert_eq!(config.compiler.cargo, "/usr/local/cargo/bin/cargo");
+        assert_eq!(config.compiler.max_code_size, 100);
+        assert_eq!(config.compiler.allowed_features, vec!["psp22"]);
+        assert_eq!(config.auth.admin_token, Some(String::from("secret")));
+    }
+
+    #[test]
+    fn missing_uri_is_an_error() {
+        let err = AppConfig::from_figment(&figment("")).unwrap_err();
+        assert!(err.contains("MONGOURI"));
+    }
+
+    #[test]
+    fn memory_storage_does_not_need_a_uri() {
+        let config = AppConfig::from_figment(&figment("storage = \"memory\"")).unwrap();
+
+        assert_eq!(config.storage, StorageBackend::Memory);
+        assert!(config.mongo.uri.is_empty());
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    #[test]
+    fn sqlite_storage_requires_the_feature() {
+        let err = AppConfig::from_figment(&figment("storage = \"sqlite\"")).unwrap_err();
+        assert!(err.contains("sqlite feature"));
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        let invalid = [
+            "[mongo]\nuri = \"\"",
+            "[mongo]\nuri = \"mongodb://localhost\"\n[compiler]\nmax_code_size = 0",
+            "[mongo]\nuri = \"mongodb://localhost\"\n[compiler]\nallowed_features = []",
+            "[mongo]\nuri = \"mongodb://localhost\"\n[compiler]\nworkspace = \"missing_dir\"",
+            "storage = \"redis\"",
+            "[mongo]\nuri = \"mongodb://localhost\"\n[mongo.artifacts]\ncompression = \"gzip\"",
+            "[mongo]\nuri = \"mongodb://localhost\"\n[mongo.artifacts]\ngridfs_threshold = 16777216",
+            "storage = \"memory\"\n[cors]\nallowed_origins = [\"*\"]\nallowed_methods = [\"GET\"]\nallowed_headers = [\"*\"]\nallow_credentials = true",
+            "storage = \"memory\"\n[rate_limit]\ncapacity = 0\nrefill_per_minute = 30\ndaily_compile_quota = 50\napi_key_daily_compile_quota = 1000",
+        ];
+
+        for toml in invalid {
+            assert!(AppConfig::from_figment(&figment(toml)).is_err(), "{}", toml);
+        }
+    }
+
+    #[test]
+    fn networks_are_required() {
+        let figment = Figment::new().merge(Toml::string("storage = \"memory\""));
+        let err = AppConfig::from_figment(&figment).unwrap_err();
+        assert!(err.starts_with("networks:"), "{}", err);
+    }
+
+    #[test]
+    fn legacy_values_take_precedence() {
+        let figment = figment("[mongo]\nuri = \"mongodb://file\"")
+            .merge(Serialized::global("mongo.uri", "mongodb://env"));
+        let config = AppConfig::from_figment(&figment).unwrap();
+
+        assert_eq!(config.mongo.uri, "mongodb://env");
+    }
+}
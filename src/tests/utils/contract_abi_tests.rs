@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod contract_abi_tests {
+    use super::super::*;
+
+    const METADATA: &str = include_str!("../fixtures/psp22_metadata.json");
+
+    fn metadata() -> Value {
+        serde_json::from_str(METADATA).unwrap()
+    }
+
+    #[test]
+    fn contract_information_is_read() {
+        let abi = ContractAbi::from_metadata(&metadata()).unwrap();
+
+        assert_eq!(abi.name, "my_psp22");
+        assert_eq!(abi.version, "0.1.0");
+        assert_eq!(abi.language, "ink! 4.2.1");
+        assert_eq!(abi.metadata_version, 4);
+    }
+
+    #[test]
+    fn constructors_are_resolved() {
+        let abi = ContractAbi::from_metadata(&metadata()).unwrap();
+        let constructor = &abi.constructors[0];
+
+        assert_eq!(constructor.label, "new");
+        assert_eq!(constructor.selector, "0x9bae9d5e");
+        assert!(!constructor.payable);
+        assert_eq!(constructor.args[0].label, "initial_supply");
+        assert_eq!(constructor.args[0].arg_type.name, "u128");
+        assert_eq!(constructor.args[0].arg_type.display_name, "Balance");
+        assert_eq!(constructor.return_type.as_ref().unwrap().name, "()");
+        assert_eq!(constructor.docs, "Mints the initial supply to the caller");
+    }
+
+    #[test]
+    fn messages_are_resolved() {
+        let abi = ContractAbi::from_metadata(&metadata()).unwrap();
+        let transfer = abi
+            .messages
+            .iter()
+            .find(|message| message.label == "PSP22::transfer")
+            .unwrap();
+
+        assert_eq!(transfer.selector, "0xdb20f9f5");
+        assert!(transfer.mutates);
+        assert!(!transfer.payable);
+        let arg_types: Vec<&str> = transfer
+            .args
+            .iter()
+            .map(|arg| arg.arg_type.name.as_str())
+            .collect();
+        assert_eq!(arg_types, vec!["AccountId", "u128", "Vec<u8>"]);
+        assert_eq!(
+            transfer.return_type.as_ref().unwrap().name,
+            "Result<(), PSP22Error>"
+        );
+
+        let owner = &abi.messages[3];
+        assert!(!owner.mutates);
+        assert_eq!(
+            owner.return_type.as_ref().unwrap().name,
+            "Option<AccountId>"
+        );
+
+        let deposit = &abi.messages[4];
+        assert!(deposit.payable);
+    }
+
+    #[test]
+    fn events_are_resolved() {
+        let abi = ContractAbi::from_metadata(&metadata()).unwrap();
+        let transfer = &abi.events[0];
+
+        assert_eq!(transfer.label, "Transfer");
+        assert_eq!(transfer.module, None);
+        assert!(transfer.args[0].indexed);
+        assert_eq!(transfer.args[0].arg_type.name, "Option<AccountId>");
+        assert!(!transfer.args[2].indexed);
+    }
+
+    #[test]
+    fn ink_5_events_have_a_signature_topic() {
+        let mut metadata = metadata();
+        metadata["version"] = Value::from(5);
+        metadata["spec"]["events"][0]["module"] = Value::from("my_psp22::my_psp22");
+        metadata["spec"]["events"][0]["signature_topic"] =
+            Value::from("0xb5b61a3e6a21a16be4f044b517c28ac692492f73c5bfd3f60178ad98c767f4cb");
+
+        let abi = ContractAbi::from_metadata(&metadata).unwrap();
+
+        assert_eq!(abi.metadata_version, 5);
+        assert_eq!(abi.events[0].module.as_deref(), Some("my_psp22::my_psp22"));
+        assert!(abi.events[0].signature_topic.is_some());
+    }
+
+    #[test]
+    fn unsupported_metadata_is_an_error() {
+        let mut metadata = metadata();
+        metadata["version"] = Value::from("3");
+
+        assert!(ContractAbi::from_metadata(&metadata).is_err());
+    }
+}
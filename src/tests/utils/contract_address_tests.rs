@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod contract_address_tests {
+    use super::super::*;
+
+    const ALICE: &str = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+
+    fn bytes32(hex_string: &str) -> [u8; 32] {
+        hex::decode(hex_string).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn code_hash_is_blake2_256() {
+        assert_eq!(
+            hex::encode(code_hash(b"")),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+    }
+
+    #[test]
+    fn address_depends_on_every_input() {
+        let deployer = bytes32(ALICE);
+        let code_hash = code_hash(b"\0asm\x01\0\0\0");
+        let mut input_data = hex::decode("9bae9d5e").unwrap();
+        input_data.extend_from_slice(&1000u128.to_le_bytes());
+
+        let address = derive_contract_address(&deployer, &code_hash, &input_data, &[1, 2, 3, 4]);
+        assert_eq!(
+            hex::encode(address),
+            "d7c4b540a71c1e08a0fa896ef4e874b66ba7e834da773cb53a0235a40b2459ea"
+        );
+
+        assert_ne!(
+            derive_contract_address(&deployer, &code_hash, &input_data, &[1, 2, 3]),
+            address
+        );
+        assert_ne!(
+            derive_contract_address(&deployer, &code_hash, &input_data[..4], &[1, 2, 3, 4]),
+            address
+        );
+        assert_ne!(
+            derive_contract_address(&[0; 32], &code_hash, &input_data, &[1, 2, 3, 4]),
+            address
+        );
+    }
+
+    #[test]
+    fn empty_input_and_salt_are_length_prefixed() {
+        assert_eq!(
+            hex::encode(derive_contract_address(&bytes32(ALICE), &[1; 32], &[], &[])),
+            "5c60f074a09fd2acbcab96f8880d73993edcea3d7d4ad158043cd7f1118a254a"
+        );
+    }
+}
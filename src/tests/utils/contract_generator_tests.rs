@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod contract_generator_tests {
+    use super::super::*;
+
+    fn spec(standard: Standard, extensions: &[&str]) -> ContractSpec {
+        ContractSpec {
+            standard,
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            name: String::from("My Token"),
+            symbol: None,
+            decimals: None,
+            initial_supply: None,
+            owner: OwnerModel::Caller,
+        }
+    }
+
+    #[test]
+    fn psp22_is_rendered_with_its_parameters() {
+        let spec = ContractSpec {
+            symbol: Some(String::from("MTK")),
+            decimals: Some(12),
+            initial_supply: Some(String::from("1000000")),
+            ..spec(Standard::Psp22, &["ownable"])
+        };
+        let code = generate_contract(&spec).unwrap();
+
+        assert!(code.contains("pub mod my_token {"));
+        assert!(code.contains("use openbrush::contracts::ownable::*;"));
+        assert!(code.contains("        ownable: ownable::Data,\n"));
+        assert!(code.contains("    impl Ownable for Contract {}\n"));
+        assert!(code.contains("pub fn new() -> Self {"));
+        assert!(code.contains("._mint_to(_instance.env().caller(), 1000000)"));
+        assert!(code.contains("_instance.metadata.name = Some(String::from(\"My Token\"));"));
+        assert!(code.contains("_instance.metadata.symbol = Some(String::from(\"MTK\"));"));
+        assert!(code.contains("_instance.metadata.decimals = 12;"));
+        assert!(code.contains("_instance._init_with_owner(_instance.env().caller());"));
+        assert!(!code.contains("{{"));
+        assert!(!code.contains("pausable"));
+        assert_eq!(spec.features(), vec!["psp22", "ownable"]);
+    }
+
+    #[test]
+    fn pausable_is_switched_by_the_owner() {
+        let code =
+            generate_contract(&spec(Standard::Psp34, &["pausable", "access-control"])).unwrap();
+
+        assert!(code.contains("impl Pausable for Contract {}"));
+        assert!(code.contains("        _id: &Id,\n"));
+        assert!(code.contains(") -> Result<(), PSP34Error> {"));
+        assert!(code.contains("#[openbrush::modifiers(only_role(DEFAULT_ADMIN_ROLE))]"));
+        assert!(code.contains("_instance._init_with_admin(_instance.env().caller());"));
+        assert!(code.contains(
+            "_instance._set_attribute(collection_id.clone(), String::from(\"name\"), String::from(\"My Token\"));"
+        ));
+        assert!(!code.contains("{{"));
+
+        let code = generate_contract(&spec(Standard::Psp37, &["pausable", "ownable"])).unwrap();
+        assert!(code.contains("#[openbrush::modifiers(only_owner)]"));
+        assert!(code.contains("_ids: &[(Id, Balance)],"));
+    }
+
+    #[test]
+    fn owner_can_be_a_constructor_argument() {
+        let spec = ContractSpec {
+            owner: OwnerModel::Argument,
+            ..spec(Standard::Psp22, &["ownable"])
+        };
+        let code = generate_contract(&spec).unwrap();
+
+        assert!(code.contains("pub fn new(owner: AccountId) -> Self {"));
+        assert!(code.contains("._mint_to(owner, 0)"));
+        assert!(code.contains("_instance._init_with_owner(owner);"));
+    }
+
+    #[test]
+    fn keywords_are_not_used_as_module_names() {
+        assert_eq!(module_name("My Token"), "my_token");
+        assert_eq!(module_name("Self"), "self_contract");
+        assert_eq!(module_name("type"), "type_contract");
+        assert_eq!(module_name("Crate-"), "crate_contract");
+        assert_eq!(module_name("fn token"), "fn_token");
+
+        let code = generate_contract(&ContractSpec {
+            name: String::from("Super"),
+            ..spec(Standard::Psp34, &[])
+        })
+        .unwrap();
+        assert!(code.contains("pub mod super_contract {"));
+    }
+
+    #[test]
+    fn unsupported_specs_are_rejected() {
+        let invalid = [
+            ContractSpec {
+                name: String::from("\"); evil"),
+                ..spec(Standard::Psp22, &[])
+            },
+            ContractSpec {
+                symbol: Some(String::from("M T")),
+                ..spec(Standard::Psp22, &[])
+            },
+            ContractSpec {
+                decimals: Some(6),
+                ..spec(Standard::Psp34, &[])
+            },
+            ContractSpec {
+                initial_supply: Some(String::from("-1")),
+                ..spec(Standard::Psp22, &[])
+            },
+            ContractSpec {
+                owner: OwnerModel::Argument,
+                ..spec(Standard::Psp22, &[])
+            },
+            spec(Standard::Psp22, &["pausable"]),
+            spec(Standard::Psp22, &["psp34"]),
+        ];
+
+        for spec in invalid {
+            assert!(generate_contract(&spec).is_err(), "{:?}", spec);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod cors_tests {
+    use super::super::*;
+
+    fn config(allowed_origins: &[&str], allowed_headers: &[&str], credentials: bool) -> CorsConfig {
+        CorsConfig {
+            allowed_origins: allowed_origins.iter().map(|o| o.to_string()).collect(),
+            allowed_headers: allowed_headers.iter().map(|h| h.to_string()).collect(),
+            allow_credentials: credentials,
+            ..CorsConfig::default()
+        }
+    }
+
+    #[test]
+    fn wildcard_allows_any_origin() {
+        let config = config(&["*"], &["*"], false);
+        assert!(config.allows_origin("https://example.com"));
+    }
+
+    #[test]
+    fn only_listed_origins_are_allowed() {
+        let config = config(&["https://wizard.example.com/"], &["*"], true);
+
+        assert!(config.allows_origin("https://wizard.example.com"));
+        assert!(config.allows_origin("HTTPS://WIZARD.EXAMPLE.COM"));
+        assert!(!config.allows_origin("https://evil.example.com"));
+        assert!(!config.allows_origin("http://wizard.example.com"));
+    }
+
+    #[test]
+    fn wildcard_headers_echo_the_requested_ones() {
+        let config = config(&["*"], &["*"], true);
+
+        assert_eq!(
+            config.allowed_headers_for(Some("content-type, authorization")),
+            Some(String::from("content-type, authorization"))
+        );
+        assert_eq!(config.allowed_headers_for(None), None);
+    }
+
+    #[test]
+    fn wildcard_headers_without_credentials() {
+        let config = config(&["*"], &["*"], false);
+        assert_eq!(config.allowed_headers_for(None), Some(String::from("*")));
+    }
+
+    #[test]
+    fn listed_headers_are_joined() {
+        let config = config(&["*"], &["Content-Type", "X-Api-Key"], false);
+        assert_eq!(
+            config.allowed_headers_for(Some("x-other")),
+            Some(String::from("Content-Type, X-Api-Key"))
+        );
+    }
+
+    #[test]
+    fn credentials_require_listed_origins() {
+        assert!(config(&["*"], &["*"], true).check().is_err());
+        assert!(config(&["https://wizard.example.com", "*"], &["*"], true)
+            .check()
+            .is_err());
+        assert!(config(&["https://wizard.example.com"], &["*"], true)
+            .check()
+            .is_ok());
+        assert!(config(&["*"], &["*"], false).check().is_ok());
+    }
+}
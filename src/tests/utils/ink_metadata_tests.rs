@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod ink_metadata_tests {
+    use super::super::*;
+
+    const METADATA: &str = include_str!("../fixtures/psp22_metadata.json");
+
+    fn metadata() -> Value {
+        serde_json::from_str(METADATA).unwrap()
+    }
+
+    #[test]
+    fn cargo_contract_metadata_is_valid() {
+        let metadata = parse_metadata(METADATA).unwrap();
+
+        assert_eq!(metadata_version(&metadata), Some(4));
+        assert_eq!(metadata["contract"]["name"], "my_psp22");
+    }
+
+    #[test]
+    fn ink_5_version_is_a_number() {
+        let mut metadata = metadata();
+        metadata["version"] = Value::from(5);
+
+        assert_eq!(metadata_version(&metadata), Some(5));
+        assert!(validate_metadata(&metadata).is_ok());
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() {
+        let mut metadata = metadata();
+        metadata["version"] = Value::from("3");
+        assert!(validate_metadata(&metadata)
+            .unwrap_err()
+            .contains("unsupported version 3"));
+
+        metadata.as_object_mut().unwrap().remove("version");
+        assert!(validate_metadata(&metadata)
+            .unwrap_err()
+            .contains("missing version"));
+    }
+
+    #[test]
+    fn invalid_json_is_rejected() {
+        assert!(parse_metadata("not json").is_err());
+        assert!(parse_metadata("[]").is_err());
+    }
+
+    #[test]
+    fn missing_sections_are_reported() {
+        let mut metadata = metadata();
+        metadata["source"].as_object_mut().unwrap().remove("hash");
+        assert_eq!(
+            validate_metadata(&metadata).unwrap_err(),
+            "Invalid ink metadata: missing source.hash"
+        );
+
+        let mut metadata = self::metadata();
+        metadata["spec"]["messages"][0]["args"] = Value::from("none");
+        assert_eq!(
+            validate_metadata(&metadata).unwrap_err(),
+            "Invalid ink metadata: args is not an array in spec.messages[0]"
+        );
+
+        let mut metadata = self::metadata();
+        metadata["types"] = Value::from(true);
+        assert_eq!(
+            validate_metadata(&metadata).unwrap_err(),
+            "Invalid ink metadata: types is not an array"
+        );
+    }
+
+    #[test]
+    fn type_names_are_resolved_from_the_registry() {
+        let registry = InkMetadata::from_value(&metadata()).unwrap().registry();
+
+        assert_eq!(registry.type_name(0), "u128");
+        assert_eq!(registry.type_name(1), "AccountId");
+        assert_eq!(registry.type_name(2), "[u8; 32]");
+        assert_eq!(registry.type_name(6), "()");
+        assert_eq!(
+            registry.type_name(8),
+            "Result<Result<(), PSP22Error>, LangError>"
+        );
+        assert_eq!(registry.type_name(11), "String");
+        assert_eq!(registry.type_name(12), "Vec<u8>");
+        assert_eq!(registry.type_name(13), "Option<AccountId>");
+        assert_eq!(registry.type_name(99), "Unknown99");
+    }
+}
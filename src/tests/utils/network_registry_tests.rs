@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod network_registry_tests {
+    use super::super::*;
+
+    fn rococo() -> Network {
+        Network {
+            id: String::from("rococo"),
+            name: String::from("Rococo Contracts"),
+            aliases: vec![String::from("rococo-testnet")],
+            ss58_prefix: 42,
+            max_code_size: 131072,
+            toolchain_profiles: vec![String::from("ink-v4")],
+            explorer: ExplorerUrls::default(),
+        }
+    }
+
+    fn shibuya() -> Network {
+        Network {
+            id: String::from("shibuya"),
+            name: String::from("Shibuya"),
+            aliases: vec![],
+            ss58_prefix: 5,
+            max_code_size: 131072,
+            toolchain_profiles: vec![String::from("ink-v4")],
+            explorer: ExplorerUrls::default(),
+        }
+    }
+
+    #[test]
+    fn resolves_id_name_and_aliases_ignoring_case() {
+        let registry = NetworkRegistry::new(vec![rococo(), shibuya()]).unwrap();
+
+        for name in ["rococo", "Rococo", "Rococo Contracts", "rococo-testnet"] {
+            assert_eq!(registry.resolve(name).unwrap().id, "rococo");
+        }
+        assert_eq!(registry.resolve("SHIBUYA").unwrap().id, "shibuya");
+        assert!(registry.resolve("kusama").is_none());
+    }
+
+    #[test]
+    fn rejects_names_used_by_two_networks() {
+        let mut shibuya = shibuya();
+        shibuya.aliases.push(String::from("Rococo"));
+
+        assert!(NetworkRegistry::new(vec![rococo(), shibuya]).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_registry() {
+        assert!(NetworkRegistry::new(vec![]).is_err());
+    }
+
+    #[test]
+    fn configured_networks_are_valid() {
+        let figment = rocket::Config::figment().merge(("storage", "memory"));
+        let config = AppConfig::from_figment(&figment).expect("networks configured in Rocket.toml");
+        let registry = NetworkRegistry::new(config.networks).unwrap();
+
+        assert_eq!(registry.resolve("Rococo").unwrap().ss58_prefix, 42);
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod rate_limiter_tests {
+    use super::super::*;
+
+    const NOW: f64 = 1_683_000_000.0;
+
+    #[test]
+    fn bucket_allows_a_burst_up_to_its_capacity() {
+        let rate_limiter = RateLimiter::new(RateLimitConfig::default());
+
+        assert_eq!(
+            rate_limiter.acquire("ip:127.0.0.1", 2, 60, NOW),
+            Ok(LimitState {
+                limit: 2,
+                remaining: 1,
+                reset: 1
+            })
+        );
+        assert!(rate_limiter.acquire("ip:127.0.0.1", 2, 60, NOW).is_ok());
+        assert_eq!(
+            rate_limiter.acquire("ip:127.0.0.1", 2, 60, NOW),
+            Err(LimitState {
+                limit: 2,
+                remaining: 0,
+                reset: 2
+            })
+        );
+    }
+
+    #[test]
+    fn bucket_is_refilled_over_time() {
+        let rate_limiter = RateLimiter::new(RateLimitConfig::default());
+
+        assert!(rate_limiter.acquire("ip:127.0.0.1", 1, 60, NOW).is_ok());
+        assert!(rate_limiter
+            .acquire("ip:127.0.0.1", 1, 60, NOW + 0.5)
+            .is_err());
+        assert!(rate_limiter
+            .acquire("ip:127.0.0.1", 1, 60, NOW + 1.0)
+            .is_ok());
+    }
+
+    #[test]
+    fn buckets_are_independent() {
+        let rate_limiter = RateLimiter::new(RateLimitConfig::default());
+
+        assert!(rate_limiter.acquire("ip:127.0.0.1", 1, 60, NOW).is_ok());
+        assert!(rate_limiter.acquire("ip:127.0.0.1", 1, 60, NOW).is_err());
+        assert!(rate_limiter.acquire("ip:127.0.0.2", 1, 60, NOW).is_ok());
+    }
+
+    #[test]
+    fn full_buckets_are_dropped_by_the_periodic_sweep() {
+        let rate_limiter = RateLimiter::new(RateLimitConfig::default());
+
+        assert!(rate_limiter.acquire("ip:127.0.0.1", 1, 60, NOW).is_ok());
+        assert!(rate_limiter
+            .acquire("ip:127.0.0.2", 1, 60, NOW + 10.0)
+            .is_ok());
+        assert_eq!(rate_limiter.buckets.lock().unwrap().entries.len(), 2);
+
+        assert!(rate_limiter
+            .acquire("ip:127.0.0.3", 1, 60, NOW + 60.0)
+            .is_ok());
+        assert_eq!(rate_limiter.buckets.lock().unwrap().entries.len(), 1);
+    }
+
+    #[test]
+    fn api_keys_do_not_skip_the_ip_bucket() {
+        let rate_limiter = RateLimiter::new(RateLimitConfig::default());
+        let client = rocket::local::blocking::Client::untracked(rocket::build())
+            .expect("valid rocket instance");
+        let request = client
+            .get("/contract")
+            .remote("127.0.0.1:8000".parse().unwrap())
+            .header(Header::new("X-Api-Key", "ck_unverified"));
+
+        let buckets = rate_limiter.request_buckets(request.inner());
+        assert_eq!(buckets, vec![(String::from("ip:127.0.0.1"), 30, 30)]);
+    }
+
+    #[test]
+    fn signature_addresses_do_not_take_a_bucket() {
+        let rate_limiter = RateLimiter::new(RateLimitConfig::default());
+        let client = rocket::local::blocking::Client::untracked(rocket::build())
+            .expect("valid rocket instance");
+        let request = client
+            .post("/deployments")
+            .remote("127.0.0.1:8000".parse().unwrap())
+            .header(Header::new(
+                "X-Signature-Address",
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            ));
+
+        let buckets = rate_limiter.request_buckets(request.inner());
+        assert_eq!(buckets, vec![(String::from("ip:127.0.0.1"), 30, 30)]);
+    }
+
+    #[test]
+    fn daily_quota_resets_at_midnight() {
+        let midnight = 1_683_072_000;
+
+        assert_eq!(
+            LimitState::daily_quota(10, 3, midnight - 60),
+            LimitState {
+                limit: 10,
+                remaining: 7,
+                reset: 60
+            }
+        );
+        assert_eq!(LimitState::daily_quota(10, 12, midnight).remaining, 0);
+    }
+
+    #[test]
+    fn utc_day_is_the_date_of_the_timestamp() {
+        assert_eq!(utc_day(1_683_072_000), "2023-05-03");
+        assert_eq!(utc_day(1_683_071_999), "2023-05-02");
+    }
+
+    #[test]
+    fn exceeded_quota_is_reported() {
+        let report = QuotaReport::default();
+        let state = LimitState::daily_quota(10, 11, 1_683_000_000);
+
+        let err = report.exceeded::<String>(state);
+        assert_eq!(err.0, Status::TooManyRequests);
+        assert_eq!(*report.0.lock().unwrap(), Some(state));
+    }
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod request_id_tests {
+    use super::super::*;
+    use log::Level;
+    use std::sync::Mutex;
+
+    // Logger recording the request id found in the MDC of each record
+    #[derive(Default)]
+    struct MdcRecorder(Mutex<Vec<Option<String>>>);
+
+    impl Log for MdcRecorder {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, _record: &Record) {
+            let request_id = log_mdc::get(REQUEST_ID_LOG_KEY, |id| id.map(String::from));
+            self.0.lock().unwrap().push(request_id);
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn log(logger: &RequestIdLogger<MdcRecorder>) {
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .args(format_args!("message"))
+                .build(),
+        );
+    }
+
+    #[rocket::async_test]
+    async fn records_are_tagged_with_the_request_id_of_their_task() {
+        let logger = RequestIdLogger(MdcRecorder::default());
+
+        log(&logger);
+        CURRENT_REQUEST_ID
+            .scope(String::from("first"), async {
+                log(&logger);
+                rocket::tokio::task::yield_now().await;
+                log(&logger);
+            })
+            .await;
+        CURRENT_REQUEST_ID
+            .scope(String::from("second"), async { log(&logger) })
+            .await;
+        log(&logger);
+
+        assert_eq!(
+            *logger.0 .0.lock().unwrap(),
+            vec![
+                None,
+                Some(String::from("first")),
+                Some(String::from("first")),
+                Some(String::from("second")),
+                None,
+            ]
+        );
+        // The MDC of the thread is left empty between records
+        assert_eq!(
+            log_mdc::get(REQUEST_ID_LOG_KEY, |id| id.map(String::from)),
+            None
+        );
+    }
+}
@@ -1,17 +1,28 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod sanity_check_tests {
 
+    use crate::models::api_models::BuildOptions;
     use crate::models::db_models::Contract;
+    use crate::utils::config::DEFAULT_MAX_CODE_SIZE;
 
     use super::super::*;
 
     const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const ALICE_POLKADOT: &str = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5";
+    const ALICE_KUSAMA: &str = "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F";
+    const ALICE_MOONBEAM_PREFIX: &str = "VdvKmYJfD4VXA9fzz1SbmCo2eYHSzUFbaDCZSuaNKJAe8YNg6";
+    const ALICE_ACCOUNT_ID: &str =
+        "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
 
     #[test]
     fn test_sanity_check_on_code_size_greater_than_maximum_allowed_size() {
         // Test case when the code size is greater than the maximum allowed size
         let wizard_message = WizardMessage {
-            code: String::from_utf8(vec![b'1'; MAX_SIZE_ALLOWED + 1]).unwrap(),
+            network: None,
+            build: BuildOptions::default(),
+            code: String::from_utf8(vec![b'1'; DEFAULT_MAX_CODE_SIZE + 1]).unwrap(),
             address: String::from(BOB),
             features: vec![String::from("psp22"), String::from("pausable")],
         };
@@ -23,9 +34,9 @@ mod sanity_check_tests {
             ))),
         ));
 
-        let result = sanity_check_wizard_message(&Json(wizard_message));
+        let result = sanity_check_wizard_message(&Json(wizard_message), &CompilerConfig::default());
         assert_eq!(result, expected_error);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
         let error = &result.err().unwrap();
         assert_eq!(error.0, Status::InternalServerError);
     }
@@ -33,7 +44,9 @@ mod sanity_check_tests {
     #[test]
     fn test_sanity_check_on_wrong_address() {
         let wizard_message = WizardMessage {
-            code: String::from_utf8(vec![b'1'; MAX_SIZE_ALLOWED - 1]).unwrap(),
+            network: None,
+            build: BuildOptions::default(),
+            code: String::from_utf8(vec![b'1'; DEFAULT_MAX_CODE_SIZE - 1]).unwrap(),
             address: String::from("0x00e329d1fb7166f9cdf6a9e6cb62b6e5dfdd67ea"),
             features: vec![String::from("psp22"), String::from("pausable")],
         };
@@ -41,13 +54,13 @@ mod sanity_check_tests {
         let expected_error = Err(Custom(
             Status::InternalServerError,
             Json(ServerResponse::<Contract>::new_error(String::from(
-                "Address is not valid.",
+                "Address is not valid: invalid base58 encoding.",
             ))),
         ));
 
-        let result = sanity_check_wizard_message(&Json(wizard_message));
+        let result = sanity_check_wizard_message(&Json(wizard_message), &CompilerConfig::default());
         assert_eq!(result, expected_error);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
         let error = &result.err().unwrap();
         assert_eq!(error.0, Status::InternalServerError);
     }
@@ -55,7 +68,9 @@ mod sanity_check_tests {
     #[test]
     fn test_sanity_check_on_empty_features() {
         let wizard_message = WizardMessage {
-            code: String::from_utf8(vec![b'1'; MAX_SIZE_ALLOWED - 1]).unwrap(),
+            network: None,
+            build: BuildOptions::default(),
+            code: String::from_utf8(vec![b'1'; DEFAULT_MAX_CODE_SIZE - 1]).unwrap(),
             address: String::from(BOB),
             features: vec![],
         };
@@ -67,9 +82,9 @@ mod sanity_check_tests {
             ))),
         ));
 
-        let result = sanity_check_wizard_message(&Json(wizard_message));
+        let result = sanity_check_wizard_message(&Json(wizard_message), &CompilerConfig::default());
         assert_eq!(result, expected_error);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
         let error = &result.err().unwrap();
         assert_eq!(error.0, Status::InternalServerError);
     }
@@ -77,7 +92,9 @@ mod sanity_check_tests {
     #[test]
     fn test_sanity_check_on_not_valid_features() {
         let wizard_message = WizardMessage {
-            code: String::from_utf8(vec![b'1'; MAX_SIZE_ALLOWED - 1]).unwrap(),
+            network: None,
+            build: BuildOptions::default(),
+            code: String::from_utf8(vec![b'1'; DEFAULT_MAX_CODE_SIZE - 1]).unwrap(),
             address: String::from(BOB),
             features: vec![
                 String::from("psp22"),
@@ -92,9 +109,9 @@ mod sanity_check_tests {
                 "Feature not allowed",
             ))),
         ));
-        let result = sanity_check_wizard_message(&Json(wizard_message));
+        let result = sanity_check_wizard_message(&Json(wizard_message), &CompilerConfig::default());
         assert_eq!(result, expected_error);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
         let error = &result.err().unwrap();
         assert_eq!(error.0, Status::InternalServerError);
     }
@@ -102,7 +119,9 @@ mod sanity_check_tests {
     #[test]
     fn test_sanity_check_on_ambiguous_standard() {
         let wizard_message = WizardMessage {
-            code: String::from_utf8(vec![b'1'; MAX_SIZE_ALLOWED - 1]).unwrap(),
+            network: None,
+            build: BuildOptions::default(),
+            code: String::from_utf8(vec![b'1'; DEFAULT_MAX_CODE_SIZE - 1]).unwrap(),
             address: String::from(BOB),
             features: vec![
                 String::from("psp22"),
@@ -117,9 +136,9 @@ mod sanity_check_tests {
                 "Feature contains ambiguous contract standard",
             ))),
         ));
-        let result = sanity_check_wizard_message(&Json(wizard_message));
+        let result = sanity_check_wizard_message(&Json(wizard_message), &CompilerConfig::default());
         assert_eq!(result, expected_error);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
         let error = &result.err().unwrap();
         assert_eq!(error.0, Status::InternalServerError);
     }
@@ -127,7 +146,9 @@ mod sanity_check_tests {
     #[test]
     fn test_sanity_check_on_no_standard() {
         let wizard_message = WizardMessage {
-            code: String::from_utf8(vec![b'1'; MAX_SIZE_ALLOWED - 1]).unwrap(),
+            network: None,
+            build: BuildOptions::default(),
+            code: String::from_utf8(vec![b'1'; DEFAULT_MAX_CODE_SIZE - 1]).unwrap(),
             address: String::from(BOB),
             features: vec![String::from("pausable")],
         };
@@ -138,9 +159,9 @@ mod sanity_check_tests {
                 "Features must contain at least one contract standard",
             ))),
         ));
-        let result = sanity_check_wizard_message(&Json(wizard_message));
+        let result = sanity_check_wizard_message(&Json(wizard_message), &CompilerConfig::default());
         assert_eq!(result, expected_error);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
         let error = &result.err().unwrap();
         assert_eq!(error.0, Status::InternalServerError);
     }
@@ -149,15 +170,265 @@ mod sanity_check_tests {
     fn test_sanity_check_on_success() {
         // Test case when the code size is greater than the maximum allowed size
         let wizard_message = WizardMessage {
-            code: String::from_utf8(vec![b'1'; MAX_SIZE_ALLOWED - 1]).unwrap(),
+            network: None,
+            build: BuildOptions::default(),
+            code: String::from_utf8(vec![b'1'; DEFAULT_MAX_CODE_SIZE - 1]).unwrap(),
             address: String::from(BOB),
             features: vec![String::from("psp22"), String::from("pausable")],
         };
 
         let expected_result = Ok(());
 
-        let result = sanity_check_wizard_message(&Json(wizard_message));
+        let result = sanity_check_wizard_message(&Json(wizard_message), &CompilerConfig::default());
         assert_eq!(result, expected_result);
-        assert_eq!(result.is_err(), false);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_decode_ss58_address_with_one_byte_prefix() {
+        let (prefix, account_id) = decode_ss58_address(ALICE).unwrap();
+        assert_eq!(prefix, 42);
+        assert_eq!(hex_string(&account_id), ALICE_ACCOUNT_ID);
+
+        let (prefix, account_id) = decode_ss58_address(ALICE_POLKADOT).unwrap();
+        assert_eq!(prefix, 0);
+        assert_eq!(hex_string(&account_id), ALICE_ACCOUNT_ID);
+    }
+
+    #[test]
+    fn test_decode_ss58_address_with_two_byte_prefix() {
+        let (prefix, account_id) = decode_ss58_address(ALICE_MOONBEAM_PREFIX).unwrap();
+        assert_eq!(prefix, 1284);
+        assert_eq!(hex_string(&account_id), ALICE_ACCOUNT_ID);
+    }
+
+    #[test]
+    fn test_encode_ss58_address_round_trips() {
+        let account_id: [u8; 32] = hex::decode(ALICE_ACCOUNT_ID).unwrap().try_into().unwrap();
+
+        assert_eq!(encode_ss58_address(42, &account_id), ALICE);
+        assert_eq!(encode_ss58_address(0, &account_id), ALICE_POLKADOT);
+        assert_eq!(encode_ss58_address(2, &account_id), ALICE_KUSAMA);
+        assert_eq!(
+            encode_ss58_address(1284, &account_id),
+            ALICE_MOONBEAM_PREFIX
+        );
+    }
+
+    #[test]
+    fn test_check_address_accepts_addresses_shorter_than_48_characters() {
+        assert_eq!(ALICE_KUSAMA.len(), 47);
+        let kusama = test_network("kusama", 2);
+        assert_eq!(check_address(ALICE_KUSAMA, Some(&kusama)), Ok(2));
+    }
+
+    #[test]
+    fn test_check_address_on_invalid_base58() {
+        let result = check_address("0x00e329d1fb7166f9cdf6a9e6cb62b6e5dfdd67ea", None);
+        assert_eq!(result, Err(Ss58Error::InvalidBase58));
+    }
+
+    #[test]
+    fn test_check_address_on_invalid_checksum() {
+        // Same length as a valid address but last character changed
+        let address = ALICE.replace("utQY", "utQZ");
+        assert_eq!(address.len(), 48);
+        assert_eq!(
+            check_address(&address, None),
+            Err(Ss58Error::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn test_check_address_on_invalid_length() {
+        let result = check_address("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNeh", None);
+        assert_eq!(result, Err(Ss58Error::InvalidLength));
+    }
+
+    #[test]
+    fn test_check_address_on_prefix_not_matching_network() {
+        let rococo = test_network("rococo", 42);
+        let result = check_address(ALICE_POLKADOT, Some(&rococo));
+        assert_eq!(
+            result,
+            Err(Ss58Error::PrefixMismatch {
+                network: String::from("rococo"),
+                expected: 42,
+                found: 0,
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "SS58 prefix 0 does not match network rococo (expected 42)"
+        );
+    }
+
+    #[test]
+    fn test_check_address_without_network_accepts_any_prefix() {
+        assert_eq!(check_address(ALICE_POLKADOT, None), Ok(0));
+    }
+
+    #[test]
+    fn test_sanity_check_deployment_addresses_reports_the_invalid_field() {
+        let rococo = test_network("rococo", 42);
+        let result = sanity_check_deployment_addresses(ALICE, "some_address", &rococo);
+
+        let expected_error = Err(Custom(
+            Status::InternalServerError,
+            Json(ServerResponse::<String>::new_error(String::from(
+                "Invalid contract_address: invalid base58 encoding.",
+            ))),
+        ));
+        assert_eq!(result, expected_error);
+    }
+
+    #[test]
+    fn test_check_network_on_unknown_network() {
+        let registry = NetworkRegistry::new(vec![test_network("rococo", 42)]).unwrap();
+
+        assert_eq!(
+            check_network::<String>(&registry, "Rococo").unwrap().id,
+            "rococo"
+        );
+
+        let expected_error = Err(Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::<String>::new_error(String::from(
+                "Network not supported: some_network.",
+            ))),
+        ));
+        assert_eq!(check_network(&registry, "some_network"), expected_error);
+    }
+
+    #[test]
+    fn test_check_toolchain_of_the_network() {
+        let config = CompilerConfig::default();
+        let mut network = test_network("rococo", 42);
+        assert!(check_toolchain::<String>(&network, &config).is_ok());
+
+        network.toolchain_profiles = vec![String::from("ink-v4"), String::from("ink-v5")];
+        assert!(check_toolchain::<String>(&network, &config).is_ok());
+
+        network.toolchain_profiles = vec![String::from("ink-v5")];
+        let expected_error = Err(Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::<String>::new_error(String::from(
+                "rococo does not support the ink-v4 toolchain of the compiler, it supports ink-v5.",
+            ))),
+        ));
+        assert_eq!(check_toolchain(&network, &config), expected_error);
+    }
+
+    #[test]
+    fn test_check_code_size_on_code_bigger_than_network_limit() {
+        let rococo = test_network("rococo", 42);
+
+        assert!(check_code_size::<String>(rococo.max_code_size, &rococo).is_ok());
+
+        let expected_error = Err(Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::<String>::new_error(String::from(
+                "Code size of 131073 bytes exceeds the rococo limit of 131072 bytes.",
+            ))),
+        ));
+        assert_eq!(
+            check_code_size(rococo.max_code_size + 1, &rococo),
+            expected_error
+        );
+    }
+
+    #[test]
+    fn test_check_contract_address_against_the_derived_address() {
+        let rococo = test_network("rococo", 42);
+        let contract = Contract {
+            id: None,
+            code_id: String::from("code_id"),
+            metadata: serde_json::Value::Null,
+            wasm: b"\0asm\x01\0\0\0".to_vec(),
+            legacy_metadata: None,
+            validation: None,
+            size_report: None,
+        };
+        let derived: [u8; 32] =
+            hex::decode("d7c4b540a71c1e08a0fa896ef4e874b66ba7e834da773cb53a0235a40b2459ea")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let mut deploy_message = deploy_message(&encode_ss58_address(42, &derived));
+
+        // Deployments without instantiation data are not verified
+        assert!(check_contract_address(&deploy_message, None, &rococo).is_ok());
+
+        deploy_message.salt = Some(String::from("0x01020304"));
+        deploy_message.constructor_data =
+            Some(String::from("0x9bae9d5ee8030000000000000000000000000000"));
+        assert!(check_contract_address(&deploy_message, Some(&contract), &rococo).is_ok());
+
+        let expected_error = Err(Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::<String>::new_error(String::from(
+                "The contract address can not be verified, contract code_id was not compiled by this service.",
+            ))),
+        ));
+        assert_eq!(
+            check_contract_address(&deploy_message, None, &rococo),
+            expected_error
+        );
+
+        deploy_message.salt = Some(String::from("0x01"));
+        let expected_error = Err(Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::<String>::new_error(format!(
+                "contract_address does not match the address {} derived from the code_id, user_address, salt and constructor_data.",
+                encode_ss58_address(
+                    42,
+                    &derive_contract_address(
+                        &hex::decode(ALICE_ACCOUNT_ID).unwrap().try_into().unwrap(),
+                        &code_hash(&contract.wasm),
+                        &hex::decode("9bae9d5ee8030000000000000000000000000000").unwrap(),
+                        &[1]
+                    )
+                )
+            ))),
+        ));
+        assert_eq!(
+            check_contract_address(&deploy_message, Some(&contract), &rococo),
+            expected_error
+        );
+
+        deploy_message.salt = Some(String::from("salt"));
+        assert!(check_contract_address(&deploy_message, Some(&contract), &rococo).is_err());
+    }
+
+    fn deploy_message(contract_address: &str) -> DeployMessage {
+        DeployMessage {
+            contract_name: None,
+            contract_address: contract_address.to_string(),
+            network: String::from("rococo"),
+            code_id: String::from("code_id"),
+            user_address: String::from(ALICE),
+            tx_hash: None,
+            date: String::from("2021-03-03T15:00:00.000Z"),
+            contract_type: String::from("psp22"),
+            external_abi: None,
+            salt: None,
+            constructor_data: None,
+        }
+    }
+
+    fn test_network(id: &str, ss58_prefix: u16) -> Network {
+        Network {
+            id: id.to_string(),
+            name: id.to_string(),
+            aliases: vec![],
+            ss58_prefix,
+            max_code_size: 131072,
+            toolchain_profiles: vec![],
+            explorer: Default::default(),
+        }
+    }
+
+    fn hex_string(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
     }
 }
@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod scale_codec_tests {
+    use super::super::*;
+
+    const METADATA: &str = include_str!("../fixtures/psp22_metadata.json");
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const ALICE_HEX: &str = "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+    const TRANSFER: &str = "0xdb20f9f5d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27de803000000000000000000000000000008dead";
+
+    fn metadata() -> Value {
+        serde_json::from_str(METADATA).unwrap()
+    }
+
+    fn registry() -> TypeRegistry {
+        InkMetadata::from_value(&metadata()).unwrap().registry()
+    }
+
+    fn round_trip(id: u32, value: Value) -> Value {
+        let registry = registry();
+        let mut encoded = Vec::new();
+        encode_value(&registry, id, &value, &mut encoded).unwrap();
+        decode_value(&registry, id, &mut encoded.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn compact_integers_use_the_smallest_mode() {
+        for (value, expected) in [
+            (0u128, "00"),
+            (63, "fc"),
+            (64, "0101"),
+            (16383, "fdff"),
+            (16384, "02000100"),
+            (1 << 30, "0300000040"),
+            (u64::MAX.into(), "13ffffffffffffffff"),
+        ] {
+            let mut encoded = Vec::new();
+            encode_compact(value, &mut encoded);
+            assert_eq!(hex::encode(&encoded), expected);
+            assert_eq!(decode_compact(&mut encoded.as_slice()).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn message_arguments_are_encoded_after_the_selector() {
+        let args = serde_json::json!({"to": ALICE, "value": 1000, "data": "0xdead"});
+        let encoded =
+            encode_call(&metadata(), CallKind::Message, "PSP22::transfer", &args).unwrap();
+
+        assert_eq!(encoded.selector, "0xdb20f9f5");
+        assert_eq!(encoded.data, TRANSFER);
+
+        let positional = serde_json::json!([ALICE_HEX, "1000", [222, 173]]);
+        let encoded = encode_call(
+            &metadata(),
+            CallKind::Message,
+            "PSP22::transfer",
+            &positional,
+        )
+        .unwrap();
+        assert_eq!(encoded.data, TRANSFER);
+    }
+
+    #[test]
+    fn constructors_and_calls_without_arguments_are_encoded() {
+        let encoded = encode_call(
+            &metadata(),
+            CallKind::Constructor,
+            "new",
+            &serde_json::json!({"initial_supply": "340282366920938463463374607431768211455"}),
+        )
+        .unwrap();
+        assert_eq!(encoded.data, format!("0x9bae9d5e{}", "ff".repeat(16)));
+
+        let encoded = encode_call(
+            &metadata(),
+            CallKind::Message,
+            "PSP22::total_supply",
+            &Value::Null,
+        )
+        .unwrap();
+        assert_eq!(encoded.data, "0x162df8c2");
+    }
+
+    #[test]
+    fn invalid_calls_are_rejected() {
+        let metadata = metadata();
+        assert_eq!(
+            encode_call(&metadata, CallKind::Message, "missing", &Value::Null).unwrap_err(),
+            "The contract has no message missing"
+        );
+        assert_eq!(
+            encode_call(
+                &metadata,
+                CallKind::Constructor,
+                "new",
+                &serde_json::json!({})
+            )
+            .unwrap_err(),
+            "Missing argument initial_supply"
+        );
+        assert!(encode_call(
+            &metadata,
+            CallKind::Message,
+            "PSP22::balance_of",
+            &serde_json::json!({"owner": "not an address"})
+        )
+        .unwrap_err()
+        .starts_with("Invalid argument owner"));
+        assert!(encode_call(
+            &metadata,
+            CallKind::Constructor,
+            "new",
+            &serde_json::json!([-1])
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn call_data_is_decoded_by_selector() {
+        let decoded = decode_data(&metadata(), DecodeKind::Call, None, TRANSFER).unwrap();
+
+        assert_eq!(decoded.label, "PSP22::transfer");
+        assert_eq!(decoded.selector.as_deref(), Some("0xdb20f9f5"));
+        assert_eq!(
+            decoded.value,
+            serde_json::json!({"to": ALICE_HEX, "value": 1000, "data": "0xdead"})
+        );
+
+        assert!(decode_data(&metadata(), DecodeKind::Call, None, "0x00000000").is_err());
+        assert_eq!(
+            decode_data(
+                &metadata(),
+                DecodeKind::Call,
+                None,
+                &format!("{}00", TRANSFER)
+            )
+            .unwrap_err(),
+            "1 bytes left after decoding"
+        );
+    }
+
+    #[test]
+    fn return_values_are_decoded_with_the_lang_error() {
+        let decoded = decode_data(
+            &metadata(),
+            DecodeKind::Return,
+            Some("PSP22::transfer"),
+            "0x000101",
+        )
+        .unwrap();
+        assert_eq!(
+            decoded.value,
+            serde_json::json!({"Ok": {"Err": "InsufficientBalance"}})
+        );
+
+        let decoded = decode_data(
+            &metadata(),
+            DecodeKind::Return,
+            Some("Ownable::owner"),
+            "0x0000",
+        )
+        .unwrap();
+        assert_eq!(decoded.value, serde_json::json!({"Ok": null}));
+
+        assert!(decode_data(&metadata(), DecodeKind::Return, None, "0x00").is_err());
+    }
+
+    #[test]
+    fn ink_4_events_are_decoded_by_index() {
+        let data = format!(
+            "0x0000 01{} e8030000000000000000000000000000",
+            &ALICE_HEX[2..]
+        )
+        .replace(' ', "");
+        let decoded = decode_data(&metadata(), DecodeKind::Event, None, &data).unwrap();
+
+        assert_eq!(decoded.label, "Transfer");
+        assert_eq!(
+            decoded.value,
+            serde_json::json!({"from": null, "to": ALICE_HEX, "value": 1000})
+        );
+        assert_eq!(
+            decode_data(&metadata(), DecodeKind::Event, Some("Transfer"), &data)
+                .unwrap()
+                .value,
+            decoded.value
+        );
+    }
+
+    #[test]
+    fn ink_5_events_need_a_label() {
+        let mut metadata = metadata();
+        metadata["version"] = Value::from(5);
+        metadata["spec"]["events"][0]["signature_topic"] = Value::from("0x01");
+        let data = format!("0x0000{}", "00".repeat(16));
+
+        assert!(decode_data(&metadata, DecodeKind::Event, None, &data).is_err());
+        let decoded = decode_data(&metadata, DecodeKind::Event, Some("0x01"), &data).unwrap();
+        assert_eq!(
+            decoded.value,
+            serde_json::json!({"from": null, "to": null, "value": 0})
+        );
+    }
+
+    #[test]
+    fn values_round_trip_through_the_registry() {
+        assert_eq!(round_trip(0, Value::from(u64::MAX)), Value::from(u64::MAX));
+        assert_eq!(
+            round_trip(0, Value::from(u128::MAX.to_string())),
+            Value::from(u128::MAX.to_string())
+        );
+        assert_eq!(round_trip(11, Value::from("psp22")), Value::from("psp22"));
+        assert_eq!(round_trip(14, Value::from(true)), Value::from(true));
+        assert_eq!(round_trip(13, Value::from(ALICE)), Value::from(ALICE_HEX));
+        assert_eq!(
+            round_trip(10, serde_json::json!({"Custom": "error"})),
+            serde_json::json!({"Custom": "error"})
+        );
+        assert_eq!(round_trip(6, Value::Null), Value::Null);
+    }
+}
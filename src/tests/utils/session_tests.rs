@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod session_tests {
+    use super::super::*;
+
+    const NOW: u64 = 1_683_000_000;
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+    const CLIENT: Option<IpAddr> = Some(IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 1)));
+
+    #[test]
+    fn issued_token_is_verified() {
+        let keys = SessionKeys::new(b"secret");
+        let (token, expires_at) = keys.issue(ALICE, NOW).unwrap();
+
+        assert_eq!(expires_at, NOW + SESSION_TOKEN_TTL_SECS);
+        assert_eq!(keys.verify(&token, NOW + 10), Ok(ALICE.to_string()));
+    }
+
+    #[test]
+    fn expired_token_is_rejected() {
+        let keys = SessionKeys::new(b"secret");
+        let (token, expires_at) = keys.issue(ALICE, NOW).unwrap();
+
+        assert_eq!(
+            keys.verify(&token, expires_at),
+            Err(SessionError::ExpiredToken)
+        );
+    }
+
+    #[test]
+    fn token_signed_with_another_secret_is_rejected() {
+        let (token, _) = SessionKeys::new(b"other secret").issue(ALICE, NOW).unwrap();
+
+        assert_eq!(
+            SessionKeys::new(b"secret").verify(&token, NOW),
+            Err(SessionError::InvalidToken)
+        );
+    }
+
+    #[test]
+    fn malformed_token_is_rejected() {
+        let keys = SessionKeys::new(b"secret");
+        assert_eq!(
+            keys.verify("not-a-token", NOW),
+            Err(SessionError::InvalidToken)
+        );
+    }
+
+    #[test]
+    fn challenge_message_contains_address_and_nonce() {
+        let challenges = ChallengeStore::new();
+        let challenge = challenges.create(ALICE, CLIENT, NOW).unwrap();
+
+        assert_eq!(challenge.expires_at, NOW + CHALLENGE_TTL_SECS);
+        assert_eq!(
+            challenge.message,
+            challenge_message(ALICE, &challenge.nonce)
+        );
+        assert!(challenge.message.contains(ALICE));
+        assert!(challenge.message.contains(&challenge.nonce));
+    }
+
+    #[test]
+    fn challenge_can_only_be_used_once() {
+        let challenges = ChallengeStore::new();
+        let challenge = challenges.create(ALICE, CLIENT, NOW).unwrap();
+
+        assert_eq!(challenges.take(ALICE, &challenge.nonce, NOW + 1), Ok(()));
+        assert_eq!(
+            challenges.take(ALICE, &challenge.nonce, NOW + 2),
+            Err(SessionError::InvalidChallenge)
+        );
+    }
+
+    #[test]
+    fn challenge_is_bound_to_the_address() {
+        let challenges = ChallengeStore::new();
+        let challenge = challenges.create(ALICE, CLIENT, NOW).unwrap();
+
+        assert_eq!(
+            challenges.take(BOB, &challenge.nonce, NOW + 1),
+            Err(SessionError::InvalidChallenge)
+        );
+    }
+
+    #[test]
+    fn expired_challenge_is_rejected() {
+        let challenges = ChallengeStore::new();
+        let challenge = challenges.create(ALICE, CLIENT, NOW).unwrap();
+
+        assert_eq!(
+            challenges.take(ALICE, &challenge.nonce, challenge.expires_at),
+            Err(SessionError::InvalidChallenge)
+        );
+    }
+
+    #[test]
+    fn expired_challenges_are_pruned() {
+        let challenges = ChallengeStore::new();
+        challenges.create(ALICE, CLIENT, NOW).unwrap();
+        challenges
+            .create(BOB, CLIENT, NOW + CHALLENGE_TTL_SECS + 1)
+            .unwrap();
+
+        assert_eq!(challenges.challenges.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn pending_challenges_are_capped_per_client() {
+        let challenges = ChallengeStore::new();
+        for _ in 0..MAX_CHALLENGES_PER_CLIENT {
+            challenges.create(ALICE, CLIENT, NOW).unwrap();
+        }
+
+        assert_eq!(
+            challenges.create(BOB, CLIENT, NOW).map(|_| ()),
+            Err(SessionError::TooManyChallenges)
+        );
+        let other_client = Some(IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 2)));
+        assert!(challenges.create(ALICE, other_client, NOW).is_ok());
+        assert!(challenges
+            .create(ALICE, CLIENT, NOW + CHALLENGE_TTL_SECS + 1)
+            .is_ok());
+    }
+
+    #[test]
+    fn full_store_drops_the_oldest_challenge() {
+        let challenges = ChallengeStore::new();
+        let oldest = challenges.create(ALICE, None, NOW).unwrap();
+        for index in 1..MAX_CHALLENGES {
+            let client = Some(IpAddr::V6(std::net::Ipv6Addr::from(index as u128)));
+            challenges.create(BOB, client, NOW + 1).unwrap();
+        }
+
+        let newest = challenges.create(ALICE, CLIENT, NOW + 2).unwrap();
+        assert_eq!(challenges.challenges.lock().unwrap().len(), MAX_CHALLENGES);
+        assert!(challenges.take(ALICE, &oldest.nonce, NOW + 3).is_err());
+        assert!(challenges.take(ALICE, &newest.nonce, NOW + 3).is_ok());
+    }
+
+    #[test]
+    fn session_secret_is_required() {
+        assert!(SessionKeys::from_config(&AuthConfig::default()).is_err());
+        assert!(SessionKeys::from_config(&AuthConfig {
+            session_token_secret: Some(String::from("secret")),
+            admin_token: None,
+        })
+        .is_ok());
+    }
+}
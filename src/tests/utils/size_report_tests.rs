@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod size_report_tests {
+    use super::super::*;
+
+    const CONTRACT: &str = r#"(module
+        (import "seal0" "seal_input" (func $input (param i32 i32)))
+        (import "env" "memory" (memory 1 16))
+        (func $small (export "deploy"))
+        (func $large (export "call")
+            (call $input (i32.const 0) (i32.const 4))
+            (call $input (i32.const 8) (i32.const 12)))
+        (data (i32.const 16) "ab")
+        (data (i32.const 1024) "abcdef"))"#;
+
+    #[test]
+    fn largest_functions_and_segments_come_first() {
+        let wasm = wat::parse_str(CONTRACT).unwrap();
+        let report = size_report(Some(&[0; 512]), &wasm).unwrap();
+
+        assert_eq!(report.original_size, Some(512));
+        assert_eq!(report.optimized_size, wasm.len());
+
+        // Function indexes count the imported function
+        assert_eq!(report.functions.len(), 2);
+        assert_eq!(report.functions[0].index, 2);
+        assert!(report.functions[0].size > report.functions[1].size);
+
+        assert_eq!(
+            report.data_segments,
+            vec![
+                DataSegmentSize {
+                    index: 1,
+                    offset: Some(1024),
+                    size: 6
+                },
+                DataSegmentSize {
+                    index: 0,
+                    offset: Some(16),
+                    size: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn names_come_from_the_name_section_or_the_exports() {
+        // wat keeps the identifiers in the name section
+        let wasm = wat::parse_str(CONTRACT).unwrap();
+        let report = size_report(None, &wasm).unwrap();
+        assert_eq!(report.functions[0].name.as_deref(), Some("large"));
+
+        let stripped =
+            wat::parse_str(CONTRACT.replace("$small ", "").replace("$large ", "")).unwrap();
+        let report = size_report(None, &stripped).unwrap();
+        assert_eq!(report.functions[0].name.as_deref(), Some("call"));
+        assert_eq!(report.original_size, None);
+    }
+
+    #[test]
+    fn invalid_wasm_is_an_error() {
+        assert!(size_report(None, b"not wasm").is_err());
+    }
+}
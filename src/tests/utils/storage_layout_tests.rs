@@ -0,0 +1,160 @@
+#[cfg(test)]
+mod storage_layout_tests {
+    use super::super::*;
+    use serde_json::json;
+
+    const METADATA: &str = include_str!("../fixtures/psp22_metadata.json");
+
+    fn metadata() -> Value {
+        serde_json::from_str(METADATA).unwrap()
+    }
+
+    // Metadata with the fields of the contract storage struct edited
+    fn with_fields(edit: impl FnOnce(&mut Vec<Value>)) -> Value {
+        let mut metadata = metadata();
+        let fields = metadata["storage"]["root"]["layout"]["struct"]["fields"]
+            .as_array_mut()
+            .unwrap();
+        edit(fields);
+        metadata
+    }
+
+    fn kinds(compatibility: &UpgradeCompatibility) -> Vec<(ChangeKind, &str)> {
+        compatibility
+            .changes
+            .iter()
+            .map(|change| (change.kind, change.path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn layout_is_extracted() {
+        let layout = storage_layout(&metadata()).unwrap();
+
+        assert_eq!(layout.cells.len(), 2);
+        let root = &layout.cells[0];
+        assert_eq!(root.path, "");
+        assert_eq!(root.root_key, "0x00000000");
+        let fields: Vec<(&str, &str)> = root
+            .fields
+            .iter()
+            .map(|field| (field.path.as_str(), field.type_name.as_str()))
+            .collect();
+        assert_eq!(fields, vec![("total_supply", "u128"), ("owner", "Option")]);
+
+        let balances = &layout.cells[1];
+        assert_eq!(balances.path, "balances");
+        assert_eq!(balances.root_key, "0x270a8fc3");
+        assert_eq!(balances.fields[0].type_name, "u128");
+    }
+
+    #[test]
+    fn same_layout_is_compatible() {
+        let compatibility = check_upgrade(&metadata(), &metadata()).unwrap();
+
+        assert_eq!(compatibility.verdict, Verdict::Compatible);
+        assert!(compatibility.changes.is_empty());
+    }
+
+    #[test]
+    fn renamed_fields_are_compatible() {
+        let renamed = with_fields(|fields| {
+            fields[0]["name"] = json!("supply");
+            fields[1]["name"] = json!("accounts");
+        });
+        let compatibility = check_upgrade(&metadata(), &renamed).unwrap();
+
+        assert_eq!(compatibility.verdict, Verdict::Compatible);
+        assert_eq!(
+            kinds(&compatibility),
+            vec![
+                (ChangeKind::Renamed, "supply"),
+                (ChangeKind::Renamed, "accounts"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reordered_and_changed_fields_are_incompatible() {
+        let reordered = with_fields(|fields| fields.swap(0, 2));
+        let compatibility = check_upgrade(&metadata(), &reordered).unwrap();
+        assert_eq!(compatibility.verdict, Verdict::Incompatible);
+        assert_eq!(
+            kinds(&compatibility),
+            vec![
+                (ChangeKind::Reordered, "owner"),
+                (ChangeKind::Reordered, "total_supply"),
+            ]
+        );
+
+        let changed = with_fields(|fields| fields[0]["layout"]["leaf"]["ty"] = json!(1));
+        let compatibility = check_upgrade(&metadata(), &changed).unwrap();
+        assert_eq!(compatibility.verdict, Verdict::Incompatible);
+        assert_eq!(
+            kinds(&compatibility),
+            vec![(ChangeKind::TypeChanged, "total_supply")]
+        );
+        assert!(compatibility.changes[0]
+            .message
+            .contains("from u128 to AccountId"));
+    }
+
+    #[test]
+    fn added_fields_depend_on_their_root_key() {
+        let added = with_fields(|fields| {
+            fields.push(
+                json!({ "name": "paused", "layout": { "leaf": { "key": "0x00000000", "ty": 0 } } }),
+            );
+            fields.push(json!({ "name": "allowances", "layout": { "root": {
+                "root_key": "0x12345678",
+                "layout": { "leaf": { "key": "0x12345678", "ty": 0 } }
+            } } }));
+        });
+        let compatibility = check_upgrade(&metadata(), &added).unwrap();
+
+        assert_eq!(compatibility.verdict, Verdict::Incompatible);
+        assert_eq!(
+            kinds(&compatibility),
+            vec![
+                (ChangeKind::Added, "paused"),
+                (ChangeKind::Added, "allowances")
+            ]
+        );
+        assert_eq!(compatibility.changes[0].impact, Impact::Breaking);
+        assert_eq!(compatibility.changes[1].impact, Impact::Safe);
+    }
+
+    #[test]
+    fn root_keys_are_checked() {
+        let moved = with_fields(|fields| {
+            fields[1]["layout"]["root"]["root_key"] = json!("0x12345678");
+        });
+        let compatibility = check_upgrade(&metadata(), &moved).unwrap();
+        assert_eq!(compatibility.verdict, Verdict::Incompatible);
+        assert_eq!(
+            kinds(&compatibility),
+            vec![(ChangeKind::RootKeyChanged, "balances")]
+        );
+
+        let removed = with_fields(|fields| {
+            fields.remove(1);
+        });
+        let compatibility = check_upgrade(&metadata(), &removed).unwrap();
+        assert_eq!(compatibility.verdict, Verdict::CompatibleWithWarnings);
+        assert_eq!(
+            kinds(&compatibility),
+            vec![(ChangeKind::Removed, "balances")]
+        );
+    }
+
+    #[test]
+    fn invalid_layout_is_an_error() {
+        let mut metadata = metadata();
+        metadata["storage"] = json!({ "leaf": { "key": "0x00000000", "ty": 0 } });
+
+        assert_eq!(
+            storage_layout(&metadata),
+            Err(String::from("Invalid storage layout: missing root key"))
+        );
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod wasm_text_tests {
+    use super::super::*;
+
+    const METADATA: &str = include_str!("../fixtures/psp22_metadata.json");
+    const CONTRACT: &str = r#"(module
+        (import "seal0" "seal_input" (func $input (param i32 i32)))
+        (import "env" "memory" (memory 1 16))
+        (func $deploy (export "deploy")
+            (call $input (i32.const 0) (i32.const 4)))
+        (func $call (export "call")
+            (call $input (i32.const 8) (i32.const 12)))
+        (data (i32.const 16) "(;)\"ab"))"#;
+
+    fn wasm() -> Vec<u8> {
+        wat::parse_str(CONTRACT).unwrap()
+    }
+
+    fn metadata() -> Value {
+        serde_json::from_str(METADATA).unwrap()
+    }
+
+    #[test]
+    fn whole_module_is_disassembled() {
+        let contract_wat = contract_wat(&wasm(), &metadata(), None).unwrap();
+
+        assert!(contract_wat.wat.starts_with("(module"));
+        assert!(contract_wat.wat.contains(r#"(import "seal0" "seal_input""#));
+        assert!(contract_wat.wat.contains("i32.const 4"));
+        assert!(contract_wat.wat.contains("i32.const 12"));
+        // The printed text is valid wat
+        assert_eq!(wat::parse_str(&contract_wat.wat).unwrap(), wasm());
+    }
+
+    #[test]
+    fn function_filter_prints_a_single_function() {
+        for function in ["2", "call"] {
+            let contract_wat = contract_wat(&wasm(), &metadata(), Some(function)).unwrap();
+
+            assert!(contract_wat.wat.trim_start().starts_with("(func $call"));
+            assert!(contract_wat.wat.contains("i32.const 12"));
+            assert!(!contract_wat.wat.contains("i32.const 4"));
+            assert!(!contract_wat.wat.contains("(data"));
+        }
+
+        let contract_wat = contract_wat(&wasm(), &metadata(), Some("deploy")).unwrap();
+        assert!(contract_wat.wat.contains("i32.const 4"));
+        assert!(!contract_wat.wat.contains("i32.const 12"));
+    }
+
+    #[test]
+    fn unknown_and_imported_functions_are_not_found() {
+        for function in ["9", "missing", "0", "input"] {
+            assert_eq!(
+                contract_wat(&wasm(), &metadata(), Some(function)),
+                Err(WatError::FunctionNotFound(function.to_string()))
+            );
+        }
+        assert!(matches!(
+            contract_wat(b"not wasm", &metadata(), None),
+            Err(WatError::InvalidWasm(_))
+        ));
+    }
+
+    #[test]
+    fn exports_are_mapped_to_the_selectors() {
+        let exports = contract_wat(&wasm(), &metadata(), None).unwrap().exports;
+
+        assert_eq!(exports.len(), 2);
+        assert_eq!(exports[0].name, "deploy");
+        assert_eq!(exports[0].function, 1);
+        assert_eq!(
+            exports[0].selectors,
+            vec![EntryPoint {
+                label: String::from("new"),
+                selector: String::from("0x9bae9d5e"),
+            }]
+        );
+        assert_eq!(exports[1].selectors.len(), 5);
+        assert_eq!(exports[1].selectors[2].label, "PSP22::transfer");
+
+        // Without readable metadata the exports have no selectors
+        let exports = contract_wat(&wasm(), &Value::Null, None).unwrap().exports;
+        assert!(exports.iter().all(|export| export.selectors.is_empty()));
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod wasm_validation_tests {
+    use super::super::*;
+
+    // Minimal contract accepted by pallet-contracts
+    const CONTRACT: &str = r#"(module
+        (import "seal0" "seal_input" (func $input (param i32 i32)))
+        (import "env" "memory" (memory 1 16))
+        (func (export "deploy"))
+        (func (export "call")))"#;
+
+    fn network(id: &str, max_code_size: usize) -> Network {
+        Network {
+            id: id.to_string(),
+            name: id.to_string(),
+            aliases: vec![],
+            ss58_prefix: 42,
+            max_code_size,
+            toolchain_profiles: vec![],
+            explorer: Default::default(),
+        }
+    }
+
+    fn networks() -> NetworkRegistry {
+        NetworkRegistry::new(vec![network("rococo", 131072), network("small", 16)]).unwrap()
+    }
+
+    fn validate(wat: &str) -> WasmValidation {
+        let wasm = wat::parse_str(wat).unwrap();
+        validate_wasm(&wasm, &networks(), networks().resolve("rococo"))
+    }
+
+    fn checks(validation: &WasmValidation) -> Vec<WasmCheck> {
+        validation.issues.iter().map(|issue| issue.check).collect()
+    }
+
+    #[test]
+    fn valid_contract_has_no_issues() {
+        let validation = validate(CONTRACT);
+
+        assert_eq!(validation, WasmValidation::default());
+        assert!(validation.is_valid());
+    }
+
+    #[test]
+    fn invalid_wasm_is_rejected() {
+        let validation = validate_wasm(b"\0asm", &networks(), None);
+
+        assert_eq!(checks(&validation), vec![WasmCheck::Parse]);
+        assert!(!validation.is_valid());
+    }
+
+    #[test]
+    fn float_instructions_are_rejected() {
+        let validation = validate(&CONTRACT.replace(
+            r#"(func (export "call"))"#,
+            r#"(func (export "call") (drop (f32.add (f32.const 1) (f32.const 2))))"#,
+        ));
+
+        assert_eq!(checks(&validation), vec![WasmCheck::FloatInstructions]);
+        assert!(validation.error_message().contains("floating-point"));
+    }
+
+    #[test]
+    fn only_seal_functions_and_env_memory_are_imported() {
+        let validation = validate(&CONTRACT.replace(
+            r#"(import "env" "memory""#,
+            r#"(import "env" "abort" (func))
+               (import "seal_x" "f" (func))
+               (import "seal1" "g" (global i32))
+               (import "env" "memory""#,
+        ));
+
+        assert_eq!(
+            checks(&validation),
+            vec![WasmCheck::Imports, WasmCheck::Imports, WasmCheck::Imports]
+        );
+        assert!(validation.issues[0].message.contains("env.abort"));
+    }
+
+    #[test]
+    fn memory_is_imported_with_a_maximum_within_the_limit() {
+        let unbounded = validate(&CONTRACT.replace("(memory 1 16)", "(memory 1)"));
+        assert_eq!(checks(&unbounded), vec![WasmCheck::Memory]);
+        assert!(unbounded.issues[0].message.contains("no maximum"));
+
+        let too_big = validate(&CONTRACT.replace("(memory 1 16)", "(memory 1 17)"));
+        assert_eq!(checks(&too_big), vec![WasmCheck::Memory]);
+
+        let defined = validate(
+            &CONTRACT.replace(r#"(import "env" "memory" (memory 1 16))"#, "(memory 1 16)"),
+        );
+        assert_eq!(checks(&defined), vec![WasmCheck::Memory, WasmCheck::Memory]);
+    }
+
+    #[test]
+    fn start_function_is_rejected() {
+        let validation = validate(&CONTRACT.replace(
+            r#"(func (export "call")))"#,
+            r#"(func (export "call")) (start 1))"#,
+        ));
+
+        assert_eq!(checks(&validation), vec![WasmCheck::StartFunction]);
+    }
+
+    #[test]
+    fn code_size_is_checked_against_the_networks() {
+        let wasm = wat::parse_str(CONTRACT).unwrap();
+
+        let validation = validate_wasm(&wasm, &networks(), None);
+        assert!(validation.is_valid());
+        assert_eq!(validation.issues[0].severity, Severity::Warning);
+        assert_eq!(
+            validation.issues[0].message,
+            format!(
+                "Code size of {} bytes exceeds the limit of small.",
+                wasm.len()
+            )
+        );
+
+        let validation = validate_wasm(&wasm, &networks(), networks().resolve("small"));
+        assert!(!validation.is_valid());
+        assert_eq!(checks(&validation), vec![WasmCheck::CodeSize]);
+    }
+}
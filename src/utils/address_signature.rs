@@ -0,0 +1,367 @@
+use crate::models::api_models::ServerResponse;
+use crate::utils::sanity_check::decode_ss58_address;
+use log::{error, info};
+use rocket::data::{self, Data, FromData, Limits};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::{Request, State};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Headers carrying the signature of a write request
+pub const SIGNATURE_ADDRESS_HEADER: &str = "X-Signature-Address";
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+pub const SIGNATURE_TYPE_HEADER: &str = "X-Signature-Type";
+pub const SIGNATURE_TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+pub const SIGNATURE_NONCE_HEADER: &str = "X-Signature-Nonce";
+// Hex encoded sha256 of the body, the hash of an empty body when missing
+pub const SIGNATURE_BODY_HASH_HEADER: &str = "X-Signature-Body-Hash";
+
+// Maximum difference in seconds between the signature timestamp and the server clock
+pub const SIGNATURE_MAX_AGE_SECS: u64 = 300;
+
+// Signing context used by substrate wallets for sr25519 signatures
+const SR25519_SIGNING_CONTEXT: &[u8] = b"substrate";
+
+// Supported signature schemes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignatureType {
+    Sr25519,
+    Ed25519,
+}
+
+// Reasons why a signed request is rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignatureError {
+    MissingHeader(&'static str),
+    InvalidAddress(String),
+    InvalidSignature,
+    UnsupportedSignatureType(String),
+    InvalidTimestamp,
+    Expired,
+    Replayed,
+    BodyMismatch,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::MissingHeader(header) => write!(f, "Missing {} header", header),
+            SignatureError::InvalidAddress(err) => write!(f, "Invalid signature address: {}", err),
+            SignatureError::InvalidSignature => write!(f, "Invalid signature"),
+            SignatureError::UnsupportedSignatureType(signature_type) => {
+                write!(f, "Unsupported signature type: {}", signature_type)
+            }
+            SignatureError::InvalidTimestamp => write!(f, "Invalid signature timestamp"),
+            SignatureError::Expired => write!(f, "Signature expired"),
+            SignatureError::Replayed => write!(f, "Signature nonce already used"),
+            SignatureError::BodyMismatch => write!(f, "Request body does not match the signature"),
+        }
+    }
+}
+
+impl SignatureType {
+    pub fn parse(signature_type: &str) -> Result<Self, SignatureError> {
+        match signature_type.to_lowercase().as_str() {
+            "sr25519" => Ok(SignatureType::Sr25519),
+            "ed25519" => Ok(SignatureType::Ed25519),
+            _ => Err(SignatureError::UnsupportedSignatureType(
+                signature_type.to_string(),
+            )),
+        }
+    }
+}
+
+// Builds the message signed by the wallet for a write request
+pub fn canonical_payload(
+    method: &str,
+    path: &str,
+    address: &str,
+    timestamp: u64,
+    nonce: &str,
+    body_hash: &str,
+) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method.to_uppercase(),
+        path,
+        address,
+        timestamp,
+        nonce,
+        body_hash.to_lowercase()
+    )
+}
+
+// Hex encoded sha256 of the request body, covered by the signature
+pub fn body_hash(body: &[u8]) -> String {
+    hex::encode(Sha256::digest(body))
+}
+
+// Verifies the signature of the message by the owner of the SS58 address
+// Wallet extensions wrap raw messages in <Bytes></Bytes> before signing, both forms are accepted
+pub fn verify_signature(
+    address: &str,
+    message: &[u8],
+    signature: &str,
+    signature_type: SignatureType,
+) -> Result<(), SignatureError> {
+    let (_prefix, account_id) = decode_ss58_address(address)
+        .map_err(|err| SignatureError::InvalidAddress(err.to_string()))?;
+    let signature = hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|_| SignatureError::InvalidSignature)?;
+
+    let wrapped_message = [b"<Bytes>".as_slice(), message, b"</Bytes>".as_slice()].concat();
+
+    let verified = match signature_type {
+        SignatureType::Sr25519 => {
+            let public_key = schnorrkel::PublicKey::from_bytes(&account_id)
+                .map_err(|_| SignatureError::InvalidSignature)?;
+            let signature = schnorrkel::Signature::from_bytes(&signature)
+                .map_err(|_| SignatureError::InvalidSignature)?;
+            [message, wrapped_message.as_slice()].iter().any(|message| {
+                public_key
+                    .verify_simple(SR25519_SIGNING_CONTEXT, message, &signature)
+                    .is_ok()
+            })
+        }
+        SignatureType::Ed25519 => {
+            let public_key = ed25519_dalek::VerifyingKey::from_bytes(&account_id)
+                .map_err(|_| SignatureError::InvalidSignature)?;
+            let signature: [u8; 64] = signature
+                .try_into()
+                .map_err(|_| SignatureError::InvalidSignature)?;
+            let signature = ed25519_dalek::Signature::from_bytes(&signature);
+            [message, wrapped_message.as_slice()]
+                .iter()
+                .any(|message| public_key.verify_strict(message, &signature).is_ok())
+        }
+    };
+
+    if verified {
+        Ok(())
+    } else {
+        Err(SignatureError::InvalidSignature)
+    }
+}
+
+// Seconds elapsed since the unix epoch
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+// Store of the nonces used in the last SIGNATURE_MAX_AGE_SECS, used to reject replayed signatures
+// The nonces are kept in the memory of the process, a signature can be replayed against another
+// instance of the service or after a restart while its timestamp is still valid
+#[derive(Default)]
+pub struct NonceStore {
+    pub used_nonces: Mutex<HashMap<String, u64>>,
+}
+
+impl NonceStore {
+    pub fn new() -> Self {
+        NonceStore::default()
+    }
+
+    // Registers the nonce of the address, failing if it was already used
+    pub fn use_nonce(&self, address: &str, nonce: &str, now: u64) -> Result<(), SignatureError> {
+        let mut used_nonces = match self.used_nonces.lock() {
+            Ok(used_nonces) => used_nonces,
+            Err(_) => {
+                error!(target: "compiler", "Error locking nonce store");
+                return Err(SignatureError::Replayed);
+            }
+        };
+
+        // Nonces older than the maximum signature age can not be replayed anymore
+        used_nonces.retain(|_, expiration| *expiration > now);
+
+        let key = format!("{}:{}", address, nonce);
+        if used_nonces.contains_key(&key) {
+            return Err(SignatureError::Replayed);
+        }
+        used_nonces.insert(key, now + 2 * SIGNATURE_MAX_AGE_SECS);
+
+        Ok(())
+    }
+}
+
+// Address of the wallet that signed the request, verified by the request guard
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedAddress(pub String);
+
+impl SignedAddress {
+    // Checks the signer owns the given address, it can be encoded with another network prefix
+    pub fn check_owns<T>(&self, address: &str) -> Result<(), Custom<Json<ServerResponse<T>>>> {
+        let signer = decode_ss58_address(&self.0).map(|(_, account_id)| account_id);
+        let owner = decode_ss58_address(address).map(|(_, account_id)| account_id);
+
+        match (signer, owner) {
+            (Ok(signer), Ok(owner)) if signer == owner => Ok(()),
+            _ => {
+                error!(target: "compiler", "Address {} is not owned by signer {}", address, self.0);
+                Err(Custom(
+                    Status::Forbidden,
+                    Json(ServerResponse::new_error(String::from(
+                        "Signature address does not match the user address.",
+                    ))),
+                ))
+            }
+        }
+    }
+}
+
+// Verifies the headers of a signed request and returns the signer address
+fn verify_request(request: &Request<'_>, nonces: &NonceStore) -> Result<String, SignatureError> {
+    let header = |name: &'static str| {
+        request
+            .headers()
+            .get_one(name)
+            .ok_or(SignatureError::MissingHeader(name))
+    };
+
+    let address = header(SIGNATURE_ADDRESS_HEADER)?;
+    let signature = header(SIGNATURE_HEADER)?;
+    let timestamp = header(SIGNATURE_TIMESTAMP_HEADER)?;
+    let nonce = header(SIGNATURE_NONCE_HEADER)?;
+    let signed_body_hash = match request.headers().get_one(SIGNATURE_BODY_HASH_HEADER) {
+        Some(signed_body_hash) => signed_body_hash.to_lowercase(),
+        None => body_hash(&[]),
+    };
+    let signature_type = match request.headers().get_one(SIGNATURE_TYPE_HEADER) {
+        Some(signature_type) => SignatureType::parse(signature_type)?,
+        None => SignatureType::Sr25519,
+    };
+
+    // The signature must be recent so the nonce store can be kept small
+    let timestamp: u64 = timestamp
+        .parse()
+        .map_err(|_| SignatureError::InvalidTimestamp)?;
+    let now = unix_timestamp();
+    if timestamp.abs_diff(now) > SIGNATURE_MAX_AGE_SECS {
+        return Err(SignatureError::Expired);
+    }
+
+    let payload = canonical_payload(
+        request.method().as_str(),
+        request.uri().path().as_str(),
+        address,
+        timestamp,
+        nonce,
+        &signed_body_hash,
+    );
+    verify_signature(address, payload.as_bytes(), signature, signature_type)?;
+
+    // The nonce is only consumed by valid signatures
+    nonces.use_nonce(address, nonce, now)?;
+
+    // The body is read after the request guards, SignedJson checks it against this hash
+    request.local_cache(|| SignedBodyHash(Some(signed_body_hash)));
+
+    Ok(address.to_string())
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SignedAddress {
+    type Error = SignatureError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let nonces = match request.guard::<&State<NonceStore>>().await {
+            Outcome::Success(nonces) => nonces,
+            _ => {
+                error!(target: "compiler", "Nonce store is not managed");
+                return Outcome::Error((Status::InternalServerError, SignatureError::Replayed));
+            }
+        };
+
+        match verify_request(request, nonces) {
+            Ok(address) => {
+                info!(target: "compiler", "Request signed by {}", address);
+                Outcome::Success(SignedAddress(address))
+            }
+            Err(err) => {
+                error!(target: "compiler", "Rejecting signed request: {}", err);
+                request.local_cache(|| Some(err.clone()));
+                Outcome::Error((Status::Unauthorized, err))
+            }
+        }
+    }
+}
+
+// Body hash covered by the signature of the request, None when the request is not signed
+struct SignedBodyHash(Option<String>);
+
+// JSON body of a write route, the body of a signed request must match the hash covered by its signature
+#[derive(Debug)]
+pub struct SignedJson<T>(pub T);
+
+impl<T> SignedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for SignedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: DeserializeOwned> FromData<'r> for SignedJson<T> {
+    type Error = String;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        let limit = request.limits().get("json").unwrap_or(Limits::JSON);
+        let body = match data.open(limit).into_bytes().await {
+            Ok(body) if body.is_complete() => body.into_inner(),
+            Ok(_) => {
+                let err = format!("Request body is over the limit of {}", limit);
+                error!(target: "compiler", "{}", err);
+                return data::Outcome::Error((Status::PayloadTooLarge, err));
+            }
+            Err(err) => {
+                error!(target: "compiler", "Error reading the request body: {}", err);
+                return data::Outcome::Error((Status::BadRequest, err.to_string()));
+            }
+        };
+
+        if let SignedBodyHash(Some(signed_body_hash)) = request.local_cache(|| SignedBodyHash(None))
+        {
+            if *signed_body_hash != body_hash(&body) {
+                let err = SignatureError::BodyMismatch;
+                error!(target: "compiler", "Rejecting signed request: {}", err);
+                request.local_cache(|| Some(err.clone()));
+                return data::Outcome::Error((Status::Unauthorized, err.to_string()));
+            }
+        }
+
+        // Same statuses as the Json guard, 422 for a well formed body with the wrong fields
+        match serde_json::from_slice(&body) {
+            Ok(value) => data::Outcome::Success(SignedJson(value)),
+            Err(err) => {
+                error!(target: "compiler", "Invalid JSON body: {}", err);
+                let status = match err.classify() {
+                    serde_json::error::Category::Data => Status::UnprocessableEntity,
+                    _ => Status::BadRequest,
+                };
+                data::Outcome::Error((status, err.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/address_signature_tests.rs"]
+mod address_signature_test;
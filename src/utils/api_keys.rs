@@ -0,0 +1,328 @@
+use crate::models::api_models::ServerResponse;
+use crate::models::db_models::{ApiKey, ApiKeyScope};
+use crate::repository::Database;
+use crate::utils::config::AppConfig;
+use crate::utils::sanity_check::decode_ss58_address;
+use crate::utils::session::AuthenticatedAddress;
+use log::{error, info};
+use mongodb::bson::DateTime;
+use rand::RngCore;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::{Request, State};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use super::address_signature::unix_timestamp;
+
+// Header carrying the API key of service clients
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+// Header carrying the token of the administration routes
+pub const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+
+// Requests per minute allowed for a key minted without an explicit limit
+pub const DEFAULT_API_KEY_RATE_LIMIT: u32 = 60;
+
+// Prefix of the keys, it makes them easy to spot in configuration files
+const API_KEY_PREFIX: &str = "ck";
+
+// Reasons why an API key is rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiKeyError {
+    InvalidKey,
+    Revoked,
+    RateLimited,
+    Unavailable,
+}
+
+impl fmt::Display for ApiKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiKeyError::InvalidKey => write!(f, "Invalid API key"),
+            ApiKeyError::Revoked => write!(f, "API key revoked"),
+            ApiKeyError::RateLimited => write!(f, "API key rate limit exceeded"),
+            ApiKeyError::Unavailable => write!(f, "Error checking API key"),
+        }
+    }
+}
+
+impl ApiKeyError {
+    fn status(&self) -> Status {
+        match self {
+            ApiKeyError::InvalidKey | ApiKeyError::Revoked => Status::Unauthorized,
+            ApiKeyError::RateLimited => Status::TooManyRequests,
+            ApiKeyError::Unavailable => Status::InternalServerError,
+        }
+    }
+}
+
+impl fmt::Display for ApiKeyScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiKeyScope::Compile => write!(f, "compile"),
+            ApiKeyScope::Read => write!(f, "read"),
+            ApiKeyScope::WriteDeployments => write!(f, "write_deployments"),
+        }
+    }
+}
+
+// Hashes the key as it is stored in the database
+pub fn hash_api_key(api_key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(api_key.trim());
+    format!("{:x}", hasher.finalize())
+}
+
+// Generates a new key, returns its public id and the secret key given to the client
+pub fn generate_api_key() -> (String, String) {
+    let mut key_id = [0u8; 6];
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key_id);
+    rand::thread_rng().fill_bytes(&mut secret);
+
+    let key_id = hex::encode(key_id);
+    let api_key = format!("{}_{}_{}", API_KEY_PREFIX, key_id, hex::encode(secret));
+    (key_id, api_key)
+}
+
+// Fixed window counter of the requests made with each key in the current minute
+#[derive(Default)]
+pub struct ApiKeyRateLimiter {
+    pub windows: Mutex<HashMap<String, (u64, u32)>>,
+}
+
+impl ApiKeyRateLimiter {
+    pub fn new() -> Self {
+        ApiKeyRateLimiter::default()
+    }
+
+    // Counts a request of the key, failing if it exceeds the per minute limit
+    pub fn check(&self, api_key: &ApiKey, now: u64) -> Result<(), ApiKeyError> {
+        let mut windows = self.windows.lock().map_err(|_| {
+            error!(target: "compiler", "Error locking API key rate limiter");
+            ApiKeyError::Unavailable
+        })?;
+
+        let minute = now / 60;
+        let window = windows.entry(api_key.key_id.clone()).or_insert((minute, 0));
+        if window.0 != minute {
+            *window = (minute, 0);
+        }
+
+        if window.1 >= api_key.rate_limit_per_minute {
+            return Err(ApiKeyError::RateLimited);
+        }
+        window.1 += 1;
+
+        Ok(())
+    }
+}
+
+// Looks up the key of the request, checks its rate limit and records its usage
+async fn verify_api_key(request: &Request<'_>, api_key: &str) -> Result<ApiKey, ApiKeyError> {
+    let (db, rate_limiter) = match (
+        request.guard::<&State<Database>>().await,
+        request.guard::<&State<ApiKeyRateLimiter>>().await,
+    ) {
+        (Outcome::Success(db), Outcome::Success(rate_limiter)) => (db, rate_limiter),
+        _ => {
+            error!(target: "compiler", "API key state is not managed");
+            return Err(ApiKeyError::Unavailable);
+        }
+    };
+
+    let api_key = match db.get_api_key_by_hash(&hash_api_key(api_key)).await {
+        Ok(Some(api_key)) => api_key,
+        Ok(None) => return Err(ApiKeyError::InvalidKey),
+        Err(_) => {
+            error!(target: "compiler", "There was DB error fetching an API key");
+            return Err(ApiKeyError::Unavailable);
+        }
+    };
+
+    if api_key.revoked {
+        return Err(ApiKeyError::Revoked);
+    }
+    rate_limiter.check(&api_key, unix_timestamp())?;
+
+    let now = DateTime::now().try_to_rfc3339_string().unwrap_or_default();
+    if db
+        .record_api_key_usage(&api_key.key_id, &now)
+        .await
+        .is_err()
+    {
+        error!(target: "compiler", "There was an error recording the usage of API key {}", &api_key.key_id);
+    }
+
+    info!(target: "compiler", "Request authenticated by API key {} ({})", &api_key.key_id, &api_key.name);
+    Ok(api_key)
+}
+
+// Error returned when the key of the request lacks a scope
+fn missing_scope<T>(api_key: &ApiKey, scope: ApiKeyScope) -> Custom<Json<ServerResponse<T>>> {
+    error!(target: "compiler", "API key {} is missing the {} scope", &api_key.key_id, scope);
+    Custom(
+        Status::Forbidden,
+        Json(ServerResponse::new_error(format!(
+            "API key is missing the {} scope.",
+            scope
+        ))),
+    )
+}
+
+// Checks the address is in the list of the key, it can be encoded with another network prefix
+fn allows_address(api_key: &ApiKey, address: &str) -> bool {
+    let account_id = match decode_ss58_address(address) {
+        Ok((_, account_id)) => account_id,
+        Err(_) => return false,
+    };
+    api_key
+        .addresses
+        .iter()
+        .any(|allowed| decode_ss58_address(allowed).is_ok_and(|(_, allowed)| allowed == account_id))
+}
+
+// Caller of a protected route, either an authenticated wallet or a service client
+#[derive(Debug, Clone, PartialEq)]
+pub enum Caller {
+    Wallet(AuthenticatedAddress),
+    Service(ApiKey),
+}
+
+impl Caller {
+    // Wallets can use every route, service clients need the scope of the route
+    pub fn require<T>(&self, scope: ApiKeyScope) -> Result<(), Custom<Json<ServerResponse<T>>>> {
+        match self {
+            Caller::Wallet(_) => Ok(()),
+            Caller::Service(api_key) if api_key.has_scope(scope) => Ok(()),
+            Caller::Service(api_key) => Err(missing_scope(api_key, scope)),
+        }
+    }
+
+    // Wallets can only write their own deployments, service clients the ones of the addresses of their key
+    pub fn check_owns<T>(&self, address: &str) -> Result<(), Custom<Json<ServerResponse<T>>>> {
+        match self {
+            Caller::Wallet(authenticated_address) => authenticated_address.check_owns(address),
+            Caller::Service(api_key) => {
+                self.require(ApiKeyScope::WriteDeployments)?;
+                if allows_address(api_key, address) {
+                    return Ok(());
+                }
+                error!(target: "compiler", "API key {} cannot write the deployments of {}", &api_key.key_id, address);
+                Err(Custom(
+                    Status::Forbidden,
+                    Json(ServerResponse::new_error(String::from(
+                        "API key is not allowed to write the deployments of the user address.",
+                    ))),
+                ))
+            }
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Caller {
+    type Error = ApiKeyError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let api_key = match request.headers().get_one(API_KEY_HEADER) {
+            Some(api_key) => api_key,
+            // Without an API key the caller must be a wallet, its guard reports the rejection
+            None => {
+                return match request.guard::<AuthenticatedAddress>().await {
+                    Outcome::Success(address) => Outcome::Success(Caller::Wallet(address)),
+                    Outcome::Error((status, _)) => {
+                        Outcome::Error((status, ApiKeyError::InvalidKey))
+                    }
+                    Outcome::Forward(status) => Outcome::Forward(status),
+                }
+            }
+        };
+
+        match verify_api_key(request, api_key).await {
+            Ok(api_key) => Outcome::Success(Caller::Service(api_key)),
+            Err(err) => {
+                error!(target: "compiler", "Rejecting API key: {}", err);
+                request.local_cache(|| Some(err.clone()));
+                Outcome::Error((err.status(), err))
+            }
+        }
+    }
+}
+
+// API key of a public route, anonymous requests are allowed
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionalApiKey(pub Option<ApiKey>);
+
+impl OptionalApiKey {
+    // Requests made with a key need the scope of the route
+    pub fn require<T>(&self, scope: ApiKeyScope) -> Result<(), Custom<Json<ServerResponse<T>>>> {
+        match &self.0 {
+            Some(api_key) if !api_key.has_scope(scope) => Err(missing_scope(api_key, scope)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for OptionalApiKey {
+    type Error = ApiKeyError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let api_key = match request.headers().get_one(API_KEY_HEADER) {
+            Some(api_key) => api_key,
+            None => return Outcome::Success(OptionalApiKey(None)),
+        };
+
+        match verify_api_key(request, api_key).await {
+            Ok(api_key) => Outcome::Success(OptionalApiKey(Some(api_key))),
+            Err(err) => {
+                error!(target: "compiler", "Rejecting API key: {}", err);
+                request.local_cache(|| Some(err.clone()));
+                Outcome::Error((err.status(), err))
+            }
+        }
+    }
+}
+
+// Guard of the administration routes, the request must carry the configured admin token
+pub struct Admin;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let admin_token = match request
+            .rocket()
+            .state::<AppConfig>()
+            .and_then(|config| config.auth.admin_token.as_ref())
+        {
+            Some(admin_token) if !admin_token.is_empty() => admin_token,
+            _ => {
+                error!(target: "compiler", "Administration routes are disabled, auth.admin_token is not set");
+                return Outcome::Error((Status::Unauthorized, ()));
+            }
+        };
+
+        // Hashes are compared so the comparison does not leak the token length
+        match request.headers().get_one(ADMIN_TOKEN_HEADER) {
+            Some(token) if hash_api_key(token) == hash_api_key(admin_token) => {
+                Outcome::Success(Admin)
+            }
+            _ => {
+                error!(target: "compiler", "Rejecting administration request");
+                Outcome::Error((Status::Unauthorized, ()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/api_keys_tests.rs"]
+mod api_keys_test;
@@ -0,0 +1,127 @@
+use crate::models::api_models::ServerResponse;
+use crate::models::db_models::Contract;
+use rocket::http::{ContentType, Header, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::status::Custom;
+use rocket::response::{self, Responder};
+use rocket::serde::json::Json;
+use rocket::{Request, Response};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+// Artifacts never change once stored, the code id is the hash of the source code
+pub const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+pub type ArtifactResult = Result<ArtifactResponse, Custom<Json<ServerResponse<String>>>>;
+
+// Raw contract artifact served with its content type and caching headers
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtifactResponse {
+    pub content_type: ContentType,
+    pub filename: String,
+    pub etag: String,
+    // None when the client already has the artifact
+    pub body: Option<Vec<u8>>,
+}
+
+impl ArtifactResponse {
+    // Builds the response, without a body when the client cache matches the ETag
+    pub fn new(
+        content_type: ContentType,
+        filename: String,
+        body: Vec<u8>,
+        if_none_match: &IfNoneMatch,
+    ) -> Self {
+        let etag = etag(&body);
+        let body = match if_none_match.matches(&etag) {
+            true => None,
+            false => Some(body),
+        };
+        ArtifactResponse {
+            content_type,
+            filename,
+            etag,
+            body,
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ArtifactResponse {
+    fn respond_to(self, _request: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = Response::build();
+        response
+            .header(Header::new("ETag", self.etag))
+            .header(Header::new("Cache-Control", IMMUTABLE_CACHE_CONTROL));
+
+        match self.body {
+            Some(body) => response
+                .header(self.content_type)
+                .header(Header::new(
+                    "Content-Disposition",
+                    format!("attachment; filename=\"{}\"", self.filename),
+                ))
+                .sized_body(body.len(), Cursor::new(body))
+                .ok(),
+            None => response.status(Status::NotModified).ok(),
+        }
+    }
+}
+
+// ETags of the If-None-Match request header
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IfNoneMatch(pub Vec<String>);
+
+impl IfNoneMatch {
+    // Weak comparison, as required for If-None-Match, "*" matches any artifact
+    pub fn matches(&self, etag: &str) -> bool {
+        self.0
+            .iter()
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IfNoneMatch {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let etags = request
+            .headers()
+            .get("If-None-Match")
+            .flat_map(|header| header.split(','))
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        Outcome::Success(IfNoneMatch(etags))
+    }
+}
+
+// Strong ETag of an artifact, the hash of its content
+pub fn etag(body: &[u8]) -> String {
+    format!("\"{:x}\"", Sha256::digest(body))
+}
+
+// Content type of the wasm binaries
+pub fn wasm_content_type() -> ContentType {
+    ContentType::new("application", "wasm")
+}
+
+// Builds the cargo-contract bundle, the metadata with the wasm embedded as hex in the source section
+pub fn contract_bundle(contract: &Contract) -> Result<Vec<u8>, String> {
+    let mut bundle = contract.metadata.clone();
+    let source = bundle
+        .get_mut("source")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| String::from("Contract metadata has no source section"))?;
+    source.insert(
+        String::from("wasm"),
+        Value::String(format!("0x{}", hex::encode(&contract.wasm))),
+    );
+
+    serde_json::to_vec(&bundle).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/artifacts_tests.rs"]
+mod artifacts_test;
@@ -1,14 +1,23 @@
 use super::super::models::api_models::WizardMessage;
 use crate::models::db_models::Contract;
-use std::sync::mpsc;
+use crate::utils::compiler::BuildLog;
 use std::sync::{Arc, Mutex};
 use log::{error};
+use rocket::tokio::sync::oneshot;
 
 // Compilation Request structure
 pub struct CompilationRequest {
     pub wizard_message: WizardMessage,
     pub code_id: String,
-    pub tx: mpsc::Sender<Result<Contract, String>>,
+    pub request_id: String,
+    // The handler awaits the result without blocking an async worker
+    pub tx: oneshot::Sender<CompilationResult>,
+}
+
+// Compilation Result sent back by the compiler for every CompilationRequest
+pub struct CompilationResult {
+    pub contract: Result<Contract, String>,
+    pub build_log: BuildLog,
 }
 
 // Compilation Queue is a thread-safe queue that holds CompilationRequests
@@ -4,12 +4,64 @@ use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::atomic::AtomicBool;
-use std::{env, sync::Arc, thread};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::{sync::Arc, thread};
 
-use crate::models::api_models::WizardMessage;
+use rocket::fairing::AdHoc;
+
+use crate::models::api_models::{BuildOptions, WizardMessage};
 use crate::models::db_models::Contract;
 
-use super::compilation_queue::CompilationQueue;
+use super::compilation_queue::{CompilationQueue, CompilationResult};
+use super::config::{AppConfig, CompilerConfig};
+use super::ink_metadata::parse_metadata;
+use super::network_registry::NetworkRegistry;
+use super::request_id::REQUEST_ID_LOG_KEY;
+use super::size_report::size_report;
+use super::wasm_validation::validate_wasm;
+
+// Maximum size in bytes of the build output kept for each job
+pub const MAX_BUILD_LOG_SIZE: usize = 64 * 1024;
+
+// Artifacts written by cargo contract, the original wasm is the output of the linker before wasm-opt
+pub const WASM_PATH: &str = "target/ink/compiled_contract.wasm";
+pub const ORIGINAL_WASM_PATH: &str =
+    "target/ink/wasm32-unknown-unknown/release/compiled_contract.wasm";
+pub const METADATA_PATH: &str = "target/ink/compiled_contract.json";
+
+// Output of a cargo contract build, capped to MAX_BUILD_LOG_SIZE bytes
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BuildLog {
+    pub output: String,
+    pub truncated: bool,
+}
+
+impl BuildLog {
+    // Creates the log from the raw stdout and stderr of the build, keeping the tail when it is too long
+    pub fn from_output(stdout: &[u8], stderr: &[u8]) -> Self {
+        let mut output = String::from_utf8_lossy(stdout).into_owned();
+        output.push_str(&String::from_utf8_lossy(stderr));
+
+        if output.len() <= MAX_BUILD_LOG_SIZE {
+            return BuildLog {
+                output,
+                truncated: false,
+            };
+        }
+
+        // The errors are at the end of the output so the beginning is discarded
+        let mut start = output.len() - MAX_BUILD_LOG_SIZE;
+        while !output.is_char_boundary(start) {
+            start += 1;
+        }
+
+        BuildLog {
+            output: output[start..].to_string(),
+            truncated: true,
+        }
+    }
+}
 
 // Compiler is a singleton that handles the compilation of contracts
 pub struct Compiler {
@@ -17,41 +69,66 @@ pub struct Compiler {
     pub compilation_queue: Arc<CompilationQueue>,
     pub shutdown_flag: Arc<AtomicBool>,
     pub dir_path: PathBuf,
+    // Networks whose code size limits are checked by the wasm validation
+    pub networks: NetworkRegistry,
 }
 
+// Handle of the thread running the compiler
+pub struct CompilerThread(pub Mutex<Option<JoinHandle<()>>>);
+
 // Compiler implementation
 impl Compiler {
     // Initializes the compiler
-    pub fn init(compilation_queue: Arc<CompilationQueue>, shutdown_flag: Arc<AtomicBool>) -> Self {
+    pub fn init(
+        config: &CompilerConfig,
+        networks: NetworkRegistry,
+        compilation_queue: Arc<CompilationQueue>,
+        shutdown_flag: Arc<AtomicBool>,
+    ) -> Self {
         info!(target: "compiler", "Initializing compiler");
-        let cargo_loc = match env::var("CARGO") {
-            Ok(v) => v.to_string(),
-            Err(_) => {
-                error!(target: "compiler", "CARGO environment variable not set");
-                std::process::exit(1);
-            }
-        };
-
-        // Create the directory for the compiler
-        let current_dir = env::current_dir();
-        if current_dir.is_err() {
-            error!(target: "compiler", "Error getting current directory");
-            std::process::exit(1);
-        }
-
-        let current_dir =
-            current_dir.expect("This will never panic because we checked for errors before");
-
-        let dir_path = current_dir.join("compilation_target");
 
         Compiler {
-            cargo_loc,
+            cargo_loc: config.cargo.clone(),
             compilation_queue,
             shutdown_flag,
-            dir_path,
+            dir_path: config.workspace.clone(),
+            networks,
         }
     }
 
+    // Fairing starting the compiler thread once the configuration is loaded
+    // The thread handle is managed so the shutdown handler can join it
+    pub fn fairing(
+        compilation_queue: Arc<CompilationQueue>,
+        shutdown_flag: Arc<AtomicBool>,
+    ) -> AdHoc {
+        AdHoc::try_on_ignite("Compiler", |rocket| async move {
+            let config = match rocket.state::<AppConfig>() {
+                Some(config) => config.compiler.clone(),
+                None => {
+                    error!(target: "compiler", "Configuration is not loaded");
+                    return Err(rocket);
+                }
+            };
+
+            // The network registry is loaded by its own fairing, attached before this one
+            let networks = match rocket.state::<NetworkRegistry>() {
+                Some(networks) => networks.clone(),
+                None => {
+                    error!(target: "compiler", "Network registry is not loaded");
+                    return Err(rocket);
+                }
+            };
+
+            let compiler = Compiler::init(&config, networks, compilation_queue, shutdown_flag);
+            let compiler_thread = thread::spawn(move || {
+                compiler.start();
+            });
+
+            Ok(rocket.manage(CompilerThread(Mutex::new(Some(compiler_thread)))))
+        })
+    }
+
     // Main compiler function
     // It has 3 stages:
     // Stage 1.- Initialize compiler and compile template contract
@@ -70,11 +147,12 @@ impl Compiler {
         }
 
         // Compile init contract
-        let res = self.compile_contract();
+        let mut init_build_log = BuildLog::default();
+        let res = self.compile_contract(&BuildOptions::default(), &mut init_build_log);
 
         if res.is_err() {
             self.delete_compilation_files();
-            error!(target: "compiler", "Error compiling init contract");
+            error!(target: "compiler", "Error compiling init contract: {}", init_build_log.output);
         }
 
         // Stage 2.-
@@ -88,9 +166,13 @@ impl Compiler {
 
             // Checking if there's something to do
             if let Some(request) = request {
+                // Tag every log record of this compilation with the originating request id
+                let _log_scope = log_mdc::insert_scoped(REQUEST_ID_LOG_KEY, &request.request_id);
+
                 // Perform the compilation for the request here
                 info!(target: "compiler",
-                    "Compiling code for user: {}",
+                    "Compiling code {} for user: {}",
+                    request.code_id,
                     request.wizard_message.address
                 );
 
@@ -101,7 +183,10 @@ impl Compiler {
                 if compile_res.is_err() {
                     self.delete_compilation_files();
                     error!(target: "compiler", "Error creating files");
-                    let msg_res = request.tx.send(Err(String::from("Error creating files.")));
+                    let msg_res = request.tx.send(CompilationResult {
+                        contract: Err(String::from("Error creating files.")),
+                        build_log: BuildLog::default(),
+                    });
 
                     if msg_res.is_err() {
                         error!(target: "compiler", "Error sending message");
@@ -111,14 +196,16 @@ impl Compiler {
                 }
 
                 // Compile contract
-                let res = self.compile_contract();
+                let mut build_log = BuildLog::default();
+                let res = self.compile_contract(&wizard_message.build, &mut build_log);
 
                 // Evaluate compilation result
                 if res.is_err() {
                     error!(target: "compiler", "Error compiling contract");
-                    let msg_res = request
-                        .tx
-                        .send(Err(String::from("Error compiling contract.")));
+                    let msg_res = request.tx.send(CompilationResult {
+                        contract: Err(String::from("Error compiling contract.")),
+                        build_log,
+                    });
 
                     if msg_res.is_err() {
                         error!(target: "compiler", "Error sending message");
@@ -132,9 +219,34 @@ impl Compiler {
 
                 if contract.is_err() {
                     error!(target: "compiler", "Error getting contract data");
-                    let msg_res = request
-                        .tx
-                        .send(Err(String::from("Error getting contract data.")));
+                    let msg_res = request.tx.send(CompilationResult {
+                        contract: Err(String::from("Error getting contract data.")),
+                        build_log,
+                    });
+
+                    if msg_res.is_err() {
+                        error!(target: "compiler", "Error sending message");
+                    }
+
+                    continue;
+                }
+
+                let mut contract =
+                    contract.expect("This will not panic because we already checked for errors");
+
+                // Validate the wasm against the rules of pallet-contracts, invalid code is not stored
+                let network = wizard_message
+                    .network
+                    .as_deref()
+                    .and_then(|network| self.networks.resolve(network));
+                let validation = validate_wasm(&contract.wasm, &self.networks, network);
+
+                if !validation.is_valid() {
+                    error!(target: "compiler", "Contract wasm is not valid: {:?}", validation.issues);
+                    let msg_res = request.tx.send(CompilationResult {
+                        contract: Err(validation.error_message()),
+                        build_log,
+                    });
 
                     if msg_res.is_err() {
                         error!(target: "compiler", "Error sending message");
@@ -142,10 +254,12 @@ impl Compiler {
 
                     continue;
                 }
+                contract.validation = Some(validation);
 
-                let msg_res =
-                    request.tx.send(Ok(contract
-                        .expect("This will not panic because we already checked for errors")));
+                let msg_res = request.tx.send(CompilationResult {
+                    contract: Ok(contract),
+                    build_log,
+                });
                 if msg_res.is_err() {
                     error!(target: "compiler", "Error sending message");
                 }
@@ -162,19 +276,31 @@ impl Compiler {
     }
 
     // Function called by the compiler to generate the contract wasm and metadata
-    fn compile_contract(&self) -> Result<(), Box<dyn std::error::Error>> {
+    // The output of the build is captured in build_log
+    fn compile_contract(
+        &self,
+        build_options: &BuildOptions,
+        build_log: &mut BuildLog,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // This is the command used to compile the contract
         let mut binding = Command::new(self.cargo_loc.clone());
         let compiler_cmd = binding
             .arg("contract")
             .arg("build")
             .arg("--release")
-            .arg("--quiet")
+            .arg(format!(
+                "--optimization-passes={}",
+                build_options.optimization.passes()
+            ))
             .current_dir(self.dir_path.clone());
+        if build_options.keep_debug_names {
+            compiler_cmd.arg("--keep-debug-symbols");
+        }
 
-        // Check the status of the command execution
-        let status = compiler_cmd.status()?;
-        if !status.success() {
+        // Capture the output and check the status of the command execution
+        let output = compiler_cmd.output()?;
+        *build_log = BuildLog::from_output(&output.stdout, &output.stderr);
+        if !output.status.success() {
             error!(target: "compiler", "Compilation failed");
             return Err("Compilation failed".into());
         }
@@ -216,21 +342,34 @@ impl Compiler {
         code_id: &String,
     ) -> Result<Contract, Box<dyn std::error::Error>> {
         // Read compiled contract
-        let mut wasm_file = File::open(self.dir_path.join("target/ink/compiled_contract.wasm"))?;
+        let mut wasm_file = File::open(self.dir_path.join(WASM_PATH))?;
         let mut wasm = Vec::new();
         wasm_file.read_to_end(&mut wasm)?;
 
+        // The original wasm is only used for the size report
+        let original_wasm = std::fs::read(self.dir_path.join(ORIGINAL_WASM_PATH)).ok();
+        let size_report = match size_report(original_wasm.as_deref(), &wasm) {
+            Ok(size_report) => Some(size_report),
+            Err(err) => {
+                error!(target: "compiler", "Error building the size report: {}", err);
+                None
+            }
+        };
+
         // Read contract metadata
-        let mut metadata_file =
-            File::open(self.dir_path.join("target/ink/compiled_contract.json"))?;
+        let mut metadata_file = File::open(self.dir_path.join(METADATA_PATH))?;
         let mut metadata = String::new();
         metadata_file.read_to_string(&mut metadata)?;
+        let metadata = parse_metadata(&metadata)?;
 
         let contract = Contract {
             id: None,
             code_id: code_id.to_owned(),
             metadata,
             wasm,
+            legacy_metadata: None,
+            validation: None,
+            size_report,
         };
         info!(target: "compiler", "get_contract_data success");
 
@@ -240,4 +379,4 @@ impl Compiler {
 
 #[cfg(test)]
 #[path = "../tests/utils/compiler_tests.rs"]
-mod compiler_tests;
+mod compiler_test;
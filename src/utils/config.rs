@@ -0,0 +1,317 @@
+use crate::utils::cors::CorsConfig;
+use crate::utils::network_registry::{Network, NetworkRegistry};
+use crate::utils::rate_limiter::RateLimitConfig;
+use crate::utils::session::SessionKeys;
+use log::{error, info};
+use rocket::fairing::AdHoc;
+use rocket::figment::providers::Serialized;
+use rocket::figment::Figment;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::PathBuf;
+
+// Maximum size in bytes of the contract source code accepted when not configured
+pub const DEFAULT_MAX_CODE_SIZE: usize = 49999;
+
+// Toolchain profile of the compiler when not configured, see Network::toolchain_profiles
+pub const DEFAULT_TOOLCHAIN_PROFILE: &str = "ink-v4";
+
+// Features accepted in a wizard message when not configured
+pub const DEFAULT_ALLOWED_FEATURES: [&str; 6] = [
+    "psp22",
+    "psp34",
+    "psp37",
+    "pausable",
+    "ownable",
+    "access-control",
+];
+
+// Artifacts stored in a contract document above this size are moved to GridFS when not configured
+pub const DEFAULT_GRIDFS_THRESHOLD: usize = 4 * 1024 * 1024;
+
+// Largest inline artifact, the wasm and the metadata of a contract must fit in a 16MB BSON document
+pub const MAX_INLINE_ARTIFACT_SIZE: usize = 8 * 1024 * 1024;
+
+// Environment variables kept for compatibility with older deployments, and the configuration key they set
+const LEGACY_ENV_VARS: [(&str, &str); 4] = [
+    ("MONGOURI", "mongo.uri"),
+    ("CARGO", "compiler.cargo"),
+    ("SESSION_TOKEN_SECRET", "auth.session_token_secret"),
+    ("ADMIN_TOKEN", "auth.admin_token"),
+];
+
+// Service configuration, loaded from Rocket.toml and ROCKET_ prefixed environment variables
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AppConfig {
+    #[serde(default)]
+    pub storage: StorageBackend,
+    #[serde(default)]
+    pub mongo: MongoConfig,
+    #[serde(default)]
+    pub sqlite: SqliteConfig,
+    #[serde(default)]
+    pub compiler: CompilerConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    // Required, checked by NetworkRegistry::new
+    #[serde(default)]
+    pub networks: Vec<Network>,
+}
+
+// Backend storing the contracts, deployments, jobs and API keys
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    #[default]
+    Mongo,
+    // Nothing is persisted, meant for development and tests
+    Memory,
+    // Local database file, requires the sqlite feature
+    Sqlite,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MongoConfig {
+    // Required by the mongo storage backend
+    #[serde(default)]
+    pub uri: String,
+    #[serde(default = "default_database")]
+    pub database: String,
+    #[serde(default)]
+    pub collections: CollectionNames,
+    #[serde(default)]
+    pub artifacts: ArtifactConfig,
+}
+
+// Encoding of the contract wasm and metadata in the contracts collection
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ArtifactConfig {
+    pub compression: ArtifactCompression,
+    // Size in bytes, after compression, above which an artifact is stored in GridFS
+    pub gridfs_threshold: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactCompression {
+    None,
+    #[default]
+    Zstd,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SqliteConfig {
+    // Database file, it is created when it does not exist
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct CollectionNames {
+    pub contracts: String,
+    pub deployments: String,
+    pub jobs: String,
+    pub api_keys: String,
+    pub compile_quotas: String,
+    // Schema migrations applied to the database
+    pub migrations: String,
+    // GridFS bucket of the large contract artifacts
+    pub artifacts: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct CompilerConfig {
+    // Path of the cargo binary with cargo-contract installed
+    pub cargo: String,
+    // Directory with the template contract, relative paths are resolved from the working directory
+    pub workspace: PathBuf,
+    // Maximum size in bytes of the contract source code
+    pub max_code_size: usize,
+    pub allowed_features: Vec<String>,
+    // Toolchain the contracts are built with, the target network must support it
+    pub toolchain_profile: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct AuthConfig {
+    // Secret signing the session tokens, the server does not start without it
+    pub session_token_secret: Option<String>,
+    // Token of the administration routes, they are disabled when it is not set
+    pub admin_token: Option<String>,
+}
+
+fn default_database() -> String {
+    String::from("ContractWizard")
+}
+
+impl Default for MongoConfig {
+    fn default() -> Self {
+        MongoConfig {
+            uri: String::new(),
+            database: default_database(),
+            collections: CollectionNames::default(),
+            artifacts: ArtifactConfig::default(),
+        }
+    }
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        SqliteConfig {
+            path: PathBuf::from("compiler.db"),
+        }
+    }
+}
+
+impl Default for CollectionNames {
+    fn default() -> Self {
+        CollectionNames {
+            contracts: String::from("Contracts"),
+            deployments: String::from("Deployments"),
+            jobs: String::from("Jobs"),
+            api_keys: String::from("ApiKeys"),
+            compile_quotas: String::from("CompileQuotas"),
+            migrations: String::from("Migrations"),
+            artifacts: String::from("Artifacts"),
+        }
+    }
+}
+
+impl Default for ArtifactConfig {
+    fn default() -> Self {
+        ArtifactConfig {
+            compression: ArtifactCompression::default(),
+            gridfs_threshold: DEFAULT_GRIDFS_THRESHOLD,
+        }
+    }
+}
+
+impl Default for CompilerConfig {
+    fn default() -> Self {
+        CompilerConfig {
+            cargo: String::from("cargo"),
+            workspace: PathBuf::from("compilation_target"),
+            max_code_size: DEFAULT_MAX_CODE_SIZE,
+            allowed_features: DEFAULT_ALLOWED_FEATURES
+                .iter()
+                .map(|feature| feature.to_string())
+                .collect(),
+            toolchain_profile: String::from(DEFAULT_TOOLCHAIN_PROFILE),
+        }
+    }
+}
+
+impl AppConfig {
+    // Adds the legacy environment variables to the Rocket figment, they take precedence over the files
+    pub fn figment(figment: Figment) -> Figment {
+        LEGACY_ENV_VARS
+            .iter()
+            .fold(figment, |figment, (env_var, key)| match env::var(env_var) {
+                Ok(value) if !value.is_empty() => figment.merge(Serialized::global(key, value)),
+                _ => figment,
+            })
+    }
+
+    // Extracts the configuration from the figment and validates it
+    pub fn from_figment(figment: &Figment) -> Result<Self, String> {
+        let mut config: AppConfig = figment.extract().map_err(|err| err.to_string())?;
+
+        if config.storage == StorageBackend::Mongo {
+            if config.mongo.uri.trim().is_empty() {
+                return Err(String::from(
+                    "mongo.uri is not set, MONGOURI or ROCKET_MONGO must set the database URI",
+                ));
+            }
+            if config.mongo.database.trim().is_empty() {
+                return Err(String::from("mongo.database is empty"));
+            }
+            if config.mongo.artifacts.gridfs_threshold > MAX_INLINE_ARTIFACT_SIZE {
+                return Err(format!(
+                    "mongo.artifacts.gridfs_threshold must not be greater than {}",
+                    MAX_INLINE_ARTIFACT_SIZE
+                ));
+            }
+        }
+        if config.storage == StorageBackend::Sqlite {
+            if cfg!(not(feature = "sqlite")) {
+                return Err(String::from(
+                    "storage sqlite requires building with the sqlite feature",
+                ));
+            }
+            if config.sqlite.path.as_os_str().is_empty() {
+                return Err(String::from("sqlite.path is empty"));
+            }
+        }
+        if config.compiler.cargo.trim().is_empty() {
+            return Err(String::from("compiler.cargo is empty"));
+        }
+        if config.compiler.max_code_size == 0 {
+            return Err(String::from(
+                "compiler.max_code_size must be greater than 0",
+            ));
+        }
+        if config.compiler.allowed_features.is_empty() {
+            return Err(String::from("compiler.allowed_features is empty"));
+        }
+
+        if config.compiler.workspace.is_relative() {
+            let current_dir = env::current_dir()
+                .map_err(|err| format!("Error getting current directory: {}", err))?;
+            config.compiler.workspace = current_dir.join(&config.compiler.workspace);
+        }
+        if !config.compiler.workspace.is_dir() {
+            return Err(format!(
+                "compiler.workspace {} is not a directory",
+                config.compiler.workspace.display()
+            ));
+        }
+
+        config
+            .cors
+            .check()
+            .map_err(|err| format!("cors: {}", err))?;
+        if config.rate_limit.capacity == 0 || config.rate_limit.refill_per_minute == 0 {
+            return Err(String::from(
+                "rate_limit.capacity and rate_limit.refill_per_minute must be greater than 0",
+            ));
+        }
+        NetworkRegistry::new(config.networks.clone())
+            .map_err(|err| format!("networks: {}", err))?;
+
+        Ok(config)
+    }
+
+    // Fairing loading the configuration and adding it, and the session keys, to the managed state
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Configuration", |rocket| async move {
+            match AppConfig::from_figment(rocket.figment()) {
+                Ok(config) => {
+                    info!(target: "compiler", "Configuration loaded, compiler workspace {}", config.compiler.workspace.display());
+                    match SessionKeys::from_config(&config.auth) {
+                        Ok(session_keys) => Ok(rocket.manage(config).manage(session_keys)),
+                        Err(err) => {
+                            error!(target: "compiler", "Invalid configuration: {}", err);
+                            Err(rocket)
+                        }
+                    }
+                }
+                Err(err) => {
+                    error!(target: "compiler", "Invalid configuration: {}", err);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/config_tests.rs"]
+mod config_test;
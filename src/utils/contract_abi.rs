@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::ink_metadata::{
+    metadata_version, InkArg, InkEventArg, InkMetadata, TypeDef, TypeRegistry, TypeSpec,
+};
+
+// Interface of a contract, resolved from its ink metadata
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContractAbi {
+    pub name: String,
+    pub version: String,
+    pub language: String,
+    pub metadata_version: u64,
+    pub docs: String,
+    pub constructors: Vec<AbiConstructor>,
+    pub messages: Vec<AbiMessage>,
+    pub events: Vec<AbiEvent>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AbiConstructor {
+    pub label: String,
+    pub selector: String,
+    pub payable: bool,
+    pub default: bool,
+    pub args: Vec<AbiArg>,
+    pub return_type: Option<AbiType>,
+    pub docs: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AbiMessage {
+    pub label: String,
+    pub selector: String,
+    pub mutates: bool,
+    pub payable: bool,
+    pub default: bool,
+    pub args: Vec<AbiArg>,
+    pub return_type: Option<AbiType>,
+    pub docs: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AbiArg {
+    pub label: String,
+    #[serde(rename = "type")]
+    pub arg_type: AbiType,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AbiEvent {
+    pub label: String,
+    // Only written by ink 5
+    pub module: Option<String>,
+    pub signature_topic: Option<String>,
+    pub args: Vec<AbiEventArg>,
+    pub docs: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AbiEventArg {
+    pub label: String,
+    pub indexed: bool,
+    #[serde(rename = "type")]
+    pub arg_type: AbiType,
+    pub docs: String,
+}
+
+// Type of the registry, with the name resolved from it and the name used in the contract source
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AbiType {
+    pub id: u32,
+    pub name: String,
+    pub display_name: String,
+}
+
+impl ContractAbi {
+    // Resolves the interface of a contract from its ink 4 or ink 5 metadata
+    pub fn from_metadata(metadata: &Value) -> Result<Self, String> {
+        let ink_metadata = InkMetadata::from_value(metadata)?;
+        let registry = ink_metadata.registry();
+        let lang_error = ink_metadata.spec.lang_error.as_ref().map(|error| error.id);
+        let abi_type = |type_spec: &TypeSpec| abi_type(&registry, type_spec);
+        let return_type = |type_spec: &Option<TypeSpec>| {
+            type_spec
+                .as_ref()
+                .map(|type_spec| unwrap_lang_error(&registry, type_spec, lang_error))
+        };
+        let args = |args: &[InkArg]| {
+            args.iter()
+                .map(|arg| AbiArg {
+                    label: arg.label.clone(),
+                    arg_type: abi_type(&arg.type_spec),
+                })
+                .collect()
+        };
+        let event_args = |args: &[InkEventArg]| {
+            args.iter()
+                .map(|arg| AbiEventArg {
+                    label: arg.label.clone(),
+                    indexed: arg.indexed,
+                    arg_type: abi_type(&arg.type_spec),
+                    docs: docs(&arg.docs),
+                })
+                .collect()
+        };
+
+        let spec = &ink_metadata.spec;
+        Ok(ContractAbi {
+            name: ink_metadata.contract.name.clone(),
+            version: ink_metadata.contract.version.clone(),
+            language: ink_metadata.source.language.clone(),
+            metadata_version: metadata_version(metadata).unwrap_or_default(),
+            docs: docs(&spec.docs),
+            constructors: spec
+                .constructors
+                .iter()
+                .map(|constructor| AbiConstructor {
+                    label: constructor.label.clone(),
+                    selector: constructor.selector.clone(),
+                    payable: constructor.payable,
+                    default: constructor.default,
+                    args: args(&constructor.args),
+                    return_type: return_type(&constructor.return_type),
+                    docs: docs(&constructor.docs),
+                })
+                .collect(),
+            messages: spec
+                .messages
+                .iter()
+                .map(|message| AbiMessage {
+                    label: message.label.clone(),
+                    selector: message.selector.clone(),
+                    mutates: message.mutates,
+                    payable: message.payable,
+                    default: message.default,
+                    args: args(&message.args),
+                    return_type: return_type(&message.return_type),
+                    docs: docs(&message.docs),
+                })
+                .collect(),
+            events: spec
+                .events
+                .iter()
+                .map(|event| AbiEvent {
+                    label: event.label.clone(),
+                    module: event.module.clone(),
+                    signature_topic: event.signature_topic.clone(),
+                    args: event_args(&event.args),
+                    docs: docs(&event.docs),
+                })
+                .collect(),
+        })
+    }
+}
+
+fn abi_type(registry: &TypeRegistry, type_spec: &TypeSpec) -> AbiType {
+    let name = registry.type_name(type_spec.id);
+    let display_name = match type_spec.display_name.last() {
+        Some(display_name) => display_name.clone(),
+        None => name.clone(),
+    };
+    AbiType {
+        id: type_spec.id,
+        name,
+        display_name,
+    }
+}
+
+// ink wraps the returned values in a Result with the language error, the value type is returned
+fn unwrap_lang_error(
+    registry: &TypeRegistry,
+    type_spec: &TypeSpec,
+    lang_error: Option<u32>,
+) -> AbiType {
+    let ok_type = registry.get(type_spec.id).ok().and_then(|info| {
+        let TypeDef::Variant { variants } = &info.def else {
+            return None;
+        };
+        if info.path != ["Result"] {
+            return None;
+        }
+        let field_type = |name: &str| {
+            variants
+                .iter()
+                .find(|variant| variant.name == name)
+                .and_then(|variant| variant.fields.first())
+                .map(|field| field.id)
+        };
+        match field_type("Err") == lang_error && lang_error.is_some() {
+            true => field_type("Ok"),
+            false => None,
+        }
+    });
+
+    match ok_type {
+        Some(id) => abi_type(
+            registry,
+            &TypeSpec {
+                id,
+                display_name: Vec::new(),
+            },
+        ),
+        None => abi_type(registry, type_spec),
+    }
+}
+
+// The doc comment lines, without the space after the slashes
+fn docs(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/contract_abi_tests.rs"]
+mod contract_abi_test;
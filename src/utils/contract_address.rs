@@ -0,0 +1,39 @@
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+
+use super::scale_codec::encode_compact;
+
+// Prefix of the data hashed by pallet-contracts to derive the address of a new contract
+pub const CONTRACT_ADDRESS_PREFIX: &[u8] = b"contract_addr_v1";
+
+type Blake2b256 = Blake2b<U32>;
+
+// Hash under which pallet-contracts stores the wasm code
+pub fn code_hash(wasm: &[u8]) -> [u8; 32] {
+    Blake2b256::digest(wasm).into()
+}
+
+// Address pallet-contracts gives to a contract instantiated by the deployer, the hash of the
+// SCALE encoded (prefix, deployer, code hash, input data, salt), where the byte slices are
+// prefixed by their compact length
+pub fn derive_contract_address(
+    deployer: &[u8; 32],
+    code_hash: &[u8; 32],
+    input_data: &[u8],
+    salt: &[u8],
+) -> [u8; 32] {
+    let mut entropy = Vec::with_capacity(96 + input_data.len() + salt.len());
+    entropy.extend_from_slice(CONTRACT_ADDRESS_PREFIX);
+    entropy.extend_from_slice(deployer);
+    entropy.extend_from_slice(code_hash);
+    for bytes in [input_data, salt] {
+        encode_compact(bytes.len() as u128, &mut entropy);
+        entropy.extend_from_slice(bytes);
+    }
+
+    Blake2b256::digest(&entropy).into()
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/contract_address_tests.rs"]
+mod contract_address_test;
@@ -0,0 +1,369 @@
+use serde::{Deserialize, Serialize};
+
+// lib.rs templates of the standards, rendered with the options of the spec
+const PSP22_TEMPLATE: &str = include_str!("../../templates/psp22.rs");
+const PSP34_TEMPLATE: &str = include_str!("../../templates/psp34.rs");
+const PSP37_TEMPLATE: &str = include_str!("../../templates/psp37.rs");
+
+// Extensions the templates can add to a standard
+pub const SUPPORTED_EXTENSIONS: [&str; 3] = ["pausable", "ownable", "access-control"];
+
+// Keywords and reserved words of Rust, the module names are lowercase
+const RUST_KEYWORDS: [&str; 51] = [
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try", "gen",
+];
+
+pub const MAX_NAME_LENGTH: usize = 32;
+pub const MAX_SYMBOL_LENGTH: usize = 12;
+pub const DEFAULT_DECIMALS: u8 = 18;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Standard {
+    Psp22,
+    Psp34,
+    Psp37,
+}
+
+// Account receiving the ownership, or the admin role, and the initial supply
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OwnerModel {
+    // The account instantiating the contract
+    #[default]
+    Caller,
+    // An account given to the constructor
+    Argument,
+}
+
+// Options of a contract generated by the service
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContractSpec {
+    pub standard: Standard,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    pub name: String,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    // Only used by psp22
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    // Decimal string, the supply does not fit in a JSON number
+    #[serde(default)]
+    pub initial_supply: Option<String>,
+    #[serde(default)]
+    pub owner: OwnerModel,
+}
+
+impl Standard {
+    pub fn feature(&self) -> &'static str {
+        match self {
+            Standard::Psp22 => "psp22",
+            Standard::Psp34 => "psp34",
+            Standard::Psp37 => "psp37",
+        }
+    }
+
+    fn template(&self) -> &'static str {
+        match self {
+            Standard::Psp22 => PSP22_TEMPLATE,
+            Standard::Psp34 => PSP34_TEMPLATE,
+            Standard::Psp37 => PSP37_TEMPLATE,
+        }
+    }
+
+    fn error_type(&self) -> &'static str {
+        match self {
+            Standard::Psp22 => "PSP22Error",
+            Standard::Psp34 => "PSP34Error",
+            Standard::Psp37 => "PSP37Error",
+        }
+    }
+
+    // Last argument of the transfer hook, the tokens being transferred
+    fn transfer_arg(&self) -> &'static str {
+        match self {
+            Standard::Psp22 => "_amount: &Balance",
+            Standard::Psp34 => "_id: &Id",
+            Standard::Psp37 => "_ids: &[(Id, Balance)]",
+        }
+    }
+}
+
+impl ContractSpec {
+    // Features of the compilation, the standard followed by its extensions
+    pub fn features(&self) -> Vec<String> {
+        let mut features = vec![self.standard.feature().to_string()];
+        for extension in &self.extensions {
+            if !features.contains(extension) {
+                features.push(extension.clone());
+            }
+        }
+        features
+    }
+
+    fn has_extension(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|e| e == extension)
+    }
+}
+
+// Renders the lib.rs of the contract described by the spec
+pub fn generate_contract(spec: &ContractSpec) -> Result<String, String> {
+    check_spec(spec)?;
+    let standard = spec.standard;
+    let ownable = spec.has_extension("ownable");
+    let access_control = spec.has_extension("access-control");
+    let owner = match spec.owner {
+        OwnerModel::Caller => "_instance.env().caller()",
+        OwnerModel::Argument => "owner",
+    };
+
+    let mut imports = Vec::new();
+    let mut storage = Vec::new();
+    let mut impls = Vec::new();
+    let mut init = Vec::new();
+    let mut messages = Vec::new();
+    let mut storage_field = |field: &str| {
+        storage.push(String::from("#[storage_field]"));
+        storage.push(field.to_string());
+    };
+
+    if ownable {
+        imports.push(String::from("use openbrush::contracts::ownable::*;"));
+        storage_field("ownable: ownable::Data,");
+        impls.push(String::from("impl Ownable for Contract {}"));
+        init.push(format!("_instance._init_with_owner({});", owner));
+    }
+    if access_control {
+        imports.push(String::from("use openbrush::contracts::access_control::*;"));
+        storage_field("access: access_control::Data,");
+        impls.push(String::from("impl AccessControl for Contract {}"));
+        init.push(format!("_instance._init_with_admin({});", owner));
+    }
+    if spec.has_extension("pausable") {
+        imports.push(String::from("use openbrush::contracts::pausable::*;"));
+        storage_field("pause: pausable::Data,");
+        impls.push(String::from("impl Pausable for Contract {}"));
+        impls.push(String::new());
+        impls.extend(lines(&format!(
+            "impl Transfer for Contract {{
+    #[openbrush::modifiers(when_not_paused)]
+    fn _before_token_transfer(
+        &mut self,
+        _from: Option<&AccountId>,
+        _to: Option<&AccountId>,
+        {},
+    ) -> Result<(), {}> {{
+        Ok(())
+    }}
+}}",
+            standard.transfer_arg(),
+            standard.error_type()
+        )));
+        // The check_spec ensures someone can switch the state
+        let guard = match ownable {
+            true => "only_owner",
+            false => "only_role(DEFAULT_ADMIN_ROLE)",
+        };
+        messages.push(String::new());
+        messages.extend(lines(&format!(
+            "#[ink(message)]
+#[openbrush::modifiers({})]
+pub fn change_state(&mut self) -> Result<(), {}> {{
+    if self.paused() {{
+        self._unpause()
+    }} else {{
+        self._pause()
+    }}
+}}",
+            guard,
+            standard.error_type()
+        )));
+    }
+
+    let symbol = match &spec.symbol {
+        Some(symbol) => format!("Some(String::from(\"{}\"))", symbol),
+        None => String::from("None"),
+    };
+    let mut attributes = vec![format!(
+        "_instance._set_attribute(collection_id.clone(), String::from(\"name\"), String::from(\"{}\"));",
+        spec.name
+    )];
+    if let Some(symbol) = &spec.symbol {
+        attributes.push(format!(
+            "_instance._set_attribute(collection_id, String::from(\"symbol\"), String::from(\"{}\"));",
+            symbol
+        ));
+    }
+    let constructor_args = match spec.owner {
+        OwnerModel::Caller => "",
+        OwnerModel::Argument => "owner: AccountId",
+    };
+
+    let inline = [
+        ("module", module_name(&spec.name)),
+        ("name", spec.name.clone()),
+        ("symbol", symbol),
+        (
+            "decimals",
+            spec.decimals.unwrap_or(DEFAULT_DECIMALS).to_string(),
+        ),
+        (
+            "initial_supply",
+            spec.initial_supply
+                .clone()
+                .unwrap_or_else(|| String::from("0")),
+        ),
+        ("owner", owner.to_string()),
+        ("constructor_args", constructor_args.to_string()),
+    ];
+    let blocks = [
+        ("imports", imports),
+        ("storage", storage),
+        ("impls", impls),
+        ("attributes", attributes),
+        ("init", init),
+        ("messages", messages),
+    ];
+    Ok(render(standard.template(), &inline, &blocks))
+}
+
+// Checks the options are supported by the standard and can be written in the source
+fn check_spec(spec: &ContractSpec) -> Result<(), String> {
+    let valid_name = spec.name.len() <= MAX_NAME_LENGTH
+        && spec.name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && spec
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '_');
+    if !valid_name {
+        return Err(format!(
+            "The name must start with a letter and have up to {} letters, digits, spaces, - or _.",
+            MAX_NAME_LENGTH
+        ));
+    }
+
+    if let Some(symbol) = &spec.symbol {
+        if symbol.is_empty()
+            || symbol.len() > MAX_SYMBOL_LENGTH
+            || !symbol.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            return Err(format!(
+                "The symbol must have 1 to {} letters or digits.",
+                MAX_SYMBOL_LENGTH
+            ));
+        }
+    }
+
+    let unused = |option: &str, standards: &str| {
+        format!(
+            "{} is not used by {}, only by {}.",
+            option,
+            spec.standard.feature(),
+            standards
+        )
+    };
+    if spec.standard == Standard::Psp37 && spec.symbol.is_some() {
+        return Err(unused("symbol", "psp22 and psp34"));
+    }
+    if spec.standard != Standard::Psp22 && spec.decimals.is_some() {
+        return Err(unused("decimals", "psp22"));
+    }
+    if spec.standard != Standard::Psp22 && spec.initial_supply.is_some() {
+        return Err(unused("initial_supply", "psp22"));
+    }
+    if let Some(initial_supply) = &spec.initial_supply {
+        if !initial_supply.bytes().all(|c| c.is_ascii_digit())
+            || initial_supply.parse::<u128>().is_err()
+        {
+            return Err(String::from(
+                "The initial_supply must be a decimal integer that fits in a u128.",
+            ));
+        }
+    }
+
+    if let Some(extension) = spec
+        .extensions
+        .iter()
+        .find(|extension| !SUPPORTED_EXTENSIONS.contains(&extension.as_str()))
+    {
+        return Err(format!(
+            "Extension {} is not supported, the supported extensions are {}.",
+            extension,
+            SUPPORTED_EXTENSIONS.join(", ")
+        ));
+    }
+    let has_owner = spec.has_extension("ownable") || spec.has_extension("access-control");
+    if spec.has_extension("pausable") && !has_owner {
+        return Err(String::from(
+            "The pausable extension requires the ownable or access-control extension to switch the state.",
+        ));
+    }
+    if spec.owner == OwnerModel::Argument && !has_owner {
+        return Err(String::from(
+            "The argument owner model requires the ownable or access-control extension.",
+        ));
+    }
+
+    Ok(())
+}
+
+// Name of the contract module, like my_token for "My Token"
+// Keywords can't name a module, "Type" is rendered as type_contract
+fn module_name(name: &str) -> String {
+    let module = name
+        .to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("_");
+    match RUST_KEYWORDS.contains(&module.as_str()) {
+        true => format!("{}_contract", module),
+        false => module,
+    }
+}
+
+fn lines(snippet: &str) -> Vec<String> {
+    snippet.lines().map(String::from).collect()
+}
+
+// Replaces the {{placeholders}}, a block placeholder alone on its line is replaced by its lines
+// with the indentation of the placeholder, an empty block removes the line
+fn render(template: &str, inline: &[(&str, String)], blocks: &[(&str, Vec<String>)]) -> String {
+    let mut rendered = String::new();
+    for line in template.lines() {
+        let trimmed = line.trim_start();
+        let block = blocks
+            .iter()
+            .find(|(name, _)| trimmed == format!("{{{{{}}}}}", name));
+        match block {
+            Some((_, block_lines)) => {
+                let indentation = &line[..line.len() - trimmed.len()];
+                for block_line in block_lines {
+                    if !block_line.is_empty() {
+                        rendered.push_str(indentation);
+                        rendered.push_str(block_line);
+                    }
+                    rendered.push('\n');
+                }
+            }
+            None => {
+                let mut line = line.to_string();
+                for (name, value) in inline {
+                    line = line.replace(&format!("{{{{{}}}}}", name), value);
+                }
+                rendered.push_str(&line);
+                rendered.push('\n');
+            }
+        }
+    }
+    rendered
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/contract_generator_tests.rs"]
+mod contract_generator_test;
@@ -1,25 +1,147 @@
+use crate::utils::config::AppConfig;
+use log::{error, info};
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::http::Header;
-use rocket::{Request, Response};
+use rocket::{Build, Request, Response, Rocket};
+use serde::{Deserialize, Serialize};
 
-pub struct CORS;
+// CORS policy, loaded from the configuration
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CorsConfig {
+    // Origins allowed to call the API, "*" allows any origin
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    // Headers allowed in the requests, "*" allows the ones requested by the browser
+    pub allowed_headers: Vec<String>,
+    #[serde(default)]
+    pub exposed_headers: Vec<String>,
+    #[serde(default)]
+    pub allow_credentials: bool,
+    // Seconds the browser can cache the preflight response
+    pub max_age: Option<u64>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            allowed_origins: vec![String::from("*")],
+            allowed_methods: ["GET", "POST", "PATCH", "DELETE", "OPTIONS"]
+                .iter()
+                .map(|method| method.to_string())
+                .collect(),
+            allowed_headers: vec![String::from("*")],
+            exposed_headers: Vec::new(),
+            allow_credentials: false,
+            max_age: None,
+        }
+    }
+}
+
+impl CorsConfig {
+    // Credentials can not be allowed for every origin, any site could make authenticated requests
+    pub fn check(&self) -> Result<(), String> {
+        if self.allow_credentials && self.allowed_origins.iter().any(|origin| origin == "*") {
+            return Err(String::from(
+                "allow_credentials requires a list of allowed origins, not \"*\"",
+            ));
+        }
+        Ok(())
+    }
+
+    // Checks if the origin is allowed, origins are compared ignoring case and trailing slashes
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        let origin = origin.trim_end_matches('/');
+        self.allowed_origins.iter().any(|allowed| {
+            allowed == "*" || allowed.trim_end_matches('/').eq_ignore_ascii_case(origin)
+        })
+    }
+
+    // Headers allowed for a request asking for the given headers in its preflight
+    pub fn allowed_headers_for(&self, requested_headers: Option<&str>) -> Option<String> {
+        if self.allowed_headers.iter().any(|header| header == "*") {
+            // Browsers do not accept the wildcard in credentialed requests, the requested headers are echoed
+            return match requested_headers {
+                Some(requested_headers) => Some(requested_headers.to_string()),
+                None if self.allow_credentials => None,
+                None => Some(String::from("*")),
+            };
+        }
+
+        if self.allowed_headers.is_empty() {
+            None
+        } else {
+            Some(self.allowed_headers.join(", "))
+        }
+    }
+}
+
+// Fairing adding the CORS headers of the configured policy to every response
+pub struct Cors;
 
 #[rocket::async_trait]
-impl Fairing for CORS {
+impl Fairing for Cors {
     fn info(&self) -> Info {
         Info {
             name: "CORS Headers",
-            kind: Kind::Response,
+            kind: Kind::Ignite | Kind::Response,
         }
     }
 
-    async fn on_response<'r>(&self, _request: &'r Request<'_>, response: &mut Response<'r>) {
-        response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        // Validated by AppConfig::from_figment
+        let config = match rocket.state::<AppConfig>() {
+            Some(app_config) => app_config.cors.clone(),
+            None => {
+                error!(target: "compiler", "CORS policy loaded before the configuration");
+                return Err(rocket);
+            }
+        };
+
+        info!(target: "compiler", "CORS policy loaded: {:?}", config);
+        Ok(rocket.manage(config))
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let config = match request.rocket().state::<CorsConfig>() {
+            Some(config) => config,
+            None => return,
+        };
+
+        // The response depends on the origin, caches must not share it between origins
+        response.adjoin_header(Header::new("Vary", "Origin"));
+
+        let origin = match request.headers().get_one("Origin") {
+            Some(origin) if config.allows_origin(origin) => origin,
+            _ => return,
+        };
+
+        response.set_header(Header::new(
+            "Access-Control-Allow-Origin",
+            origin.to_string(),
+        ));
         response.set_header(Header::new(
             "Access-Control-Allow-Methods",
-            "POST, GET, PATCH, OPTIONS",
+            config.allowed_methods.join(", "),
         ));
-        response.set_header(Header::new("Access-Control-Allow-Headers", "*"));
-        response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        let requested_headers = request.headers().get_one("Access-Control-Request-Headers");
+        if let Some(allowed_headers) = config.allowed_headers_for(requested_headers) {
+            response.set_header(Header::new("Access-Control-Allow-Headers", allowed_headers));
+        }
+        if !config.exposed_headers.is_empty() {
+            response.set_header(Header::new(
+                "Access-Control-Expose-Headers",
+                config.exposed_headers.join(", "),
+            ));
+        }
+        if config.allow_credentials {
+            response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        }
+        if let Some(max_age) = config.max_age {
+            response.set_header(Header::new("Access-Control-Max-Age", max_age.to_string()));
+        }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/cors_tests.rs"]
+mod cors_test;
@@ -0,0 +1,368 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Versions of the ink metadata format accepted, ink 4 and ink 5
+pub const SUPPORTED_METADATA_VERSIONS: [u64; 2] = [4, 5];
+
+// Parses the metadata generated by cargo-contract and checks it follows the ink metadata schema
+pub fn parse_metadata(metadata: &str) -> Result<Value, String> {
+    let metadata: Value =
+        serde_json::from_str(metadata).map_err(|err| format!("Invalid ink metadata: {}", err))?;
+    validate_metadata(&metadata)?;
+    Ok(metadata)
+}
+
+// Version of the metadata format, ink 4 writes it as a string and ink 5 as a number
+pub fn metadata_version(metadata: &Value) -> Option<u64> {
+    match metadata.get("version")? {
+        Value::String(version) => version.parse().ok(),
+        version => version.as_u64(),
+    }
+}
+
+// Checks the sections of the metadata read by the service are present and well formed
+pub fn validate_metadata(metadata: &Value) -> Result<(), String> {
+    if !metadata.is_object() {
+        return Err(invalid("the metadata is not an object"));
+    }
+
+    match metadata_version(metadata) {
+        Some(version) if SUPPORTED_METADATA_VERSIONS.contains(&version) => (),
+        Some(version) => return Err(invalid(&format!("unsupported version {}", version))),
+        None => return Err(invalid("missing version")),
+    }
+
+    for field in ["hash", "language", "compiler"] {
+        require_string(metadata, &["source", field])?;
+    }
+    require_string(metadata, &["contract", "name"])?;
+    require_string(metadata, &["contract", "version"])?;
+    require(
+        metadata,
+        &["contract", "authors"],
+        Value::is_array,
+        "an array",
+    )?;
+    require(metadata, &["storage"], Value::is_object, "an object")?;
+
+    for section in ["constructors", "messages"] {
+        let entries = require(metadata, &["spec", section], Value::is_array, "an array")?;
+        for (index, entry) in entries.as_array().into_iter().flatten().enumerate() {
+            require_string(entry, &["label"])
+                .and_then(|_| require_string(entry, &["selector"]))
+                .and_then(|_| require(entry, &["args"], Value::is_array, "an array"))
+                .map_err(|err| format!("{} in spec.{}[{}]", err, section, index))?;
+        }
+    }
+    require(metadata, &["spec", "events"], Value::is_array, "an array")?;
+
+    let types = require(metadata, &["types"], Value::is_array, "an array")?;
+    for (index, registry_type) in types.as_array().into_iter().flatten().enumerate() {
+        require(registry_type, &["id"], Value::is_u64, "a number")
+            .and_then(|_| require(registry_type, &["type"], Value::is_object, "an object"))
+            .map_err(|err| format!("{} in types[{}]", err, index))?;
+    }
+
+    Ok(())
+}
+
+// Returns the value at the path when it has the expected kind
+fn require<'a>(
+    value: &'a Value,
+    path: &[&str],
+    is_kind: fn(&Value) -> bool,
+    kind: &str,
+) -> Result<&'a Value, String> {
+    let found = path
+        .iter()
+        .try_fold(value, |value, key| value.get(key))
+        .ok_or_else(|| invalid(&format!("missing {}", path.join("."))))?;
+
+    match is_kind(found) {
+        true => Ok(found),
+        false => Err(invalid(&format!("{} is not {}", path.join("."), kind))),
+    }
+}
+
+fn require_string<'a>(value: &'a Value, path: &[&str]) -> Result<&'a Value, String> {
+    require(value, path, Value::is_string, "a string")
+}
+
+fn invalid(reason: &str) -> String {
+    format!("Invalid ink metadata: {}", reason)
+}
+
+// Typed view of the sections of the ink metadata used by the service, shared by ink 4 and ink 5
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InkMetadata {
+    pub source: InkSource,
+    pub contract: InkContract,
+    pub spec: InkSpec,
+    pub types: Vec<RegistryType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InkSource {
+    pub hash: String,
+    pub language: String,
+    pub compiler: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InkContract {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub authors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InkSpec {
+    pub constructors: Vec<InkConstructor>,
+    pub messages: Vec<InkMessage>,
+    pub events: Vec<InkEvent>,
+    #[serde(default)]
+    pub docs: Vec<String>,
+    #[serde(default, rename = "lang_error")]
+    pub lang_error: Option<TypeSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InkConstructor {
+    pub label: String,
+    pub selector: String,
+    #[serde(default)]
+    pub payable: bool,
+    #[serde(default)]
+    pub default: bool,
+    pub args: Vec<InkArg>,
+    #[serde(default)]
+    pub return_type: Option<TypeSpec>,
+    #[serde(default)]
+    pub docs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InkMessage {
+    pub label: String,
+    pub selector: String,
+    #[serde(default)]
+    pub mutates: bool,
+    #[serde(default)]
+    pub payable: bool,
+    #[serde(default)]
+    pub default: bool,
+    pub args: Vec<InkArg>,
+    #[serde(default)]
+    pub return_type: Option<TypeSpec>,
+    #[serde(default)]
+    pub docs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InkArg {
+    pub label: String,
+    #[serde(rename = "type")]
+    pub type_spec: TypeSpec,
+}
+
+// Reference to a type of the registry, with the name used in the contract source
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeSpec {
+    #[serde(rename = "type")]
+    pub id: u32,
+    #[serde(default)]
+    pub display_name: Vec<String>,
+}
+
+// ink 5 adds the module and the signature topic of the events
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InkEvent {
+    pub label: String,
+    pub args: Vec<InkEventArg>,
+    #[serde(default)]
+    pub docs: Vec<String>,
+    #[serde(default)]
+    pub module: Option<String>,
+    #[serde(default)]
+    pub signature_topic: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InkEventArg {
+    pub label: String,
+    #[serde(default)]
+    pub indexed: bool,
+    #[serde(rename = "type")]
+    pub type_spec: TypeSpec,
+    #[serde(default)]
+    pub docs: Vec<String>,
+}
+
+// Entry of the scale-info type registry
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RegistryType {
+    pub id: u32,
+    #[serde(rename = "type")]
+    pub info: TypeInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TypeInfo {
+    #[serde(default)]
+    pub path: Vec<String>,
+    #[serde(default)]
+    pub params: Vec<TypeParam>,
+    pub def: TypeDef,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TypeParam {
+    pub name: String,
+    #[serde(default, rename = "type")]
+    pub id: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TypeDef {
+    Composite {
+        #[serde(default)]
+        fields: Vec<TypeField>,
+    },
+    Variant {
+        #[serde(default)]
+        variants: Vec<TypeVariant>,
+    },
+    Sequence {
+        #[serde(rename = "type")]
+        id: u32,
+    },
+    Array {
+        len: u32,
+        #[serde(rename = "type")]
+        id: u32,
+    },
+    Tuple(Vec<u32>),
+    Primitive(String),
+    Compact {
+        #[serde(rename = "type")]
+        id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    BitSequence {
+        bit_store_type: u32,
+        bit_order_type: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeField {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub id: u32,
+    #[serde(default)]
+    pub type_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TypeVariant {
+    pub name: String,
+    pub index: u8,
+    #[serde(default)]
+    pub fields: Vec<TypeField>,
+    #[serde(default)]
+    pub docs: Vec<String>,
+}
+
+// Nesting resolved when naming a type, the registry can contain recursive types
+const MAX_TYPE_NAME_DEPTH: usize = 16;
+
+// Types of the registry indexed by their id
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TypeRegistry(HashMap<u32, TypeInfo>);
+
+impl InkMetadata {
+    // Reads the typed view of a validated metadata
+    pub fn from_value(metadata: &Value) -> Result<Self, String> {
+        validate_metadata(metadata)?;
+        serde_json::from_value(metadata.clone())
+            .map_err(|err| format!("Invalid ink metadata: {}", err))
+    }
+
+    pub fn registry(&self) -> TypeRegistry {
+        TypeRegistry(
+            self.types
+                .iter()
+                .map(|registry_type| (registry_type.id, registry_type.info.clone()))
+                .collect(),
+        )
+    }
+}
+
+impl TypeRegistry {
+    pub fn get(&self, id: u32) -> Result<&TypeInfo, String> {
+        self.0
+            .get(&id)
+            .ok_or_else(|| format!("Type {} is not in the registry", id))
+    }
+
+    // Name of the type as written in Rust, like Option<AccountId> or [u8; 32]
+    pub fn type_name(&self, id: u32) -> String {
+        self.type_name_at_depth(id, 0)
+    }
+
+    fn type_name_at_depth(&self, id: u32, depth: usize) -> String {
+        let Ok(info) = self.get(id) else {
+            return format!("Unknown{}", id);
+        };
+        if depth > MAX_TYPE_NAME_DEPTH {
+            return String::from("..");
+        }
+        let name = |id: u32| self.type_name_at_depth(id, depth + 1);
+
+        match &info.def {
+            TypeDef::Primitive(primitive) if primitive == "str" => String::from("String"),
+            TypeDef::Primitive(primitive) => primitive.clone(),
+            TypeDef::Sequence { id } => format!("Vec<{}>", name(*id)),
+            TypeDef::Array { len, id } => format!("[{}; {}]", name(*id), len),
+            TypeDef::Tuple(ids) if ids.len() == 1 => format!("({},)", name(ids[0])),
+            TypeDef::Tuple(ids) => {
+                let names: Vec<String> = ids.iter().map(|id| name(*id)).collect();
+                format!("({})", names.join(", "))
+            }
+            TypeDef::Compact { id } => format!("Compact<{}>", name(*id)),
+            TypeDef::BitSequence { .. } => String::from("BitVec"),
+            TypeDef::Composite { .. } | TypeDef::Variant { .. } => {
+                let base = info
+                    .path
+                    .last()
+                    .cloned()
+                    .unwrap_or_else(|| format!("Type{}", id));
+                let params: Vec<String> = info
+                    .params
+                    .iter()
+                    .map(|param| match param.id {
+                        Some(id) => name(id),
+                        None => param.name.clone(),
+                    })
+                    .collect();
+                match params.is_empty() {
+                    true => base,
+                    false => format!("{}<{}>", base, params.join(", ")),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/ink_metadata_tests.rs"]
+mod ink_metadata_test;
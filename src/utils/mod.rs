@@ -1,4 +1,21 @@
+pub mod address_signature;
+pub mod api_keys;
+pub mod artifacts;
 pub mod compilation_queue;
 pub mod compiler;
-pub mod sanity_check;
+pub mod config;
+pub mod contract_abi;
+pub mod contract_address;
+pub mod contract_generator;
 pub mod cors;
+pub mod ink_metadata;
+pub mod network_registry;
+pub mod rate_limiter;
+pub mod request_id;
+pub mod sanity_check;
+pub mod scale_codec;
+pub mod session;
+pub mod size_report;
+pub mod storage_layout;
+pub mod wasm_text;
+pub mod wasm_validation;
@@ -0,0 +1,111 @@
+use crate::utils::config::AppConfig;
+use log::{error, info};
+use rocket::fairing::AdHoc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// Network where contracts can be deployed, loaded from the configuration
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Network {
+    // Canonical id stored in the deployments
+    pub id: String,
+    pub name: String,
+    // Other spellings accepted for the network, like "Rococo" or "rococo-testnet"
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub ss58_prefix: u16,
+    // Maximum size in bytes of the wasm code accepted by the chain
+    pub max_code_size: usize,
+    // Toolchains whose contracts the chain accepts, compiling for the network requires the one
+    // of the compiler, any toolchain is accepted when the list is empty
+    #[serde(default)]
+    pub toolchain_profiles: Vec<String>,
+    #[serde(default)]
+    pub explorer: ExplorerUrls,
+}
+
+// Block explorer URL templates, `{address}` and `{hash}` are replaced by the client
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct ExplorerUrls {
+    pub account: Option<String>,
+    pub contract: Option<String>,
+    pub transaction: Option<String>,
+}
+
+// Registry of the supported networks
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkRegistry {
+    pub networks: Vec<Network>,
+}
+
+impl Network {
+    // Checks if the given name is the id, the display name or an alias of the network
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.id.eq_ignore_ascii_case(name)
+            || self.name.eq_ignore_ascii_case(name)
+            || self
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(name))
+    }
+}
+
+impl NetworkRegistry {
+    // Creates the registry checking that no name is used by two networks
+    pub fn new(networks: Vec<Network>) -> Result<Self, String> {
+        if networks.is_empty() {
+            return Err("The network registry is empty".to_string());
+        }
+
+        let mut names = HashSet::new();
+        for network in &networks {
+            // The id and the name of a network are usually the same word
+            let spellings: HashSet<String> = [&network.id, &network.name]
+                .into_iter()
+                .chain(network.aliases.iter())
+                .map(|spelling| spelling.trim().to_lowercase())
+                .collect();
+            for spelling in spellings {
+                if spelling.is_empty() || !names.insert(spelling.clone()) {
+                    return Err(format!(
+                        "Network name {:?} is empty or used more than once",
+                        spelling
+                    ));
+                }
+            }
+        }
+
+        Ok(NetworkRegistry { networks })
+    }
+
+    // Finds a network by its id, display name or alias
+    pub fn resolve(&self, name: &str) -> Option<&Network> {
+        self.networks.iter().find(|network| network.matches(name))
+    }
+
+    // Fairing building the registry from the networks of AppConfig and adding it to the managed state
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Network Registry", |rocket| async move {
+            let networks = match rocket.state::<AppConfig>() {
+                Some(config) => NetworkRegistry::new(config.networks.clone()),
+                None => Err(String::from("loaded before the configuration")),
+            };
+
+            match networks {
+                Ok(registry) => {
+                    info!(target: "compiler", "Network registry loaded with {} networks", registry.networks.len());
+                    Ok(rocket.manage(registry))
+                }
+                Err(err) => {
+                    error!(target: "compiler", "Error loading the network registry: {}", err);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/network_registry_tests.rs"]
+mod network_registry_test;
@@ -0,0 +1,437 @@
+use crate::models::api_models::ServerResponse;
+use crate::repository::Database;
+use crate::utils::address_signature::unix_timestamp;
+use crate::utils::api_keys::Caller;
+use crate::utils::config::AppConfig;
+use crate::utils::session::SessionKeys;
+use log::{error, info};
+use mongodb::bson::DateTime;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Method, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::status::Custom;
+use rocket::route::{self, Handler};
+use rocket::serde::json::Json;
+use rocket::{Build, Data, Request, Response, Rocket, Route};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Headers describing the token bucket of the request
+pub const RATE_LIMIT_LIMIT_HEADER: &str = "X-RateLimit-Limit";
+pub const RATE_LIMIT_REMAINING_HEADER: &str = "X-RateLimit-Remaining";
+pub const RATE_LIMIT_RESET_HEADER: &str = "X-RateLimit-Reset";
+
+// Headers describing the daily compile quota of the request
+pub const QUOTA_LIMIT_HEADER: &str = "X-Quota-Limit";
+pub const QUOTA_REMAINING_HEADER: &str = "X-Quota-Remaining";
+pub const QUOTA_RESET_HEADER: &str = "X-Quota-Reset";
+
+const SECONDS_PER_DAY: u64 = 86400;
+
+// Full buckets are dropped when the map reaches this size, or when the last sweep is this old
+const SWEEP_THRESHOLD: usize = 10_000;
+const SWEEP_INTERVAL_SECS: f64 = 60.0;
+
+// Rate limit settings, loaded from the configuration
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    // Burst of requests allowed for an IP or an address
+    // API keys are also limited by their own rate_limit_per_minute, see api_keys::ApiKeyRateLimiter
+    pub capacity: u32,
+    pub refill_per_minute: u32,
+    // Compilations allowed per day for an address or an API key
+    pub daily_compile_quota: u32,
+    pub api_key_daily_compile_quota: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            capacity: 30,
+            refill_per_minute: 30,
+            daily_compile_quota: 50,
+            api_key_daily_compile_quota: 1000,
+        }
+    }
+}
+
+// State of a bucket or a quota, returned to the client in the response headers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimitState {
+    pub limit: u32,
+    pub remaining: u32,
+    // Seconds until the limit is fully restored
+    pub reset: u64,
+}
+
+impl LimitState {
+    // Daily quota state after `used` compilations, it resets at midnight UTC
+    pub fn daily_quota(limit: u32, used: u32, now: u64) -> Self {
+        LimitState {
+            limit,
+            remaining: limit.saturating_sub(used),
+            reset: SECONDS_PER_DAY - now % SECONDS_PER_DAY,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, now: f64) {
+        self.tokens =
+            (self.tokens + (now - self.updated_at) * self.refill_per_sec).min(self.capacity);
+        self.updated_at = now;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    entries: HashMap<String, TokenBucket>,
+    swept_at: f64,
+}
+
+impl Buckets {
+    // Full buckets are equivalent to missing ones, they are dropped to keep the map small
+    fn sweep(&mut self, now: f64) {
+        if self.entries.len() < SWEEP_THRESHOLD && now - self.swept_at < SWEEP_INTERVAL_SECS {
+            return;
+        }
+        self.entries.retain(|_, bucket| {
+            bucket.refill(now);
+            bucket.tokens < bucket.capacity
+        });
+        self.swept_at = now;
+    }
+}
+
+// Token buckets of the clients, refilled continuously up to their capacity
+pub struct RateLimiter {
+    pub config: RateLimitConfig,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    // Takes a token from the bucket of the key, failing when it is empty
+    pub fn acquire(
+        &self,
+        key: &str,
+        capacity: u32,
+        refill_per_minute: u32,
+        now: f64,
+    ) -> Result<LimitState, LimitState> {
+        let capacity = f64::from(capacity);
+        let refill_per_sec = f64::from(refill_per_minute.max(1)) / 60.0;
+
+        let mut buckets = match self.buckets.lock() {
+            Ok(buckets) => buckets,
+            Err(_) => {
+                error!(target: "compiler", "Error locking rate limiter");
+                return Err(LimitState {
+                    limit: capacity as u32,
+                    remaining: 0,
+                    reset: 0,
+                });
+            }
+        };
+
+        buckets.sweep(now);
+
+        let bucket = buckets
+            .entries
+            .entry(key.to_string())
+            .or_insert(TokenBucket {
+                tokens: capacity,
+                updated_at: now,
+                capacity,
+                refill_per_sec,
+            });
+        bucket.refill(now);
+
+        let acquired = bucket.tokens >= 1.0;
+        if acquired {
+            bucket.tokens -= 1.0;
+        }
+
+        let state = LimitState {
+            limit: capacity as u32,
+            remaining: bucket.tokens.floor() as u32,
+            reset: ((capacity - bucket.tokens) / refill_per_sec).ceil() as u64,
+        };
+
+        if acquired {
+            Ok(state)
+        } else {
+            Err(state)
+        }
+    }
+
+    // Counts a compilation in the daily quota of the caller, failing when it is exhausted
+    // The quota is not enforced when the counter can not be updated
+    pub async fn check_compile_quota<T>(
+        &self,
+        db: &Database,
+        caller: &Caller,
+        address: &str,
+        report: &QuotaReport,
+    ) -> Result<(), Custom<Json<ServerResponse<T>>>> {
+        let (quota_key, limit) = match caller {
+            Caller::Wallet(_) => (
+                format!("address:{}", address),
+                self.config.daily_compile_quota,
+            ),
+            Caller::Service(api_key) => (
+                format!("key:{}", api_key.key_id),
+                self.config.api_key_daily_compile_quota,
+            ),
+        };
+
+        let now = unix_timestamp();
+        let used = match db.increment_compile_quota(&quota_key, &utc_day(now)).await {
+            Ok(used) => used,
+            Err(_) => {
+                error!(target: "compiler", "There was an error updating the compile quota of {}", &quota_key);
+                return Ok(());
+            }
+        };
+
+        let state = LimitState::daily_quota(limit, used, now);
+        if used > limit {
+            info!(target: "compiler", "Daily compile quota exceeded for {}", &quota_key);
+            return Err(report.exceeded(state));
+        }
+        report.report(state);
+
+        Ok(())
+    }
+
+    // Buckets of the request, the IP bucket is always charged so unverified credentials can not skip it
+    // API keys are limited once verified by their guard
+    fn request_buckets(&self, request: &Request<'_>) -> Vec<(String, u32, u32)> {
+        let mut buckets = Vec::new();
+        if let Some(ip) = request.client_ip() {
+            buckets.push((
+                format!("ip:{}", ip),
+                self.config.capacity,
+                self.config.refill_per_minute,
+            ));
+        }
+        if let Some(address) = request_address(request) {
+            buckets.push((
+                format!("address:{}", address),
+                self.config.capacity,
+                self.config.refill_per_minute,
+            ));
+        }
+
+        buckets
+    }
+}
+
+// Wallet address of a verified session token, the signature headers are only verified with the body
+// so an unverified address would let anyone drain the bucket of another wallet
+fn request_address(request: &Request<'_>) -> Option<String> {
+    let token = request
+        .headers()
+        .get_one("Authorization")?
+        .strip_prefix("Bearer ")?;
+    let keys = request.rocket().state::<SessionKeys>()?;
+    keys.verify(token.trim(), unix_timestamp_secs() as u64).ok()
+}
+
+// Date of the UTC day of the timestamp, like 2023-05-10
+pub fn utc_day(now: u64) -> String {
+    let day_start = (now - now % SECONDS_PER_DAY) as i64 * 1000;
+    let date = DateTime::from_millis(day_start)
+        .try_to_rfc3339_string()
+        .unwrap_or_default();
+    date.chars().take(10).collect()
+}
+
+fn unix_timestamp_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs_f64())
+        .unwrap_or_default()
+}
+
+// Limits reported in the headers of the response
+#[derive(Debug, Default)]
+struct RequestLimits {
+    bucket: Option<LimitState>,
+    limited: bool,
+}
+
+// Daily compile quota of the request, filled by the handler and reported in the response headers
+#[derive(Debug, Clone, Default)]
+pub struct QuotaReport(pub Arc<Mutex<Option<LimitState>>>);
+
+impl QuotaReport {
+    pub fn report(&self, state: LimitState) {
+        if let Ok(mut quota) = self.0.lock() {
+            *quota = Some(state);
+        }
+    }
+
+    // Error returned when the daily quota is exhausted
+    pub fn exceeded<T>(&self, state: LimitState) -> Custom<Json<ServerResponse<T>>> {
+        self.report(state);
+        Custom(
+            Status::TooManyRequests,
+            Json(ServerResponse::new_error(format!(
+                "Daily compile quota of {} exceeded.",
+                state.limit
+            ))),
+        )
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for QuotaReport {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(request.local_cache(QuotaReport::default).clone())
+    }
+}
+
+// Handler of a route, requests over their rate limit are answered by the 429 catcher without running it
+#[derive(Clone)]
+pub struct RateLimitedHandler(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for RateLimitedHandler {
+    async fn handle<'r>(&self, request: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        if request.local_cache(RequestLimits::default).limited {
+            return route::Outcome::Error(Status::TooManyRequests);
+        }
+        self.0.handle(request, data).await
+    }
+}
+
+// Rejects the requests of the routes over the limits applied by RateLimitFairing
+pub fn rate_limited(routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(RateLimitedHandler(route.handler));
+            route
+        })
+        .collect()
+}
+
+// Fairing applying the token buckets to every request, see rate_limited for the rejection
+pub struct RateLimitFairing;
+
+#[rocket::async_trait]
+impl Fairing for RateLimitFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Rate Limiter",
+            kind: Kind::Ignite | Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        match rocket.state::<AppConfig>() {
+            Some(app_config) => {
+                let config = app_config.rate_limit.clone();
+                info!(target: "compiler", "Rate limiter loaded: {:?}", config);
+                Ok(rocket.manage(RateLimiter::new(config)))
+            }
+            None => {
+                error!(target: "compiler", "Rate limiter loaded before the configuration");
+                Err(rocket)
+            }
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data<'_>) {
+        if request.method() == Method::Options {
+            return;
+        }
+        let rate_limiter = match request.rocket().state::<RateLimiter>() {
+            Some(rate_limiter) => rate_limiter,
+            None => return,
+        };
+
+        let now = unix_timestamp_secs();
+        let mut limits = RequestLimits::default();
+        for (key, capacity, refill_per_minute) in rate_limiter.request_buckets(request) {
+            let (state, acquired) =
+                match rate_limiter.acquire(&key, capacity, refill_per_minute, now) {
+                    Ok(state) => (state, true),
+                    Err(state) => (state, false),
+                };
+
+            // The most restrictive bucket is reported
+            if limits
+                .bucket
+                .is_none_or(|bucket| state.remaining < bucket.remaining)
+                || !acquired
+            {
+                limits.bucket = Some(state);
+            }
+            if !acquired {
+                info!(target: "compiler", "Rate limit exceeded for {}", key);
+                limits.limited = true;
+                break;
+            }
+        }
+
+        request.local_cache(|| limits);
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let limits = request.local_cache(RequestLimits::default);
+        if let Some(bucket) = limits.bucket {
+            response.set_header(Header::new(
+                RATE_LIMIT_LIMIT_HEADER,
+                bucket.limit.to_string(),
+            ));
+            response.set_header(Header::new(
+                RATE_LIMIT_REMAINING_HEADER,
+                bucket.remaining.to_string(),
+            ));
+            response.set_header(Header::new(
+                RATE_LIMIT_RESET_HEADER,
+                bucket.reset.to_string(),
+            ));
+            if limits.limited {
+                response.set_header(Header::new("Retry-After", bucket.reset.max(1).to_string()));
+            }
+        }
+
+        let quota = request.local_cache(QuotaReport::default);
+        if let Some(quota) = quota.0.lock().ok().and_then(|quota| *quota) {
+            response.set_header(Header::new(QUOTA_LIMIT_HEADER, quota.limit.to_string()));
+            response.set_header(Header::new(
+                QUOTA_REMAINING_HEADER,
+                quota.remaining.to_string(),
+            ));
+            response.set_header(Header::new(QUOTA_RESET_HEADER, quota.reset.to_string()));
+            if response.status() == Status::TooManyRequests {
+                response.set_header(Header::new("Retry-After", quota.reset.to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/rate_limiter_tests.rs"]
+mod rate_limiter_test;
@@ -0,0 +1,114 @@
+use log::{Log, Metadata, Record};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::request::{FromRequest, Outcome};
+use rocket::route::{self, Handler};
+use rocket::{Data, Request, Response, Route};
+use uuid::Uuid;
+
+// Header used to return the request id to the client
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+// Key under which the request id is stored in the logging MDC
+pub const REQUEST_ID_LOG_KEY: &str = "request_id";
+
+// Unique identifier assigned to every incoming request
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    // Generates a new random request id
+    pub fn generate() -> Self {
+        RequestId(Uuid::new_v4().to_string())
+    }
+}
+
+rocket::tokio::task_local! {
+    // Id of the request handled by the current task, it follows the task across threads
+    static CURRENT_REQUEST_ID: String;
+}
+
+// Logger adding the request id of the current task to the MDC read by the log4rs patterns
+// The MDC is thread-local, so it is only set while a record is written and never held across awaits
+pub struct RequestIdLogger<L: Log>(pub L);
+
+impl<L: Log> Log for RequestIdLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.0.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        match CURRENT_REQUEST_ID.try_with(String::clone) {
+            Ok(request_id) => {
+                let _log_scope = log_mdc::insert_scoped(REQUEST_ID_LOG_KEY, request_id);
+                self.0.log(record);
+            }
+            Err(_) => self.0.log(record),
+        }
+    }
+
+    fn flush(&self) {
+        self.0.flush()
+    }
+}
+
+// Route handler running the wrapped handler, with its request guards, in the scope of the request id
+#[derive(Clone)]
+pub struct RequestIdHandler(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for RequestIdHandler {
+    async fn handle<'r>(&self, request: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        let request_id = request.local_cache(RequestId::generate).0.clone();
+        CURRENT_REQUEST_ID
+            .scope(request_id, self.0.handle(request, data))
+            .await
+    }
+}
+
+// Tags the log records of every handler of the routes with the id of its request
+pub fn log_scoped(routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(RequestIdHandler(route.handler));
+            route
+        })
+        .collect()
+}
+
+// Fairing that assigns a request id to every request and returns it in the response headers
+pub struct RequestIdFairing;
+
+#[rocket::async_trait]
+impl Fairing for RequestIdFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request Id",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data<'_>) {
+        request.local_cache(RequestId::generate);
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let request_id = request.local_cache(RequestId::generate);
+        response.set_header(Header::new(REQUEST_ID_HEADER, request_id.0.clone()));
+    }
+}
+
+// Request guard giving handlers access to the id of the current request
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestId {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(request.local_cache(RequestId::generate).clone())
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/request_id_tests.rs"]
+mod request_id_test;
@@ -1,71 +1,326 @@
-use crate::models::api_models::{ServerResponse, WizardMessage};
+use crate::models::api_models::{DeployMessage, ServerResponse, WizardMessage};
 use crate::models::db_models::Contract;
+use crate::utils::config::CompilerConfig;
+use crate::utils::contract_address::{code_hash, derive_contract_address};
+use crate::utils::network_registry::{Network, NetworkRegistry};
+use blake2::{Blake2b512, Digest};
 use log::error;
 use rocket::{http::Status, response::status::Custom, serde::json::Json};
+use std::fmt;
 
 pub const CONTRACTS: [&str; 3] = ["psp22", "psp34", "psp37"];
 
-pub const ALLOWED_FEATURES: [&str; 6] = [
-    "psp22",
-    "psp34",
-    "psp37",
-    "pausable",
-    "ownable",
-    "access-control",
-];
+// Prefix prepended to the payload when computing the SS58 checksum
+pub const SS58_CHECKSUM_PREFIX: &[u8] = b"SS58PRE";
+pub const SS58_CHECKSUM_LEN: usize = 2;
+const ACCOUNT_ID_LEN: usize = 32;
 
-pub const MAX_SIZE_ALLOWED: usize = 49999;
+// Reasons why an SS58 address is rejected
+#[derive(Debug, PartialEq)]
+pub enum Ss58Error {
+    InvalidBase58,
+    InvalidLength,
+    InvalidPrefix,
+    InvalidChecksum,
+    PrefixMismatch {
+        network: String,
+        expected: u16,
+        found: u16,
+    },
+}
+
+impl fmt::Display for Ss58Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ss58Error::InvalidBase58 => write!(f, "invalid base58 encoding"),
+            Ss58Error::InvalidLength => write!(f, "invalid length"),
+            Ss58Error::InvalidPrefix => write!(f, "invalid SS58 prefix"),
+            Ss58Error::InvalidChecksum => write!(f, "invalid checksum"),
+            Ss58Error::PrefixMismatch {
+                network,
+                expected,
+                found,
+            } => write!(
+                f,
+                "SS58 prefix {} does not match network {} (expected {})",
+                found, network, expected
+            ),
+        }
+    }
+}
 
 pub fn sanity_check_wizard_message(
     wizard_message: &Json<WizardMessage>,
+    config: &CompilerConfig,
 ) -> Result<(), Custom<Json<ServerResponse<Contract>>>> {
     // Checks length of the code not passing the max allowed
-    match check_code_len(&wizard_message.code) {
+    match check_code_len(&wizard_message.code, config.max_code_size) {
         Ok(_) => (),
         Err(msg) => {
             return Err(Custom(
                 Status::InternalServerError,
-                Json(ServerResponse::new_error(String::from(msg))),
+                Json(ServerResponse::new_error(msg)),
             ))
         }
     }
 
-    // Checks the address len is valid
-    match check_address_len(&wizard_message.address) {
+    // Checks the address is a valid SS58 address
+    match check_address(&wizard_message.address, None) {
         Ok(_) => (),
-        Err(msg) => {
+        Err(err) => {
             return Err(Custom(
                 Status::InternalServerError,
-                Json(ServerResponse::new_error(String::from(msg))),
+                Json(ServerResponse::new_error(format!(
+                    "Address is not valid: {}.",
+                    err
+                ))),
             ))
         }
     }
 
-    check_features(&wizard_message.features)?;
+    check_features(&wizard_message.features, &config.allowed_features)?;
+
+    Ok(())
+}
+
+// Checks the user and contract addresses of a deployment belong to its network
+pub fn sanity_check_deployment_addresses(
+    user_address: &str,
+    contract_address: &str,
+    network: &Network,
+) -> Result<(), Custom<Json<ServerResponse<String>>>> {
+    for (field, address) in [
+        ("user_address", user_address),
+        ("contract_address", contract_address),
+    ] {
+        if let Err(err) = check_address(address, Some(network)) {
+            return Err(Custom(
+                Status::InternalServerError,
+                Json(ServerResponse::new_error(format!(
+                    "Invalid {}: {}.",
+                    field, err
+                ))),
+            ));
+        }
+    }
 
     Ok(())
 }
 
-pub fn check_code_len(code: &String) -> Result<(), String> {
-    if code.len() > MAX_SIZE_ALLOWED {
+// Checks the contract address of a deployment is the one derived by pallet-contracts, when the
+// deployment gives the salt or the constructor data of the instantiation
+pub fn check_contract_address(
+    deploy_message: &DeployMessage,
+    contract: Option<&Contract>,
+    network: &Network,
+) -> Result<(), Custom<Json<ServerResponse<String>>>> {
+    if deploy_message.salt.is_none() && deploy_message.constructor_data.is_none() {
+        return Ok(());
+    }
+    let error = |message: String| {
+        error!(target: "compiler", "Contract address {} not verified: {}", &deploy_message.contract_address, message);
+        Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::new_error(message)),
+        )
+    };
+
+    // Only the wasm of the contracts compiled by this service is known
+    let contract = contract.ok_or_else(|| {
+        error(format!(
+            "The contract address can not be verified, contract {} was not compiled by this service.",
+            deploy_message.code_id
+        ))
+    })?;
+    let decode_hex = |field: &str, value: &Option<String>| {
+        let value = value.as_deref().unwrap_or_default();
+        hex::decode(value.strip_prefix("0x").unwrap_or(value))
+            .map_err(|_| error(format!("Invalid {}: not hex encoded.", field)))
+    };
+    let salt = decode_hex("salt", &deploy_message.salt)?;
+    let input_data = decode_hex("constructor_data", &deploy_message.constructor_data)?;
+    let (_, deployer) = decode_ss58_address(&deploy_message.user_address)
+        .map_err(|err| error(format!("Invalid user_address: {}.", err)))?;
+    let (_, contract_address) = decode_ss58_address(&deploy_message.contract_address)
+        .map_err(|err| error(format!("Invalid contract_address: {}.", err)))?;
+
+    let expected =
+        derive_contract_address(&deployer, &code_hash(&contract.wasm), &input_data, &salt);
+    if expected != contract_address {
+        return Err(error(format!(
+            "contract_address does not match the address {} derived from the code_id, user_address, salt and constructor_data.",
+            encode_ss58_address(network.ss58_prefix, &expected)
+        )));
+    }
+
+    Ok(())
+}
+
+// Finds the network in the registry, rejecting unknown networks
+pub fn check_network<'a, T>(
+    registry: &'a NetworkRegistry,
+    network: &str,
+) -> Result<&'a Network, Custom<Json<ServerResponse<T>>>> {
+    match registry.resolve(network) {
+        Some(network) => Ok(network),
+        None => {
+            error!(target: "compiler", "Network not supported: {:?}", network);
+            Err(Custom(
+                Status::UnprocessableEntity,
+                Json(ServerResponse::new_error(format!(
+                    "Network not supported: {}.",
+                    network
+                ))),
+            ))
+        }
+    }
+}
+
+// Checks the wasm code is not bigger than the maximum code size of the network
+pub fn check_code_size<T>(
+    code_size: usize,
+    network: &Network,
+) -> Result<(), Custom<Json<ServerResponse<T>>>> {
+    if code_size > network.max_code_size {
+        error!(target: "compiler", "Code size {} exceeds the limit of network {}", code_size, network.id);
+        return Err(Custom(
+            Status::UnprocessableEntity,
+            Json(ServerResponse::new_error(format!(
+                "Code size of {} bytes exceeds the {} limit of {} bytes.",
+                code_size, network.name, network.max_code_size
+            ))),
+        ));
+    }
+    Ok(())
+}
+
+// Checks the network accepts the contracts built by the toolchain of the compiler
+pub fn check_toolchain<T>(
+    network: &Network,
+    config: &CompilerConfig,
+) -> Result<(), Custom<Json<ServerResponse<T>>>> {
+    if network.toolchain_profiles.is_empty()
+        || network
+            .toolchain_profiles
+            .contains(&config.toolchain_profile)
+    {
+        return Ok(());
+    }
+
+    error!(target: "compiler", "Network {} does not support the {} toolchain", network.id, config.toolchain_profile);
+    Err(Custom(
+        Status::UnprocessableEntity,
+        Json(ServerResponse::new_error(format!(
+            "{} does not support the {} toolchain of the compiler, it supports {}.",
+            network.name,
+            config.toolchain_profile,
+            network.toolchain_profiles.join(", ")
+        ))),
+    ))
+}
+
+pub fn check_code_len(code: &str, max_code_size: usize) -> Result<(), String> {
+    if code.len() > max_code_size {
         error!(target: "compiler", "Code size is too big");
         return Err("Code size too big.".to_string());
     }
     Ok(())
 }
 
-pub fn check_address_len(address: &String) -> Result<(), String> {
-    println!("Address: {:?}", address);
-    println!("Address len: {:?}", address.len());
-    if address.to_owned().len() != 48 {
-        error!(target: "compiler", "Address is not valid");
-        return Err("Address is not valid.".to_string());
+// Checks the address is a valid SS58 account address, and when a network is given,
+// that its prefix matches the network. Returns the decoded prefix
+pub fn check_address(address: &str, network: Option<&Network>) -> Result<u16, Ss58Error> {
+    let (prefix, _account_id) = decode_ss58_address(address).map_err(|err| {
+        error!(target: "compiler", "Address {} is not valid: {}", address, err);
+        err
+    })?;
+
+    if let Some(network) = network {
+        if network.ss58_prefix != prefix {
+            error!(target: "compiler", "Address {} does not belong to network {}", address, network.id);
+            return Err(Ss58Error::PrefixMismatch {
+                network: network.id.clone(),
+                expected: network.ss58_prefix,
+                found: prefix,
+            });
+        }
     }
-    Ok(())
+
+    Ok(prefix)
+}
+
+// Decodes an SS58 address into its network prefix and account id
+pub fn decode_ss58_address(address: &str) -> Result<(u16, [u8; ACCOUNT_ID_LEN]), Ss58Error> {
+    let data = bs58::decode(address)
+        .into_vec()
+        .map_err(|_| Ss58Error::InvalidBase58)?;
+
+    if data.is_empty() {
+        return Err(Ss58Error::InvalidLength);
+    }
+
+    // Prefixes below 64 take one byte, prefixes up to 16383 take two bytes
+    let (prefix_len, prefix) = match data[0] {
+        0..=63 => (1, data[0] as u16),
+        64..=127 => {
+            if data.len() < 2 {
+                return Err(Ss58Error::InvalidLength);
+            }
+            let lower = (data[0] << 2) | (data[1] >> 6);
+            let upper = data[1] & 0b0011_1111;
+            (2, (lower as u16) | ((upper as u16) << 8))
+        }
+        _ => return Err(Ss58Error::InvalidPrefix),
+    };
+
+    if data.len() != prefix_len + ACCOUNT_ID_LEN + SS58_CHECKSUM_LEN {
+        return Err(Ss58Error::InvalidLength);
+    }
+
+    // Prefixes 46 and 47 are reserved
+    if prefix == 46 || prefix == 47 {
+        return Err(Ss58Error::InvalidPrefix);
+    }
+
+    let (payload, checksum) = data.split_at(data.len() - SS58_CHECKSUM_LEN);
+    let hash = Blake2b512::new()
+        .chain_update(SS58_CHECKSUM_PREFIX)
+        .chain_update(payload)
+        .finalize();
+    if hash[..SS58_CHECKSUM_LEN] != *checksum {
+        return Err(Ss58Error::InvalidChecksum);
+    }
+
+    let mut account_id = [0u8; ACCOUNT_ID_LEN];
+    account_id.copy_from_slice(&payload[prefix_len..]);
+
+    Ok((prefix, account_id))
+}
+
+// Encodes an account id as an SS58 address with the network prefix
+pub fn encode_ss58_address(prefix: u16, account_id: &[u8; ACCOUNT_ID_LEN]) -> String {
+    // Prefixes up to 16383 are split in two bytes with the first one in 64..=127
+    let mut data = match prefix {
+        0..=63 => vec![prefix as u8],
+        _ => vec![
+            ((prefix & 0b1111_1100) >> 2) as u8 | 0b0100_0000,
+            (prefix >> 8) as u8 | ((prefix & 0b11) << 6) as u8,
+        ],
+    };
+    data.extend_from_slice(account_id);
+
+    let hash = Blake2b512::new()
+        .chain_update(SS58_CHECKSUM_PREFIX)
+        .chain_update(&data)
+        .finalize();
+    data.extend_from_slice(&hash[..SS58_CHECKSUM_LEN]);
+
+    bs58::encode(data).into_string()
 }
 
 pub fn check_features(
     features: &Vec<String>,
+    allowed_features: &[String],
 ) -> Result<(), Custom<Json<ServerResponse<Contract>>>> {
     // Checks features not to be empty
     if features.is_empty() {
@@ -80,7 +335,7 @@ pub fn check_features(
 
     // Checks all the features passed are allowed
     for feature in features {
-        if !ALLOWED_FEATURES.contains(&feature.as_str()) {
+        if !allowed_features.contains(feature) {
             error!(target: "compiler", "Feature not allowed: {:?}", feature);
             return Err(Custom(
                 Status::InternalServerError,
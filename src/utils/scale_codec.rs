@@ -0,0 +1,673 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::ink_metadata::{InkArg, InkMetadata, TypeDef, TypeField, TypeInfo, TypeRegistry};
+use super::sanity_check::decode_ss58_address;
+
+// Nesting of the encoded values, the registry can contain recursive types
+const MAX_DEPTH: usize = 32;
+
+// Kind of the entry point of a contract call
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallKind {
+    Constructor,
+    Message,
+}
+
+// Kind of the SCALE data to decode
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecodeKind {
+    Call,
+    Return,
+    Event,
+}
+
+// Call data of a constructor or a message, the selector followed by the encoded arguments
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EncodedCall {
+    pub kind: CallKind,
+    pub label: String,
+    pub selector: String,
+    pub data: String,
+}
+
+// SCALE data decoded to JSON, the selector is only set for call data
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DecodedData {
+    pub kind: DecodeKind,
+    pub label: String,
+    pub selector: Option<String>,
+    pub value: Value,
+}
+
+// Encodes the call data of a constructor or a message of the contract
+// The arguments are an object keyed by their labels or an array in the order of the spec
+pub fn encode_call(
+    metadata: &Value,
+    kind: CallKind,
+    label: &str,
+    args: &Value,
+) -> Result<EncodedCall, String> {
+    let ink_metadata = InkMetadata::from_value(metadata)?;
+    let registry = ink_metadata.registry();
+    let spec = &ink_metadata.spec;
+    let (selector, spec_args) = match kind {
+        CallKind::Constructor => spec
+            .constructors
+            .iter()
+            .find(|constructor| constructor.label == label)
+            .map(|constructor| (&constructor.selector, &constructor.args)),
+        CallKind::Message => spec
+            .messages
+            .iter()
+            .find(|message| message.label == label)
+            .map(|message| (&message.selector, &message.args)),
+    }
+    .ok_or_else(|| {
+        let kind = match kind {
+            CallKind::Constructor => "constructor",
+            CallKind::Message => "message",
+        };
+        format!("The contract has no {} {}", kind, label)
+    })?;
+
+    let mut data = decode_hex(selector)?;
+    let arg_value = |index: usize, arg: &InkArg| match args {
+        Value::Object(object) => object.get(&arg.label),
+        Value::Array(items) => items.get(index),
+        _ => None,
+    };
+    match args {
+        Value::Array(items) if items.len() != spec_args.len() => {
+            return Err(format!("Expected {} arguments", spec_args.len()))
+        }
+        Value::Object(_) | Value::Array(_) => {}
+        Value::Null if spec_args.is_empty() => {}
+        _ => return Err(String::from("Arguments must be an object or an array")),
+    }
+    for (index, arg) in spec_args.iter().enumerate() {
+        let value =
+            arg_value(index, arg).ok_or_else(|| format!("Missing argument {}", arg.label))?;
+        encode_value(&registry, arg.type_spec.id, value, &mut data)
+            .map_err(|err| format!("Invalid argument {}: {}", arg.label, err))?;
+    }
+
+    Ok(EncodedCall {
+        kind,
+        label: label.to_string(),
+        selector: selector.clone(),
+        data: encode_hex(&data),
+    })
+}
+
+// Decodes call data, a return value or an event payload of the contract
+// Return values need the label of the message, ink 5 events the label or the signature topic
+pub fn decode_data(
+    metadata: &Value,
+    kind: DecodeKind,
+    label: Option<&str>,
+    data: &str,
+) -> Result<DecodedData, String> {
+    let ink_metadata = InkMetadata::from_value(metadata)?;
+    let registry = ink_metadata.registry();
+    let spec = &ink_metadata.spec;
+    let bytes = decode_hex(data)?;
+    let input = &mut bytes.as_slice();
+
+    let decoded = match kind {
+        DecodeKind::Call => {
+            let selector = encode_hex(take(input, 4)?);
+            let (label, args) = spec
+                .messages
+                .iter()
+                .map(|message| (&message.label, &message.selector, &message.args))
+                .chain(spec.constructors.iter().map(|constructor| {
+                    (&constructor.label, &constructor.selector, &constructor.args)
+                }))
+                .find(|(_, spec_selector, _)| spec_selector.eq_ignore_ascii_case(&selector))
+                .map(|(label, _, args)| (label, args))
+                .ok_or_else(|| {
+                    format!("No constructor or message has the selector {}", selector)
+                })?;
+            let args = args
+                .iter()
+                .map(|arg| {
+                    Ok((
+                        arg.label.clone(),
+                        decode_value(&registry, arg.type_spec.id, input)?,
+                    ))
+                })
+                .collect::<Result<Map<String, Value>, String>>()?;
+            DecodedData {
+                kind,
+                label: label.clone(),
+                selector: Some(selector),
+                value: Value::Object(args),
+            }
+        }
+        DecodeKind::Return => {
+            let label =
+                label.ok_or_else(|| String::from("The label of the message is required"))?;
+            let return_type = spec
+                .messages
+                .iter()
+                .find(|message| message.label == label)
+                .map(|message| &message.return_type)
+                .or_else(|| {
+                    spec.constructors
+                        .iter()
+                        .find(|constructor| constructor.label == label)
+                        .map(|constructor| &constructor.return_type)
+                })
+                .ok_or_else(|| format!("The contract has no message {}", label))?;
+            let value = match return_type {
+                Some(type_spec) => decode_value(&registry, type_spec.id, input)?,
+                None => Value::Null,
+            };
+            DecodedData {
+                kind,
+                label: label.to_string(),
+                selector: None,
+                value,
+            }
+        }
+        DecodeKind::Event => {
+            // ink 4 prefixes the payload with the index of the event, ink 5 identifies it by its topic
+            let ink_4 = spec
+                .events
+                .iter()
+                .all(|event| event.signature_topic.is_none());
+            let index = match ink_4 {
+                true => Some(usize::from(take(input, 1)?[0])),
+                false => None,
+            };
+            let event = match (label, index) {
+                (Some(label), _) => spec.events.iter().enumerate().find(|(_, event)| {
+                    event.label == label || event.signature_topic.as_deref() == Some(label)
+                }),
+                (None, Some(index)) => spec.events.get(index).map(|event| (index, event)),
+                (None, None) => {
+                    return Err(String::from(
+                        "The label or the signature topic of the event is required",
+                    ))
+                }
+            }
+            .filter(|(position, _)| index.is_none_or(|index| index == *position))
+            .map(|(_, event)| event)
+            .ok_or_else(|| String::from("The contract has no such event"))?;
+            let args = event
+                .args
+                .iter()
+                .map(|arg| {
+                    Ok((
+                        arg.label.clone(),
+                        decode_value(&registry, arg.type_spec.id, input)?,
+                    ))
+                })
+                .collect::<Result<Map<String, Value>, String>>()?;
+            DecodedData {
+                kind,
+                label: event.label.clone(),
+                selector: None,
+                value: Value::Object(args),
+            }
+        }
+    };
+
+    if !input.is_empty() {
+        return Err(format!("{} bytes left after decoding", input.len()));
+    }
+    Ok(decoded)
+}
+
+// Encodes a JSON value as the SCALE encoding of a type of the registry
+// Integers wider than 64 bits are given as strings, byte arrays and sequences as 0x prefixed hex,
+// account ids as SS58 or hex, Option as null or the value, other enums as "Variant" or {"Variant": value}
+pub fn encode_value(
+    registry: &TypeRegistry,
+    id: u32,
+    value: &Value,
+    output: &mut Vec<u8>,
+) -> Result<(), String> {
+    Encoder { registry, output }.encode(id, value, 0)
+}
+
+// Decodes a SCALE encoded value of a type of the registry, consuming its bytes from the input
+pub fn decode_value(registry: &TypeRegistry, id: u32, input: &mut &[u8]) -> Result<Value, String> {
+    Decoder { registry, input }.decode(id, 0)
+}
+
+// Parses 0x prefixed, or unprefixed, hex
+pub fn decode_hex(hex_string: &str) -> Result<Vec<u8>, String> {
+    let digits = hex_string.strip_prefix("0x").unwrap_or(hex_string);
+    hex::decode(digits).map_err(|err| format!("Invalid hex {}: {}", hex_string, err))
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+// Compact encoding of the lengths and compact integers
+pub fn encode_compact(value: u128, output: &mut Vec<u8>) {
+    match value {
+        0..=0x3f => output.push((value as u8) << 2),
+        0x40..=0x3fff => output.extend_from_slice(&(((value as u16) << 2) | 0b01).to_le_bytes()),
+        0x4000..=0x3fff_ffff => {
+            output.extend_from_slice(&(((value as u32) << 2) | 0b10).to_le_bytes())
+        }
+        _ => {
+            let bytes = value.to_le_bytes();
+            let len = bytes.len() - bytes.iter().rev().take_while(|byte| **byte == 0).count();
+            output.push((((len - 4) as u8) << 2) | 0b11);
+            output.extend_from_slice(&bytes[..len]);
+        }
+    }
+}
+
+pub fn decode_compact(input: &mut &[u8]) -> Result<u128, String> {
+    let first = *take(input, 1)?.first().unwrap_or(&0);
+    let value = match first & 0b11 {
+        0b00 => u128::from(first >> 2),
+        0b01 => {
+            let second = take(input, 1)?[0];
+            u128::from(u16::from_le_bytes([first, second]) >> 2)
+        }
+        0b10 => {
+            let rest = take(input, 3)?;
+            u128::from(u32::from_le_bytes([first, rest[0], rest[1], rest[2]]) >> 2)
+        }
+        _ => {
+            let len = usize::from(first >> 2) + 4;
+            if len > 16 {
+                return Err(String::from("Compact integer wider than 128 bits"));
+            }
+            let mut bytes = [0u8; 16];
+            bytes[..len].copy_from_slice(take(input, len)?);
+            u128::from_le_bytes(bytes)
+        }
+    };
+    Ok(value)
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if input.len() < len {
+        return Err(String::from("Unexpected end of the encoded data"));
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+fn is_account_id(info: &TypeInfo) -> bool {
+    info.path.last().map(String::as_str) == Some("AccountId")
+}
+
+fn is_option(info: &TypeInfo) -> bool {
+    info.path == ["Option"]
+}
+
+struct Encoder<'a> {
+    registry: &'a TypeRegistry,
+    output: &'a mut Vec<u8>,
+}
+
+impl Encoder<'_> {
+    fn encode(&mut self, id: u32, value: &Value, depth: usize) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err(String::from("Value nested too deeply"));
+        }
+        let info = self.registry.get(id)?;
+        let name = self.registry.type_name(id);
+
+        match &info.def {
+            TypeDef::Primitive(primitive) => self.encode_primitive(primitive, value),
+            TypeDef::Composite { fields } if is_account_id(info) => {
+                let account_id = match value.as_str() {
+                    Some(address) if !address.starts_with("0x") => decode_ss58_address(address)
+                        .map(|(_, account_id)| account_id.to_vec())
+                        .map_err(|err| format!("Invalid account id {}: {}", address, err))?,
+                    _ => return self.encode_fields(fields, value, &name, depth),
+                };
+                self.output.extend_from_slice(&account_id);
+                Ok(())
+            }
+            TypeDef::Composite { fields } => self.encode_fields(fields, value, &name, depth),
+            TypeDef::Variant { variants } if is_option(info) && !value.is_object() => {
+                let (index, inner) = match value {
+                    Value::Null => (0, None),
+                    value => (1, Some(value)),
+                };
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.index == index)
+                    .ok_or_else(|| format!("Invalid {}", name))?;
+                self.output.push(variant.index);
+                match (inner, variant.fields.first()) {
+                    (Some(inner), Some(field)) => self.encode(field.id, inner, depth + 1),
+                    _ => Ok(()),
+                }
+            }
+            TypeDef::Variant { variants } => {
+                let (variant_name, fields_value) = match value {
+                    Value::String(variant_name) => (variant_name.as_str(), &Value::Null),
+                    Value::Object(object) if object.len() == 1 => {
+                        let (variant_name, fields_value) = object.iter().next().unwrap();
+                        (variant_name.as_str(), fields_value)
+                    }
+                    _ => return Err(format!("Expected a variant of {}", name)),
+                };
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.name == variant_name)
+                    .ok_or_else(|| format!("{} has no variant {}", name, variant_name))?;
+                self.output.push(variant.index);
+                self.encode_fields(&variant.fields, fields_value, &name, depth)
+            }
+            TypeDef::Sequence { id: item } => {
+                let items = self.items(*item, value, &name)?;
+                encode_compact(items.len() as u128, self.output);
+                self.encode_items(*item, &items, depth)
+            }
+            TypeDef::Array { len, id: item } => {
+                let items = self.items(*item, value, &name)?;
+                if items.len() != *len as usize {
+                    return Err(format!("Expected {} items for {}", len, name));
+                }
+                self.encode_items(*item, &items, depth)
+            }
+            TypeDef::Tuple(ids) if ids.is_empty() => Ok(()),
+            TypeDef::Tuple(ids) => {
+                let items = value
+                    .as_array()
+                    .filter(|items| items.len() == ids.len())
+                    .ok_or_else(|| format!("Expected {} items for {}", ids.len(), name))?;
+                ids.iter()
+                    .zip(items)
+                    .try_for_each(|(id, item)| self.encode(*id, item, depth + 1))
+            }
+            TypeDef::Compact { .. } => {
+                encode_compact(unsigned(value, u128::MAX, &name)?, self.output);
+                Ok(())
+            }
+            TypeDef::BitSequence { .. } => Err(format!("{} is not supported", name)),
+        }
+    }
+
+    // Named fields are given as an object, a single unnamed field as its value and several as an array
+    fn encode_fields(
+        &mut self,
+        fields: &[TypeField],
+        value: &Value,
+        name: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        match fields {
+            [] => Ok(()),
+            [field] if field.name.is_none() => self.encode(field.id, value, depth + 1),
+            _ if fields.iter().all(|field| field.name.is_some()) => {
+                let object = value
+                    .as_object()
+                    .ok_or_else(|| format!("Expected an object for {}", name))?;
+                fields.iter().try_for_each(|field| {
+                    let field_name = field.name.as_deref().unwrap_or_default();
+                    let field_value = object
+                        .get(field_name)
+                        .ok_or_else(|| format!("Missing field {} of {}", field_name, name))?;
+                    self.encode(field.id, field_value, depth + 1)
+                })
+            }
+            _ => {
+                let items = value
+                    .as_array()
+                    .filter(|items| items.len() == fields.len())
+                    .ok_or_else(|| format!("Expected {} items for {}", fields.len(), name))?;
+                fields
+                    .iter()
+                    .zip(items)
+                    .try_for_each(|(field, item)| self.encode(field.id, item, depth + 1))
+            }
+        }
+    }
+
+    // Items of a sequence or an array, the byte ones can also be given as hex
+    fn items(&self, item: u32, value: &Value, name: &str) -> Result<Vec<Value>, String> {
+        match value {
+            Value::Array(items) => Ok(items.clone()),
+            Value::String(hex_string) if self.is_byte(item) => Ok(decode_hex(hex_string)?
+                .into_iter()
+                .map(Value::from)
+                .collect()),
+            _ => Err(format!("Expected an array for {}", name)),
+        }
+    }
+
+    fn encode_items(&mut self, item: u32, items: &[Value], depth: usize) -> Result<(), String> {
+        items
+            .iter()
+            .try_for_each(|value| self.encode(item, value, depth + 1))
+    }
+
+    fn is_byte(&self, id: u32) -> bool {
+        matches!(self.registry.get(id).map(|info| &info.def), Ok(TypeDef::Primitive(primitive)) if primitive == "u8")
+    }
+
+    fn encode_primitive(&mut self, primitive: &str, value: &Value) -> Result<(), String> {
+        let output = &mut *self.output;
+        match primitive {
+            "bool" => output.push(
+                value
+                    .as_bool()
+                    .ok_or_else(|| String::from("Expected a bool"))? as u8,
+            ),
+            "char" => {
+                let mut chars = value.as_str().unwrap_or_default().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(char), None) => output.extend_from_slice(&(char as u32).to_le_bytes()),
+                    _ => return Err(String::from("Expected a char")),
+                }
+            }
+            "str" => {
+                let string = value
+                    .as_str()
+                    .ok_or_else(|| String::from("Expected a string"))?;
+                encode_compact(string.len() as u128, output);
+                output.extend_from_slice(string.as_bytes());
+            }
+            "u8" => output.push(unsigned(value, u8::MAX.into(), primitive)? as u8),
+            "u16" => output.extend_from_slice(
+                &(unsigned(value, u16::MAX.into(), primitive)? as u16).to_le_bytes(),
+            ),
+            "u32" => output.extend_from_slice(
+                &(unsigned(value, u32::MAX.into(), primitive)? as u32).to_le_bytes(),
+            ),
+            "u64" => output.extend_from_slice(
+                &(unsigned(value, u64::MAX.into(), primitive)? as u64).to_le_bytes(),
+            ),
+            "u128" => {
+                output.extend_from_slice(&unsigned(value, u128::MAX, primitive)?.to_le_bytes())
+            }
+            "i8" | "i16" | "i32" | "i64" | "i128" => {
+                let bits = primitive[1..].parse::<u32>().unwrap_or(128);
+                let signed = signed(value, bits, primitive)?;
+                output.extend_from_slice(&signed.to_le_bytes()[..(bits / 8) as usize]);
+            }
+            _ => return Err(format!("{} is not supported", primitive)),
+        }
+        Ok(())
+    }
+}
+
+// Integers are accepted as JSON numbers or as decimal strings
+fn unsigned(value: &Value, max: u128, name: &str) -> Result<u128, String> {
+    let number = match value {
+        Value::Number(number) => number.as_u64().map(u128::from),
+        Value::String(number) => number.parse::<u128>().ok(),
+        _ => None,
+    };
+    number
+        .filter(|number| *number <= max)
+        .ok_or_else(|| format!("Expected an unsigned integer for {}", name))
+}
+
+fn signed(value: &Value, bits: u32, name: &str) -> Result<i128, String> {
+    let number = match value {
+        Value::Number(number) => number.as_i64().map(i128::from),
+        Value::String(number) => number.parse::<i128>().ok(),
+        _ => None,
+    };
+    let max = i128::MAX >> (128 - bits);
+    number
+        .filter(|number| *number <= max && *number >= -max - 1)
+        .ok_or_else(|| format!("Expected an integer for {}", name))
+}
+
+struct Decoder<'a, 'b> {
+    registry: &'a TypeRegistry,
+    input: &'a mut &'b [u8],
+}
+
+impl Decoder<'_, '_> {
+    fn decode(&mut self, id: u32, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(String::from("Value nested too deeply"));
+        }
+        let info = self.registry.get(id)?;
+        let name = self.registry.type_name(id);
+
+        match &info.def {
+            TypeDef::Primitive(primitive) => self.decode_primitive(primitive),
+            TypeDef::Composite { .. } if is_account_id(info) => {
+                Ok(Value::from(encode_hex(take(self.input, 32)?)))
+            }
+            TypeDef::Composite { fields } => self.decode_fields(fields, depth),
+            TypeDef::Variant { variants } => {
+                let index = take(self.input, 1)?[0];
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.index == index)
+                    .ok_or_else(|| format!("{} has no variant with index {}", name, index))?;
+                let fields = self.decode_fields(&variant.fields, depth)?;
+
+                if is_option(info) {
+                    return Ok(fields);
+                }
+                match variant.fields.is_empty() {
+                    true => Ok(Value::from(variant.name.clone())),
+                    false => Ok(Value::Object(Map::from_iter([(
+                        variant.name.clone(),
+                        fields,
+                    )]))),
+                }
+            }
+            TypeDef::Sequence { id: item } => {
+                let len = decode_compact(self.input)?;
+                if len > self.input.len() as u128 {
+                    return Err(format!("Invalid length of {}", name));
+                }
+                self.decode_items(*item, len as usize, depth)
+            }
+            TypeDef::Array { len, id: item } => self.decode_items(*item, *len as usize, depth),
+            TypeDef::Tuple(ids) if ids.is_empty() => Ok(Value::Null),
+            TypeDef::Tuple(ids) => ids
+                .iter()
+                .map(|id| self.decode(*id, depth + 1))
+                .collect::<Result<Vec<Value>, String>>()
+                .map(Value::from),
+            TypeDef::Compact { .. } => Ok(integer(decode_compact(self.input)?)),
+            TypeDef::BitSequence { .. } => Err(format!("{} is not supported", name)),
+        }
+    }
+
+    fn decode_fields(&mut self, fields: &[TypeField], depth: usize) -> Result<Value, String> {
+        match fields {
+            [] => Ok(Value::Null),
+            [field] if field.name.is_none() => self.decode(field.id, depth + 1),
+            _ if fields.iter().all(|field| field.name.is_some()) => fields
+                .iter()
+                .map(|field| {
+                    let value = self.decode(field.id, depth + 1)?;
+                    Ok((field.name.clone().unwrap_or_default(), value))
+                })
+                .collect::<Result<Map<String, Value>, String>>()
+                .map(Value::Object),
+            _ => fields
+                .iter()
+                .map(|field| self.decode(field.id, depth + 1))
+                .collect::<Result<Vec<Value>, String>>()
+                .map(Value::from),
+        }
+    }
+
+    // Byte arrays and sequences are returned as hex
+    fn decode_items(&mut self, item: u32, len: usize, depth: usize) -> Result<Value, String> {
+        if matches!(self.registry.get(item).map(|info| &info.def), Ok(TypeDef::Primitive(primitive)) if primitive == "u8")
+        {
+            return Ok(Value::from(encode_hex(take(self.input, len)?)));
+        }
+        (0..len)
+            .map(|_| self.decode(item, depth + 1))
+            .collect::<Result<Vec<Value>, String>>()
+            .map(Value::from)
+    }
+
+    fn decode_primitive(&mut self, primitive: &str) -> Result<Value, String> {
+        let mut fixed = |len: usize| -> Result<[u8; 16], String> {
+            let mut bytes = [0u8; 16];
+            bytes[..len].copy_from_slice(take(self.input, len)?);
+            Ok(bytes)
+        };
+
+        let value = match primitive {
+            "bool" => match fixed(1)?[0] {
+                0 => Value::from(false),
+                1 => Value::from(true),
+                _ => return Err(String::from("Invalid bool")),
+            },
+            "char" => {
+                let bytes = fixed(4)?;
+                let code = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                let char = char::from_u32(code).ok_or_else(|| String::from("Invalid char"))?;
+                Value::from(char.to_string())
+            }
+            "str" => {
+                let len = decode_compact(self.input)?;
+                if len > self.input.len() as u128 {
+                    return Err(String::from("Invalid length of String"));
+                }
+                let bytes = take(self.input, len as usize)?;
+                Value::from(String::from_utf8(bytes.to_vec()).map_err(|err| err.to_string())?)
+            }
+            "u8" | "u16" | "u32" | "u64" | "u128" => {
+                let len = primitive[1..].parse::<usize>().unwrap_or(128) / 8;
+                integer(u128::from_le_bytes(fixed(len)?))
+            }
+            "i8" | "i16" | "i32" | "i64" | "i128" => {
+                let bits = primitive[1..].parse::<u32>().unwrap_or(128);
+                let unsigned = u128::from_le_bytes(fixed((bits / 8) as usize)?);
+                // Sign extension of the narrower integers
+                let shift = 128 - bits;
+                let signed = ((unsigned << shift) as i128) >> shift;
+                match i64::try_from(signed) {
+                    Ok(signed) => Value::from(signed),
+                    Err(_) => Value::from(signed.to_string()),
+                }
+            }
+            _ => return Err(format!("{} is not supported", primitive)),
+        };
+        Ok(value)
+    }
+}
+
+// Integers wider than 64 bits are returned as strings, JSON numbers can not hold them
+fn integer(value: u128) -> Value {
+    match u64::try_from(value) {
+        Ok(value) => Value::from(value),
+        Err(_) => Value::from(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/scale_codec_tests.rs"]
+mod scale_codec_test;
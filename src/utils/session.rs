@@ -0,0 +1,280 @@
+use crate::models::api_models::{AuthChallenge, ServerResponse};
+use crate::utils::address_signature::{
+    unix_timestamp, SignatureError, SignedAddress, SIGNATURE_ADDRESS_HEADER,
+};
+use crate::utils::config::AuthConfig;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use log::{error, info};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::{Request, State};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+// Seconds a sign-in challenge can be answered
+pub const CHALLENGE_TTL_SECS: u64 = 300;
+
+// Seconds a session token is valid
+pub const SESSION_TOKEN_TTL_SECS: u64 = 3600;
+
+// Pending challenges kept in memory, in total and for each client IP
+pub const MAX_CHALLENGES: usize = 10_000;
+pub const MAX_CHALLENGES_PER_CLIENT: usize = 20;
+
+// Reasons why a session is rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionError {
+    MissingCredentials,
+    InvalidToken,
+    ExpiredToken,
+    InvalidChallenge,
+    TooManyChallenges,
+    Signature(SignatureError),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::MissingCredentials => {
+                write!(f, "Missing session token or request signature")
+            }
+            SessionError::InvalidToken => write!(f, "Invalid session token"),
+            SessionError::ExpiredToken => write!(f, "Session token expired"),
+            SessionError::InvalidChallenge => write!(f, "Unknown or expired challenge"),
+            SessionError::TooManyChallenges => write!(f, "Too many pending challenges"),
+            SessionError::Signature(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+// Builds the message the wallet signs to answer a sign-in challenge
+pub fn challenge_message(address: &str, nonce: &str) -> String {
+    format!(
+        "Sign in to the ink! compiler\nAddress: {}\nNonce: {}",
+        address, nonce
+    )
+}
+
+// Claims of the session token, the subject is the SS58 address of the wallet
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SessionClaims {
+    pub sub: String,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+// Keys signing and verifying the session tokens
+pub struct SessionKeys {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl SessionKeys {
+    pub fn new(secret: &[u8]) -> Self {
+        SessionKeys {
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+        }
+    }
+
+    // Uses the configured secret, the tokens of every instance must be signed with the same one
+    pub fn from_config(config: &AuthConfig) -> Result<Self, String> {
+        match &config.session_token_secret {
+            Some(secret) if !secret.is_empty() => Ok(SessionKeys::new(secret.as_bytes())),
+            _ => Err(String::from("auth.session_token_secret is not set")),
+        }
+    }
+
+    // Issues a token for the address valid for SESSION_TOKEN_TTL_SECS
+    pub fn issue(&self, address: &str, now: u64) -> Result<(String, u64), SessionError> {
+        let claims = SessionClaims {
+            sub: address.to_string(),
+            iat: now,
+            exp: now + SESSION_TOKEN_TTL_SECS,
+        };
+
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map(|token| (token, claims.exp))
+            .map_err(|err| {
+                error!(target: "compiler", "Error encoding session token: {}", err);
+                SessionError::InvalidToken
+            })
+    }
+
+    // Verifies the token and returns the address it was issued for
+    pub fn verify(&self, token: &str, now: u64) -> Result<String, SessionError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        // Expiration is checked below against the given time
+        validation.validate_exp = false;
+        validation.set_required_spec_claims(&["exp", "sub"]);
+
+        let claims = decode::<SessionClaims>(token, &self.decoding_key, &validation)
+            .map_err(|_| SessionError::InvalidToken)?
+            .claims;
+
+        if claims.exp <= now {
+            return Err(SessionError::ExpiredToken);
+        }
+
+        Ok(claims.sub)
+    }
+}
+
+// Challenge waiting for the signature of the wallet
+pub struct PendingChallenge {
+    address: String,
+    // IP of the client that asked for the challenge
+    client: Option<IpAddr>,
+    expires_at: u64,
+}
+
+// Store of the pending sign-in challenges, indexed by nonce
+#[derive(Default)]
+pub struct ChallengeStore {
+    pub challenges: Mutex<HashMap<String, PendingChallenge>>,
+}
+
+impl ChallengeStore {
+    pub fn new() -> Self {
+        ChallengeStore::default()
+    }
+
+    // Creates a new challenge for the address, asked by the client
+    pub fn create(
+        &self,
+        address: &str,
+        client: Option<IpAddr>,
+        now: u64,
+    ) -> Result<AuthChallenge, SessionError> {
+        let mut challenges = self.challenges.lock().map_err(|_| {
+            error!(target: "compiler", "Error locking challenge store");
+            SessionError::InvalidChallenge
+        })?;
+
+        // Challenges not answered in time are discarded
+        challenges.retain(|_, challenge| challenge.expires_at > now);
+
+        // Each client must answer or let expire its pending challenges before asking for more
+        let pending = challenges
+            .values()
+            .filter(|challenge| challenge.client == client)
+            .count();
+        if pending >= MAX_CHALLENGES_PER_CLIENT {
+            return Err(SessionError::TooManyChallenges);
+        }
+        // A full store drops its oldest challenge, so it never blocks the sign-in of other clients
+        if challenges.len() >= MAX_CHALLENGES {
+            let oldest = challenges
+                .iter()
+                .min_by_key(|(_, challenge)| challenge.expires_at)
+                .map(|(nonce, _)| nonce.clone());
+            if let Some(oldest) = oldest {
+                challenges.remove(&oldest);
+            }
+        }
+
+        let nonce = Uuid::new_v4().to_string();
+        let expires_at = now + CHALLENGE_TTL_SECS;
+        challenges.insert(
+            nonce.clone(),
+            PendingChallenge {
+                address: address.to_string(),
+                client,
+                expires_at,
+            },
+        );
+
+        Ok(AuthChallenge {
+            address: address.to_string(),
+            message: challenge_message(address, &nonce),
+            nonce,
+            expires_at,
+        })
+    }
+
+    // Consumes the challenge, failing if it was not issued for the address or it expired
+    pub fn take(&self, address: &str, nonce: &str, now: u64) -> Result<(), SessionError> {
+        let mut challenges = self.challenges.lock().map_err(|_| {
+            error!(target: "compiler", "Error locking challenge store");
+            SessionError::InvalidChallenge
+        })?;
+
+        match challenges.remove(nonce) {
+            Some(challenge) if challenge.address == address && challenge.expires_at > now => Ok(()),
+            _ => Err(SessionError::InvalidChallenge),
+        }
+    }
+}
+
+// Address authenticated by a session token or by a signed request
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatedAddress(pub String);
+
+impl AuthenticatedAddress {
+    // Checks the authenticated wallet owns the given address, it can be encoded with another network prefix
+    pub fn check_owns<T>(&self, address: &str) -> Result<(), Custom<Json<ServerResponse<T>>>> {
+        SignedAddress(self.0.clone()).check_owns(address)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthenticatedAddress {
+    type Error = SessionError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        // Requests without a session token can still be signed individually
+        let authorization = match request.headers().get_one("Authorization") {
+            Some(authorization) => authorization,
+            None if request.headers().contains(SIGNATURE_ADDRESS_HEADER) => {
+                return match request.guard::<SignedAddress>().await {
+                    Outcome::Success(signed_address) => {
+                        Outcome::Success(AuthenticatedAddress(signed_address.0))
+                    }
+                    Outcome::Error((status, err)) => {
+                        Outcome::Error((status, SessionError::Signature(err)))
+                    }
+                    Outcome::Forward(status) => Outcome::Forward(status),
+                };
+            }
+            None => {
+                request.local_cache(|| Some(SessionError::MissingCredentials));
+                return Outcome::Error((Status::Unauthorized, SessionError::MissingCredentials));
+            }
+        };
+
+        let keys = match request.guard::<&State<SessionKeys>>().await {
+            Outcome::Success(keys) => keys,
+            _ => {
+                error!(target: "compiler", "Session keys are not managed");
+                return Outcome::Error((Status::InternalServerError, SessionError::InvalidToken));
+            }
+        };
+
+        let verified = match authorization.strip_prefix("Bearer ") {
+            Some(token) => keys.verify(token.trim(), unix_timestamp()),
+            None => Err(SessionError::InvalidToken),
+        };
+
+        match verified {
+            Ok(address) => {
+                info!(target: "compiler", "Request authenticated by session of {}", address);
+                Outcome::Success(AuthenticatedAddress(address))
+            }
+            Err(err) => {
+                error!(target: "compiler", "Rejecting session: {}", err);
+                request.local_cache(|| Some(err.clone()));
+                Outcome::Error((Status::Unauthorized, err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/session_tests.rs"]
+mod session_test;
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wasmparser::{DataKind, ExternalKind, KnownCustom, Name, Operator, Parser, Payload, TypeRef};
+
+// Number of functions and data segments listed in the report
+pub const SIZE_REPORT_ENTRIES: usize = 10;
+
+// Sizes of the wasm of a contract, the upload deposit depends on the optimized size
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SizeReport {
+    // Size before wasm-opt, only known right after the compilation
+    pub original_size: Option<usize>,
+    pub optimized_size: usize,
+    // Largest function bodies and data segments of the optimized wasm
+    pub functions: Vec<FunctionSize>,
+    pub data_segments: Vec<DataSegmentSize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FunctionSize {
+    pub index: u32,
+    // From the name section when the debug names are kept, or from the exports
+    pub name: Option<String>,
+    pub size: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DataSegmentSize {
+    pub index: u32,
+    // Memory offset of the active segments initialized with a constant
+    pub offset: Option<i64>,
+    pub size: usize,
+}
+
+// Builds the report of the optimized wasm, the original one is the output of the linker
+pub fn size_report(original: Option<&[u8]>, optimized: &[u8]) -> Result<SizeReport, String> {
+    let mut imported_functions = 0;
+    let mut functions = Vec::new();
+    let mut data_segments = Vec::new();
+    let mut names = HashMap::new();
+    let mut export_names = HashMap::new();
+
+    for payload in Parser::new(0).parse_all(optimized) {
+        match payload.map_err(|err| format!("Invalid wasm: {}", err))? {
+            Payload::ImportSection(imports) => {
+                for import in imports {
+                    let import = import.map_err(|err| err.to_string())?;
+                    if matches!(import.ty, TypeRef::Func(_) | TypeRef::FuncExact(_)) {
+                        imported_functions += 1;
+                    }
+                }
+            }
+            Payload::ExportSection(exports) => {
+                for export in exports {
+                    let export = export.map_err(|err| err.to_string())?;
+                    if export.kind == ExternalKind::Func {
+                        export_names.insert(export.index, export.name.to_string());
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => functions.push(FunctionSize {
+                index: imported_functions + functions.len() as u32,
+                name: None,
+                size: body.range().len(),
+            }),
+            Payload::DataSection(segments) => {
+                for segment in segments {
+                    let segment = segment.map_err(|err| err.to_string())?;
+                    let offset = match segment.kind {
+                        DataKind::Active { offset_expr, .. } => {
+                            match offset_expr.get_operators_reader().read() {
+                                Ok(Operator::I32Const { value }) => Some(i64::from(value)),
+                                Ok(Operator::I64Const { value }) => Some(value),
+                                _ => None,
+                            }
+                        }
+                        DataKind::Passive => None,
+                    };
+                    data_segments.push(DataSegmentSize {
+                        index: data_segments.len() as u32,
+                        offset,
+                        size: segment.data.len(),
+                    });
+                }
+            }
+            Payload::CustomSection(section) => {
+                if let KnownCustom::Name(name_section) = section.as_known() {
+                    for name in name_section.into_iter().flatten() {
+                        if let Name::Function(function_names) = name {
+                            for naming in function_names.into_iter().flatten() {
+                                names.insert(naming.index, naming.name.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for function in functions.iter_mut() {
+        function.name = names
+            .remove(&function.index)
+            .or_else(|| export_names.remove(&function.index));
+    }
+    functions.sort_by_key(|function| std::cmp::Reverse(function.size));
+    functions.truncate(SIZE_REPORT_ENTRIES);
+    data_segments.sort_by_key(|segment| std::cmp::Reverse(segment.size));
+    data_segments.truncate(SIZE_REPORT_ENTRIES);
+
+    Ok(SizeReport {
+        original_size: original.map(<[u8]>::len),
+        optimized_size: optimized.len(),
+        functions,
+        data_segments,
+    })
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/size_report_tests.rs"]
+mod size_report_test;
@@ -0,0 +1,515 @@
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::ink_metadata::{InkMetadata, TypeDef, TypeRegistry};
+
+// Nesting resolved when describing an encoding, the registry can contain recursive types
+const MAX_ENCODING_DEPTH: usize = 16;
+
+// Storage of a contract, one cell per root key with the values packed under it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StorageLayout {
+    pub cells: Vec<StorageCell>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StorageCell {
+    // Path of the Mapping or Lazy field owning the root key, empty for the contract root
+    pub path: String,
+    pub root_key: String,
+    // Type of the Mapping or Lazy, only written by ink 5
+    pub type_name: Option<String>,
+    // Fields SCALE encoded in order under the root key
+    pub fields: Vec<StorageField>,
+    #[serde(skip)]
+    encoding: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StorageField {
+    pub path: String,
+    pub type_name: String,
+    // Shape of the SCALE encoding, the names of the types and fields are not encoded
+    #[serde(skip)]
+    encoding: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Impact {
+    Safe,
+    Warning,
+    Breaking,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Renamed,
+    Reordered,
+    TypeChanged,
+    RootKeyChanged,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LayoutChange {
+    pub impact: Impact,
+    pub kind: ChangeKind,
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Compatible,
+    CompatibleWithWarnings,
+    Incompatible,
+}
+
+// Result of upgrading the code of a contract with set_code_hash, from one layout to the other
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UpgradeCompatibility {
+    pub verdict: Verdict,
+    pub changes: Vec<LayoutChange>,
+    pub from: StorageLayout,
+    pub to: StorageLayout,
+}
+
+// Storage layout section of the ink metadata
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum InkLayout {
+    Leaf {
+        ty: u32,
+    },
+    Root {
+        root_key: String,
+        layout: Box<InkLayout>,
+        #[serde(default)]
+        ty: Option<u32>,
+    },
+    Array {
+        len: u32,
+        layout: Box<InkLayout>,
+    },
+    Struct {
+        name: String,
+        fields: Vec<InkFieldLayout>,
+    },
+    Enum {
+        name: String,
+        variants: BTreeMap<u8, InkVariantLayout>,
+    },
+    Hash(IgnoredAny),
+}
+
+#[derive(Deserialize)]
+struct InkFieldLayout {
+    name: String,
+    layout: InkLayout,
+}
+
+#[derive(Deserialize)]
+struct InkVariantLayout {
+    #[serde(default)]
+    fields: Vec<InkFieldLayout>,
+}
+
+// Reads the storage cells of a contract from its ink 4 or ink 5 metadata
+pub fn storage_layout(metadata: &Value) -> Result<StorageLayout, String> {
+    let registry = InkMetadata::from_value(metadata)?.registry();
+    let layout: InkLayout = serde_json::from_value(metadata["storage"].clone())
+        .map_err(|err| format!("Invalid storage layout: {}", err))?;
+
+    let mut cells = Vec::new();
+    match &layout {
+        InkLayout::Root { .. } => add_fields(&registry, &layout, "", None, &mut cells)?,
+        _ => return Err(String::from("Invalid storage layout: missing root key")),
+    }
+    Ok(StorageLayout { cells })
+}
+
+// Compares the layouts of the metadata of the running code and of the new code
+pub fn check_upgrade(
+    from_metadata: &Value,
+    to_metadata: &Value,
+) -> Result<UpgradeCompatibility, String> {
+    let from = storage_layout(from_metadata)?;
+    let to = storage_layout(to_metadata)?;
+    let changes = compare_layouts(&from, &to);
+    let has_impact = |impact: Impact| changes.iter().any(|change| change.impact == impact);
+    let verdict = if has_impact(Impact::Breaking) {
+        Verdict::Incompatible
+    } else if has_impact(Impact::Warning) {
+        Verdict::CompatibleWithWarnings
+    } else {
+        Verdict::Compatible
+    };
+
+    Ok(UpgradeCompatibility {
+        verdict,
+        changes,
+        from,
+        to,
+    })
+}
+
+// Changes of the cells, matched by the path of the field owning them
+pub fn compare_layouts(from: &StorageLayout, to: &StorageLayout) -> Vec<LayoutChange> {
+    let mut changes = Vec::new();
+    for cell in &to.cells {
+        match from.cells.iter().find(|old| old.path == cell.path) {
+            Some(old) => compare_cells(old, cell, &mut changes),
+            None => match from.cells.iter().find(|old| {
+                old.root_key == cell.root_key && !to.cells.iter().any(|new| new.path == old.path)
+            }) {
+                // The fields of the cell are renamed with it
+                Some(old) => {
+                    changes.push(change(
+                        Impact::Safe,
+                        ChangeKind::Renamed,
+                        &cell.path,
+                        format!("{} is renamed to {}.", cell_name(&old.path), cell.path),
+                    ));
+                    let renamed = StorageCell {
+                        fields: old
+                            .fields
+                            .iter()
+                            .map(|field| StorageField {
+                                path: field.path.replacen(&old.path, &cell.path, 1),
+                                ..field.clone()
+                            })
+                            .collect(),
+                        ..old.clone()
+                    };
+                    compare_cells(&renamed, cell, &mut changes);
+                }
+                None => changes.push(change(
+                    Impact::Safe,
+                    ChangeKind::Added,
+                    &cell.path,
+                    format!(
+                        "{} is added under the new root key {}.",
+                        cell.path, cell.root_key
+                    ),
+                )),
+            },
+        }
+    }
+
+    for old in &from.cells {
+        let kept = to
+            .cells
+            .iter()
+            .any(|cell| cell.path == old.path || cell.root_key == old.root_key);
+        if !kept {
+            changes.push(change(
+                Impact::Warning,
+                ChangeKind::Removed,
+                &old.path,
+                format!(
+                    "{} is removed, the values stored under the root key {} are no longer reachable.",
+                    cell_name(&old.path),
+                    old.root_key
+                ),
+            ));
+        }
+    }
+    changes
+}
+
+fn compare_cells(old: &StorageCell, new: &StorageCell, changes: &mut Vec<LayoutChange>) {
+    let name = cell_name(&new.path);
+    if old.root_key != new.root_key {
+        changes.push(change(
+            Impact::Breaking,
+            ChangeKind::RootKeyChanged,
+            &new.path,
+            format!(
+                "The root key of {} changes from {} to {}, the stored values are no longer read.",
+                name, old.root_key, new.root_key
+            ),
+        ));
+    }
+    if let (Some(old_encoding), Some(new_encoding)) = (&old.encoding, &new.encoding) {
+        if old_encoding != new_encoding {
+            changes.push(type_changed(
+                &new.path,
+                old.type_name.as_deref().unwrap_or_default(),
+                new.type_name.as_deref().unwrap_or_default(),
+            ));
+        }
+    }
+
+    // The packed values are decoded in order, same encodings only differ by the field names
+    let encodings = |cell: &StorageCell| -> Vec<String> {
+        cell.fields
+            .iter()
+            .map(|field| field.encoding.clone())
+            .collect()
+    };
+    let has_field = |cell: &StorageCell, path: &str| cell.fields.iter().any(|f| f.path == path);
+    if encodings(old) == encodings(new) {
+        for (old_field, new_field) in old.fields.iter().zip(&new.fields) {
+            if old_field.path == new_field.path {
+                continue;
+            }
+            match has_field(old, &new_field.path) {
+                true => changes.push(reordered(&new_field.path, &old_field.path)),
+                false => changes.push(change(
+                    Impact::Safe,
+                    ChangeKind::Renamed,
+                    &new_field.path,
+                    format!("{} is renamed to {}.", old_field.path, new_field.path),
+                )),
+            }
+        }
+        return;
+    }
+
+    for old_field in &old.fields {
+        if !has_field(new, &old_field.path) {
+            changes.push(change(
+                Impact::Breaking,
+                ChangeKind::Removed,
+                &old_field.path,
+                format!(
+                    "{} is removed from the packed storage of {}, the following values are read at the wrong offset.",
+                    old_field.path, name
+                ),
+            ));
+        }
+    }
+    let common = |cell: &StorageCell, other: &StorageCell| -> Vec<String> {
+        cell.fields
+            .iter()
+            .filter(|field| has_field(other, &field.path))
+            .map(|field| field.path.clone())
+            .collect()
+    };
+    let (old_order, new_order) = (common(old, new), common(new, old));
+    for (index, new_field) in new.fields.iter().enumerate() {
+        let Some(old_field) = old.fields.iter().find(|f| f.path == new_field.path) else {
+            changes.push(change(
+                Impact::Breaking,
+                ChangeKind::Added,
+                &new_field.path,
+                format!(
+                    "{} is added to the packed storage of {}, the stored values have no data for it.",
+                    new_field.path, name
+                ),
+            ));
+            continue;
+        };
+        if old_field.encoding != new_field.encoding {
+            changes.push(type_changed(
+                &new_field.path,
+                &old_field.type_name,
+                &new_field.type_name,
+            ));
+        }
+        let position = |order: &[String]| order.iter().position(|path| path == &new_field.path);
+        if position(&old_order) != position(&new_order) {
+            let replaced = old
+                .fields
+                .get(index)
+                .map_or("", |field| field.path.as_str());
+            changes.push(reordered(&new_field.path, replaced));
+        }
+    }
+}
+
+fn change(impact: Impact, kind: ChangeKind, path: &str, message: String) -> LayoutChange {
+    LayoutChange {
+        impact,
+        kind,
+        path: path.to_string(),
+        message,
+    }
+}
+
+fn type_changed(path: &str, old_type: &str, new_type: &str) -> LayoutChange {
+    change(
+        Impact::Breaking,
+        ChangeKind::TypeChanged,
+        path,
+        format!(
+            "The type of {} changes from {} to {}, the stored value can not be decoded.",
+            cell_name(path),
+            old_type,
+            new_type
+        ),
+    )
+}
+
+fn reordered(path: &str, replaced: &str) -> LayoutChange {
+    change(
+        Impact::Breaking,
+        ChangeKind::Reordered,
+        path,
+        format!(
+            "{} is moved, it would read the value stored for {}.",
+            path, replaced
+        ),
+    )
+}
+
+fn cell_name(path: &str) -> &str {
+    match path.is_empty() {
+        true => "the contract storage",
+        false => path,
+    }
+}
+
+// Adds the cell of a root layout and the cells of the roots nested in it
+fn add_fields(
+    registry: &TypeRegistry,
+    layout: &InkLayout,
+    path: &str,
+    cell: Option<usize>,
+    cells: &mut Vec<StorageCell>,
+) -> Result<(), String> {
+    let push = |cells: &mut Vec<StorageCell>, type_name: String, encoding: String| {
+        if let Some(cell) = cell {
+            cells[cell].fields.push(StorageField {
+                path: path.to_string(),
+                type_name,
+                encoding,
+            });
+        }
+    };
+
+    match layout {
+        InkLayout::Root {
+            root_key,
+            layout,
+            ty,
+        } => {
+            cells.push(StorageCell {
+                path: path.to_string(),
+                root_key: root_key.clone(),
+                type_name: ty.map(|ty| registry.type_name(ty)),
+                fields: Vec::new(),
+                encoding: ty.map(|ty| type_encoding(registry, ty, 0)),
+            });
+            let root = cells.len() - 1;
+            add_fields(registry, layout, path, Some(root), cells)?;
+        }
+        InkLayout::Struct { fields, .. } => {
+            for field in fields {
+                let field_path = match path.is_empty() {
+                    true => field.name.clone(),
+                    false => format!("{}.{}", path, field.name),
+                };
+                add_fields(registry, &field.layout, &field_path, cell, cells)?;
+            }
+        }
+        InkLayout::Leaf { ty } => {
+            push(
+                cells,
+                registry.type_name(*ty),
+                type_encoding(registry, *ty, 0),
+            );
+        }
+        // Enums and arrays are one packed value, their own roots are not tracked
+        InkLayout::Enum { .. } | InkLayout::Array { .. } => {
+            push(
+                cells,
+                layout_type_name(registry, layout),
+                layout_encoding(registry, layout),
+            );
+        }
+        InkLayout::Hash(_) => {
+            return Err(format!(
+                "Unsupported storage layout: hash layout of {}",
+                cell_name(path)
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn layout_type_name(registry: &TypeRegistry, layout: &InkLayout) -> String {
+    match layout {
+        InkLayout::Leaf { ty } => registry.type_name(*ty),
+        InkLayout::Struct { name, .. } | InkLayout::Enum { name, .. } => name.clone(),
+        InkLayout::Array { len, layout } => {
+            format!("[{}; {}]", layout_type_name(registry, layout), len)
+        }
+        InkLayout::Root { .. } | InkLayout::Hash(_) => String::new(),
+    }
+}
+
+// Encoding of a packed layout, the nested roots are not encoded in it
+fn layout_encoding(registry: &TypeRegistry, layout: &InkLayout) -> String {
+    let fields = |fields: &[InkFieldLayout]| -> String {
+        let encodings: Vec<String> = fields
+            .iter()
+            .filter(|field| !matches!(field.layout, InkLayout::Root { .. }))
+            .map(|field| layout_encoding(registry, &field.layout))
+            .collect();
+        format!("({})", encodings.join(","))
+    };
+
+    match layout {
+        InkLayout::Leaf { ty } => type_encoding(registry, *ty, 0),
+        InkLayout::Root { .. } | InkLayout::Hash(_) => String::new(),
+        InkLayout::Array { len, layout } => {
+            format!("[{};{}]", layout_encoding(registry, layout), len)
+        }
+        InkLayout::Struct {
+            fields: layouts, ..
+        } => fields(layouts),
+        InkLayout::Enum { variants, .. } => {
+            let variants: Vec<String> = variants
+                .iter()
+                .map(|(index, variant)| format!("{}:{}", index, fields(&variant.fields)))
+                .collect();
+            format!("enum{{{}}}", variants.join("|"))
+        }
+    }
+}
+
+// Encoding of a type of the registry, written as the encoding of the layouts
+fn type_encoding(registry: &TypeRegistry, id: u32, depth: usize) -> String {
+    let Ok(info) = registry.get(id) else {
+        return format!("unknown{}", id);
+    };
+    if depth > MAX_ENCODING_DEPTH {
+        return String::from("..");
+    }
+    let encoding = |id: u32| type_encoding(registry, id, depth + 1);
+    let tuple = |ids: Vec<u32>| -> String {
+        let encodings: Vec<String> = ids.into_iter().map(encoding).collect();
+        format!("({})", encodings.join(","))
+    };
+
+    match &info.def {
+        TypeDef::Primitive(primitive) => primitive.clone(),
+        TypeDef::Composite { fields } => tuple(fields.iter().map(|field| field.id).collect()),
+        TypeDef::Variant { variants } => {
+            let variants: Vec<String> = variants
+                .iter()
+                .map(|variant| {
+                    let fields = variant.fields.iter().map(|field| field.id).collect();
+                    format!("{}:{}", variant.index, tuple(fields))
+                })
+                .collect();
+            format!("enum{{{}}}", variants.join("|"))
+        }
+        TypeDef::Sequence { id } => format!("Vec<{}>", encoding(*id)),
+        TypeDef::Array { len, id } => format!("[{};{}]", encoding(*id), len),
+        TypeDef::Tuple(ids) => tuple(ids.clone()),
+        TypeDef::Compact { id } => format!("Compact<{}>", encoding(*id)),
+        TypeDef::BitSequence { .. } => String::from("BitVec"),
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/storage_layout_tests.rs"]
+mod storage_layout_test;
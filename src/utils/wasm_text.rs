@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Range;
+use wasmparser::{ExternalKind, KnownCustom, Name, Parser, Payload, TypeRef};
+
+use super::contract_abi::ContractAbi;
+
+// Text format of the wasm of a contract, with the ink entry points of its exports
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContractWat {
+    pub wat: String,
+    pub exports: Vec<WasmExport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WasmExport {
+    pub name: String,
+    pub function: u32,
+    // ink dispatches the constructors from deploy and the messages from call by their selector
+    pub selectors: Vec<EntryPoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EntryPoint {
+    pub label: String,
+    pub selector: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatError {
+    InvalidWasm(String),
+    FunctionNotFound(String),
+}
+
+// Functions of the wasm needed to filter the disassembly
+#[derive(Default)]
+struct WasmFunctions {
+    imported: u32,
+    // Byte range of the body of each defined function
+    bodies: Vec<Range<usize>>,
+    names: HashMap<u32, String>,
+    exports: Vec<(String, u32)>,
+}
+
+// Disassembles the wasm, only printing the given function when there is one
+// The function is given by its index, its name in the name section or its export name
+pub fn contract_wat(
+    wasm: &[u8],
+    metadata: &Value,
+    function: Option<&str>,
+) -> Result<ContractWat, WatError> {
+    let functions = read_functions(wasm).map_err(WatError::InvalidWasm)?;
+    let body = match function {
+        Some(function) => Some(
+            functions
+                .body(function)
+                .ok_or_else(|| WatError::FunctionNotFound(function.to_string()))?,
+        ),
+        None => None,
+    };
+
+    let mut storage = String::new();
+    let lines = wasmprinter::Config::new()
+        .offsets_and_lines(wasm, &mut storage)
+        .map_err(|err| WatError::InvalidWasm(err.to_string()))?;
+    let mut wat = String::new();
+    // Lines without an offset, like the closing parentheses, belong to the previous line
+    let mut selected = body.is_none();
+    for (offset, line) in lines {
+        if let (Some(offset), Some(body)) = (offset, &body) {
+            selected = body.start <= offset && offset < body.end;
+        }
+        if selected {
+            wat.push_str(line);
+        }
+    }
+
+    Ok(ContractWat {
+        wat,
+        exports: exports(&functions, metadata),
+    })
+}
+
+impl WasmFunctions {
+    fn body(&self, function: &str) -> Option<Range<usize>> {
+        let index = function.parse::<u32>().ok().or_else(|| {
+            self.names
+                .iter()
+                .find(|(_, name)| name.as_str() == function)
+                .map(|(index, _)| *index)
+                .or_else(|| {
+                    self.exports
+                        .iter()
+                        .find(|(name, _)| name == function)
+                        .map(|(_, index)| *index)
+                })
+        })?;
+        // Imported functions have no body
+        let defined = index.checked_sub(self.imported)?;
+        self.bodies.get(defined as usize).cloned()
+    }
+}
+
+fn read_functions(wasm: &[u8]) -> Result<WasmFunctions, String> {
+    let mut functions = WasmFunctions::default();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.map_err(|err| err.to_string())? {
+            Payload::ImportSection(imports) => {
+                for import in imports {
+                    let import = import.map_err(|err| err.to_string())?;
+                    if matches!(import.ty, TypeRef::Func(_) | TypeRef::FuncExact(_)) {
+                        functions.imported += 1;
+                    }
+                }
+            }
+            Payload::ExportSection(exports) => {
+                for export in exports {
+                    let export = export.map_err(|err| err.to_string())?;
+                    if export.kind == ExternalKind::Func {
+                        functions
+                            .exports
+                            .push((export.name.to_string(), export.index));
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => functions.bodies.push(body.range()),
+            Payload::CustomSection(section) => {
+                if let KnownCustom::Name(name_section) = section.as_known() {
+                    for name in name_section.into_iter().flatten() {
+                        if let Name::Function(function_names) = name {
+                            for naming in function_names.into_iter().flatten() {
+                                functions
+                                    .names
+                                    .insert(naming.index, naming.name.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(functions)
+}
+
+// Exported functions, deploy and call are mapped to the selectors of the metadata when it can be read
+fn exports(functions: &WasmFunctions, metadata: &Value) -> Vec<WasmExport> {
+    let abi = ContractAbi::from_metadata(metadata).ok();
+    functions
+        .exports
+        .iter()
+        .map(|(name, index)| {
+            let selectors = match (name.as_str(), &abi) {
+                ("deploy", Some(abi)) => abi
+                    .constructors
+                    .iter()
+                    .map(|constructor| EntryPoint {
+                        label: constructor.label.clone(),
+                        selector: constructor.selector.clone(),
+                    })
+                    .collect(),
+                ("call", Some(abi)) => abi
+                    .messages
+                    .iter()
+                    .map(|message| EntryPoint {
+                        label: message.label.clone(),
+                        selector: message.selector.clone(),
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            WasmExport {
+                name: name.clone(),
+                function: *index,
+                selectors,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/wasm_text_tests.rs"]
+mod wasm_text_test;
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use wasmparser::{Parser, Payload, TypeRef, Validator, WasmFeatures};
+
+use super::network_registry::{Network, NetworkRegistry};
+
+// Memory pages of 64KiB a contract can import, the default limit of pallet-contracts
+pub const MAX_MEMORY_PAGES: u64 = 16;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+// Rule of pallet-contracts checked on the wasm
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmCheck {
+    Parse,
+    FloatInstructions,
+    Imports,
+    Memory,
+    StartFunction,
+    CodeSize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WasmIssue {
+    pub severity: Severity,
+    pub check: WasmCheck,
+    pub message: String,
+}
+
+// Issues found in the wasm of a contract, any error means the chain would reject the code
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct WasmValidation {
+    pub issues: Vec<WasmIssue>,
+}
+
+impl WasmValidation {
+    pub fn is_valid(&self) -> bool {
+        !self
+            .issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error)
+    }
+
+    // Message listing the errors, returned when the contract is rejected
+    pub fn error_message(&self) -> String {
+        let errors: Vec<&str> = self
+            .issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .map(|issue| issue.message.as_str())
+            .collect();
+        format!(
+            "Contract rejected by the wasm validation: {}",
+            errors.join(" ")
+        )
+    }
+
+    fn push(&mut self, severity: Severity, check: WasmCheck, message: String) {
+        self.issues.push(WasmIssue {
+            severity,
+            check,
+            message,
+        });
+    }
+}
+
+// Checks the wasm against the rules pallet-contracts applies when the code is uploaded
+// The code size is checked against the target network, or without one, a warning lists the
+// networks whose limit is exceeded
+pub fn validate_wasm(
+    wasm: &[u8],
+    networks: &NetworkRegistry,
+    network: Option<&Network>,
+) -> WasmValidation {
+    let mut validation = WasmValidation::default();
+    check_code_size(wasm.len(), networks, network, &mut validation);
+
+    if let Err(err) = Validator::new().validate_all(wasm) {
+        validation.push(
+            Severity::Error,
+            WasmCheck::Parse,
+            format!("Invalid wasm: {}.", err),
+        );
+        return validation;
+    }
+
+    // The chain requires deterministic code, the validator stops at the first float instruction
+    let deterministic = WasmFeatures::default().difference(WasmFeatures::FLOATS);
+    if let Err(err) = Validator::new_with_features(deterministic).validate_all(wasm) {
+        validation.push(
+            Severity::Error,
+            WasmCheck::FloatInstructions,
+            format!("Floating-point instructions are not allowed: {}.", err),
+        );
+    }
+
+    let mut imported_memory = None;
+    for payload in Parser::new(0).parse_all(wasm).flatten() {
+        match payload {
+            Payload::ImportSection(imports) => {
+                for import in imports.into_iter().flatten() {
+                    match import.ty {
+                        TypeRef::Func(_) if is_seal_module(import.module) => {}
+                        TypeRef::Memory(memory)
+                            if import.module == "env" && import.name == "memory" =>
+                        {
+                            imported_memory = Some(memory);
+                        }
+                        _ => validation.push(
+                            Severity::Error,
+                            WasmCheck::Imports,
+                            format!(
+                                "Import {}.{} is not allowed, contracts can only import seal functions and env.memory.",
+                                import.module, import.name
+                            ),
+                        ),
+                    }
+                }
+            }
+            Payload::MemorySection(_) => validation.push(
+                Severity::Error,
+                WasmCheck::Memory,
+                String::from("Contracts must import their memory from env.memory, not define it."),
+            ),
+            Payload::StartSection { func, .. } => validation.push(
+                Severity::Error,
+                WasmCheck::StartFunction,
+                format!(
+                    "Contracts must not have a start function, found function {}.",
+                    func
+                ),
+            ),
+            _ => {}
+        }
+    }
+
+    match imported_memory.map(|memory| memory.maximum) {
+        None => validation.push(
+            Severity::Error,
+            WasmCheck::Memory,
+            String::from("The memory is not imported from env.memory."),
+        ),
+        Some(None) => validation.push(
+            Severity::Error,
+            WasmCheck::Memory,
+            String::from("The imported memory has no maximum number of pages."),
+        ),
+        Some(Some(maximum)) if maximum > MAX_MEMORY_PAGES => validation.push(
+            Severity::Error,
+            WasmCheck::Memory,
+            format!(
+                "The imported memory has a maximum of {} pages, the limit is {}.",
+                maximum, MAX_MEMORY_PAGES
+            ),
+        ),
+        Some(Some(_)) => {}
+    }
+
+    validation
+}
+
+// Host functions are imported from the versioned seal0, seal1, ... modules
+fn is_seal_module(module: &str) -> bool {
+    module
+        .strip_prefix("seal")
+        .is_some_and(|version| !version.is_empty() && version.bytes().all(|c| c.is_ascii_digit()))
+}
+
+fn check_code_size(
+    code_size: usize,
+    networks: &NetworkRegistry,
+    network: Option<&Network>,
+    validation: &mut WasmValidation,
+) {
+    match network {
+        Some(network) if code_size > network.max_code_size => validation.push(
+            Severity::Error,
+            WasmCheck::CodeSize,
+            format!(
+                "Code size of {} bytes exceeds the {} limit of {} bytes.",
+                code_size, network.name, network.max_code_size
+            ),
+        ),
+        Some(_) => {}
+        None => {
+            let exceeded: Vec<&str> = networks
+                .networks
+                .iter()
+                .filter(|network| code_size > network.max_code_size)
+                .map(|network| network.name.as_str())
+                .collect();
+            if !exceeded.is_empty() {
+                validation.push(
+                    Severity::Warning,
+                    WasmCheck::CodeSize,
+                    format!(
+                        "Code size of {} bytes exceeds the limit of {}.",
+                        code_size,
+                        exceeded.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/utils/wasm_validation_tests.rs"]
+mod wasm_validation_test;
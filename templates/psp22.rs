@@ -0,0 +1,43 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+#[openbrush::contract]
+pub mod {{module}} {
+    // imports from openbrush
+    use openbrush::contracts::psp22::extensions::metadata::*;
+    {{imports}}
+    use openbrush::traits::Storage;
+    use openbrush::traits::String;
+
+    /// {{name}}
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct Contract {
+        #[storage_field]
+        psp22: psp22::Data,
+        #[storage_field]
+        metadata: metadata::Data,
+        {{storage}}
+    }
+
+    // Section contains default implementation without any modifications
+    impl PSP22 for Contract {}
+    impl PSP22Metadata for Contract {}
+    {{impls}}
+
+    impl Contract {
+        #[ink(constructor)]
+        pub fn new({{constructor_args}}) -> Self {
+            let mut _instance = Self::default();
+            _instance
+                ._mint_to({{owner}}, {{initial_supply}})
+                .expect("Should mint");
+            _instance.metadata.name = Some(String::from("{{name}}"));
+            _instance.metadata.symbol = {{symbol}};
+            _instance.metadata.decimals = {{decimals}};
+            {{init}}
+            _instance
+        }
+        {{messages}}
+    }
+}
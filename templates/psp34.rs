@@ -0,0 +1,39 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+#[openbrush::contract]
+pub mod {{module}} {
+    // imports from openbrush
+    use openbrush::contracts::psp34::extensions::metadata::*;
+    {{imports}}
+    use openbrush::traits::Storage;
+    use openbrush::traits::String;
+
+    /// {{name}}
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct Contract {
+        #[storage_field]
+        psp34: psp34::Data,
+        #[storage_field]
+        metadata: metadata::Data,
+        {{storage}}
+    }
+
+    // Section contains default implementation without any modifications
+    impl PSP34 for Contract {}
+    impl PSP34Metadata for Contract {}
+    {{impls}}
+
+    impl Contract {
+        #[ink(constructor)]
+        pub fn new({{constructor_args}}) -> Self {
+            let mut _instance = Self::default();
+            let collection_id = _instance.collection_id();
+            {{attributes}}
+            {{init}}
+            _instance
+        }
+        {{messages}}
+    }
+}
@@ -0,0 +1,33 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+#[openbrush::contract]
+pub mod {{module}} {
+    // imports from openbrush
+    use openbrush::contracts::psp37::*;
+    {{imports}}
+    use openbrush::traits::Storage;
+
+    /// {{name}}
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct Contract {
+        #[storage_field]
+        psp37: psp37::Data,
+        {{storage}}
+    }
+
+    // Section contains default implementation without any modifications
+    impl PSP37 for Contract {}
+    {{impls}}
+
+    impl Contract {
+        #[ink(constructor)]
+        pub fn new({{constructor_args}}) -> Self {
+            let mut _instance = Self::default();
+            {{init}}
+            _instance
+        }
+        {{messages}}
+    }
+}